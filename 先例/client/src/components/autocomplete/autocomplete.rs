@@ -1,141 +1,71 @@
-// use std::{cell::RefCell, rc::Rc};
+use dioxus::prelude::*;
+use keyboard_types::Code;
 
-// use dioxus::prelude::*;
+use crate::components::autocomplete::autocomplete_list::AutocompleteList;
+use crate::components::autocomplete::autocomplete_option::AutocompleteOption;
+use crate::components::autocomplete::use_autocomplete::filter_options;
 
-// #[derive(PartialEq, Clone, Debug)]
-// struct AutocompleteProps<'a> {
-//     options: Vec<&'a str>,
-//     placeholder: &'a str,
-//     on_select: EventHandler<String>,
-// }
+/// 入力欄と候補一覧。入力に合わせて候補をあいまい検索で絞り込む
+/// ↑↓ で候補を選び、Enter で決定、Esc で閉じる
+#[component]
+pub fn Autocomplete(
+    options: Vec<AutocompleteOption>,
+    #[props(into, default)] placeholder: String,
+    on_select: EventHandler<AutocompleteOption>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut input_value = use_signal(String::new);
+    let mut active = use_signal(|| 0usize);
 
-// // impl<'a> Properties for AutocompleteProps<'a> {
-// //     // implement required methods
-// // }
+    let matches = filter_options(&options, &input_value.read());
+    let count = matches.len();
+    let current = active().min(count.saturating_sub(1));
+    let selected = matches.get(current).map(|m| m.option.clone());
 
-// #[component]
-// // pub fn Autocomplete(props: AutocompleteProps) -> Element {
-// //     // このstateはcontextにする
-// //     let input_value = use_signal(|| String::new());
-// //     let filtered_options = props
-// //         .options
-// //         .iter()
-// //         .filter(|option| option.contains(input_value.read().as_str()))
-// //         .cloned()
-// //         .collect::<Vec<_>>();
+    // 入力欄のキー入力はエディタに渡さない
+    let handle_keys = move |event: Event<KeyboardData>| {
+        event.stop_propagation();
+        match event.code() {
+            Code::ArrowDown if count > 0 => {
+                event.prevent_default();
+                active.set((current + 1) % count);
+            }
+            Code::ArrowUp if count > 0 => {
+                event.prevent_default();
+                active.set((current + count - 1) % count);
+            }
+            Code::Enter => {
+                if let Some(option) = selected.clone() {
+                    on_select.call(option);
+                }
+            }
+            Code::Escape => on_close.call(()),
+            _ => (),
+        }
+    };
 
-// //     rsx! {
-// //       div {
-// //         class: "autocomplete-container",
-// //         style: "position: relative; width: 100%;",
-// //         // input {
-// //         //     class: "autocomplete-input",
-// //         //     style: "width: 100%; padding: 8px; border: 1px solid #ccc; border-radius: 4px;",
-// //         //     placeholder: "{cx.props.placeholder}",
-// //         //     value: "{input_value}",
-// //         //     // oninput: move |evt| input_value.set(evt.value.clone()),
-// //         // }
-// //         // if !filtered_options.is_empty() {
-// //         //     ul {
-// //         //         class: "autocomplete-dropdown",
-// //         //         style: "position: absolute; top: 100%; left: 0; right: 0; background: white; border: 1px solid #ccc; border-radius: 4px; max-height: 150px; overflow-y: auto; z-index: 1000;",
-// //         //         filtered_options.iter().map(|option| rsx!(
-// //         //             li {
-// //         //                 class: "autocomplete-item",
-// //         //                 style: "padding: 8px; cursor: pointer;",
-// //         //                 onclick: move |_| {
-// //         //                     props.on_select.call(option.to_string());
-// //         //                     input_value.set(option.to_string());
-// //         //                 },
-// //         //                 "{option}"
-// //         //             }
-// //         //         ))
-// //         //     }
-// //         // }
-// //         "あああああああああああ"
-// //       }
-// //     }
-// // }
-// #[component]
-// pub fn Autocomplete() -> Element {
-//     let mut input_value = use_signal(|| "".to_string());
-//     let suggestions: Signal<Vec<&str>> = use_signal(|| vec!["Apple", "Banana", "Cherry"]);
-//     // let mut filtered_suggestions: Signal<Vec<&str>> = use_signal(|| vec![]);
-//     let filtered_suggestions = Rc::new(RefCell::new(vec![]));
-
-//     // let on_input = move |event: FormEvent| {
-//     //     let query = event.value().clone();
-//     //     input_value.set(query.clone());
-//     //     filtered_suggestions.set(
-//     //         suggestions
-//     //             .read()
-//     //             .iter()
-//     //             .filter(|&&item| item.to_lowercase().contains(&query.to_lowercase()))
-//     //             .cloned() // 元の&strをクローン
-//     //             .collect(),
-//     //     );
-//     // };
-//     let on_input = {
-//         // let filtered_suggestions = Rc::clone(&filtered_suggestions);
-//         // let suggestions = Rc::clone(&suggestions);
-
-//         move |event: FormEvent| {
-//             let query = event.value().clone();
-//             input_value.set(query.clone());
-
-//             let filtered: Vec<&str> = suggestions
-//                 .read()
-//                 .iter()
-//                 .filter(|&&item| item.to_lowercase().contains(&query.to_lowercase()))
-//                 .cloned() // 元の&strをクローン
-//                 .collect();
-//             // .iter()
-//             // .filter(|&&item| item.to_lowercase().contains(&query.to_lowercase()))
-//             // .cloned()
-//             // .collect();
-//             // filtered_suggestions.set(
-//             //     suggestions
-//             //         .read()
-//             //         .iter()
-//             //         .filter(|&&item| item.to_lowercase().contains(&query.to_lowercase()))
-//             //         .cloned() // 元の&strをクローン
-//             //         .collect(),
-//             // );
-
-//             filtered_suggestions.borrow_mut().clear();
-//             filtered_suggestions.borrow_mut().extend(filtered);
-//         }
-//     };
-
-//     // let mut on_select = move |selection| {
-//     //     input_value.set(selection);
-//     //     filtered_suggestions.set(vec![]); // Clear suggestions
-//     // };
-//     let on_select = {
-//         move |selection: &str| {
-//             input_value.set(selection.to_string());
-//             filtered_suggestions.borrow_mut().clear(); // Clear suggestions
-//         }
-//     };
-
-//     rsx!(
-//       div {
-//         h1 { "Autocomplete Example" }
-//         input {
-//           value: "{input_value}",
-//           oninput: on_input,
-//           placeholder: "Type to search..."
-//         }
-//         ul {
-//           {filtered_suggestions.borrow().iter().map(|&suggestion| rsx!{
-//               li {
-//                   key: "{suggestion}",
-//                   onclick: move |_| on_select(suggestion),
-//                   "{suggestion}"
-//               }
-//             }
-//           )}
-//         }
-//       }
-//     )
-// }
+    rsx! {
+        div {
+            input {
+                style: "width: 100%; box-sizing: border-box; padding: 6px 8px; border: none; border-bottom: 1px solid #ddd; outline: none;",
+                placeholder: "{placeholder}",
+                value: "{input_value}",
+                onmounted: move |event| async move {
+                    let _ = event.set_focus(true).await;
+                },
+                oninput: move |event| {
+                    input_value.set(event.value());
+                    active.set(0);
+                },
+                onkeydown: handle_keys,
+                onblur: move |_| on_close.call(()),
+            }
+            AutocompleteList {
+                matches,
+                active: current,
+                on_select,
+                on_hover: move |idx| active.set(idx),
+            }
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use dioxus::prelude::*;
+
+/// 一致する候補がない場合の表示
+#[component]
+pub fn AutocompleteEmpty() -> Element {
+    rsx! {
+        div { style: "padding: 4px 8px; color: #888;", "一致する項目がありません" }
+    }
+}
@@ -1 +1,36 @@
+use dioxus::prelude::*;
 
+use crate::components::autocomplete::autocomplete_empty::AutocompleteEmpty;
+use crate::components::autocomplete::autocomplete_option::{
+    AutocompleteOption, AutocompleteOptionItem,
+};
+use crate::components::autocomplete::use_autocomplete::AutocompleteMatch;
+
+/// 絞り込んだ候補の一覧
+#[component]
+pub fn AutocompleteList(
+    matches: Vec<AutocompleteMatch>,
+    active: usize,
+    on_select: EventHandler<AutocompleteOption>,
+    on_hover: EventHandler<usize>,
+) -> Element {
+    if matches.is_empty() {
+        return rsx! {
+            AutocompleteEmpty {}
+        };
+    }
+
+    rsx! {
+        ul { style: "list-style: none; margin: 0; padding: 0; max-height: 300px; overflow-y: auto;",
+            for (idx, item) in matches.into_iter().enumerate() {
+                AutocompleteOptionItem {
+                    key: "{item.option.value}",
+                    item,
+                    active: idx == active,
+                    on_select,
+                    on_hover: move |_| on_hover.call(idx),
+                }
+            }
+        }
+    }
+}
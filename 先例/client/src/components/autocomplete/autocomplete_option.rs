@@ -0,0 +1,51 @@
+use dioxus::prelude::*;
+
+use crate::components::autocomplete::use_autocomplete::{highlight_segments, AutocompleteMatch};
+
+/// Autocompleteの候補
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutocompleteOption {
+    /// 選択時に呼び出し側が使う値。絞り込みにも使う
+    pub value: String,
+    /// 表示名
+    pub label: String,
+    /// 右側に表示する補足 (キーバインドなど)
+    pub detail: String,
+}
+
+/// 候補1件。一致した文字を太字にする
+#[component]
+pub fn AutocompleteOptionItem(
+    item: AutocompleteMatch,
+    active: bool,
+    on_select: EventHandler<AutocompleteOption>,
+    on_hover: EventHandler<()>,
+) -> Element {
+    let background = if active {
+        "background-color: #dbeafe;"
+    } else {
+        "background-color: white;"
+    };
+    let segments = highlight_segments(&item.option.label, &item.positions);
+    let option = item.option.clone();
+
+    rsx! {
+        li {
+            style: "display: flex; justify-content: space-between; gap: 16px; padding: 4px 8px; cursor: pointer; {background}",
+            onmouseenter: move |_| on_hover.call(()),
+            // クリックで入力欄のフォーカスが外れないようにする
+            onmousedown: move |event| event.prevent_default(),
+            onclick: move |_| on_select.call(option.clone()),
+            span {
+                for (text, matched) in segments {
+                    if matched {
+                        b { "{text}" }
+                    } else {
+                        "{text}"
+                    }
+                }
+            }
+            span { style: "color: #888;", "{item.option.detail}" }
+        }
+    }
+}
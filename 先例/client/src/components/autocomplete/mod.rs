@@ -1 +1,5 @@
 pub mod autocomplete;
+pub mod autocomplete_empty;
+pub mod autocomplete_list;
+pub mod autocomplete_option;
+pub mod use_autocomplete;
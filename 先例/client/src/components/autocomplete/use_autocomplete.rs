@@ -0,0 +1,135 @@
+use crate::components::autocomplete::autocomplete_option::AutocompleteOption;
+
+/// 入力に一致した候補
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutocompleteMatch {
+    pub option: AutocompleteOption,
+    /// ラベルのうち一致した文字の位置 (文字単位)
+    pub positions: Vec<usize>,
+    pub score: i32,
+}
+
+/// `query` の文字が順番通りに `candidate` に含まれていれば一致とする (あいまい検索)
+/// 連続した一致や単語の先頭での一致ほどスコアが高い
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i32, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0;
+    for (idx, ch) in chars.iter().enumerate() {
+        let Some(&expected) = query.get(positions.len()) else {
+            break;
+        };
+        if ch.to_lowercase().next() != Some(expected) {
+            continue;
+        }
+        score += 1;
+        if idx > 0 && positions.last() == Some(&(idx - 1)) {
+            score += 5;
+        }
+        if idx == 0 || !chars[idx - 1].is_alphanumeric() {
+            score += 3;
+        }
+        positions.push(idx);
+    }
+    if positions.len() < query.len() {
+        return None;
+    }
+    // 先頭に近い一致を優先する
+    Some((score - positions[0] as i32, positions))
+}
+
+/// 候補を絞り込み、スコアの高い順に並べる
+/// ラベルに一致しない場合は `value` でも探す (日本語のラベルを英字で探せるように)
+pub fn filter_options(options: &[AutocompleteOption], query: &str) -> Vec<AutocompleteMatch> {
+    let mut matches: Vec<AutocompleteMatch> = options
+        .iter()
+        .filter_map(|option| {
+            let (score, positions) = fuzzy_match(query, &option.label)
+                .or_else(|| fuzzy_match(query, &option.value).map(|(score, _)| (score, vec![])))?;
+            Some(AutocompleteMatch {
+                option: option.clone(),
+                positions,
+                score,
+            })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+/// ラベルを一致した部分 (true) とそれ以外に分ける (強調表示用)
+pub fn highlight_segments(label: &str, positions: &[usize]) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    for (idx, ch) in label.chars().enumerate() {
+        let matched = positions.contains(&idx);
+        match segments.last_mut() {
+            Some((text, last)) if *last == matched => text.push(ch),
+            _ => segments.push((ch.to_string(), matched)),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn option(value: &str, label: &str) -> AutocompleteOption {
+        AutocompleteOption {
+            value: value.to_string(),
+            label: label.to_string(),
+            detail: String::new(),
+        }
+    }
+
+    #[test]
+    fn fuzzy_match_prefers_consecutive_and_word_starts() {
+        assert_eq!(fuzzy_match("", "anything"), Some((0, vec![])));
+        assert_eq!(fuzzy_match("xyz", "Undo"), None);
+        let (_, positions) = fuzzy_match("tv", "Toggle Vim").unwrap();
+        assert_eq!(positions, vec![0, 7]);
+
+        let (consecutive, _) = fuzzy_match("cut", "Cut").unwrap();
+        let (scattered, _) = fuzzy_match("cut", "Clear underline text").unwrap();
+        assert!(consecutive > scattered);
+    }
+
+    #[test]
+    fn filter_falls_back_to_value() {
+        let options = vec![
+            option("delete_word_backward", "前の単語を削除"),
+            option("undo", "元に戻す"),
+            option("redo", "やり直す"),
+        ];
+        let matches = filter_options(&options, "undo");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].option.label, "元に戻す");
+        assert!(matches[0].positions.is_empty());
+
+        assert_eq!(filter_options(&options, "do").len(), 3);
+        assert_eq!(filter_options(&options, "").len(), 3);
+        assert_eq!(filter_options(&options, "単語")[0].positions, vec![2, 3]);
+    }
+
+    #[test]
+    fn highlight_groups_runs() {
+        assert_eq!(
+            highlight_segments("Toggle", &[0, 1, 4]),
+            vec![
+                ("To".to_string(), true),
+                ("gg".to_string(), false),
+                ("l".to_string(), true),
+                ("e".to_string(), false),
+            ]
+        );
+    }
+}
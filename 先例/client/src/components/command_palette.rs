@@ -0,0 +1,147 @@
+use dioxus::prelude::*;
+
+use crate::components::autocomplete::autocomplete::Autocomplete;
+use crate::components::autocomplete::autocomplete_option::AutocompleteOption;
+use crate::r#fn::editor_command::EditorCommand;
+use crate::r#fn::keymap::Keymap;
+use crate::r#fn::shell::registry::CommandRegistry;
+use crate::r#fn::vim::ExCommand;
+
+const PALETTE_STYLE: &str = r#"
+    position: fixed;
+    top: 10%;
+    left: 50%;
+    transform: translateX(-50%);
+    width: 480px;
+    z-index: 10;
+    background-color: white;
+    border: 1px solid #ddd;
+    border-radius: 4px;
+    box-shadow: 0 4px 12px rgba(0, 0, 0, 0.15);
+    font-family: sans-serif;
+    font-size: 13px;
+"#;
+
+/// コマンドパレットから実行する操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteAction {
+    /// キーマップに登録したコマンド
+    Editor(EditorCommand),
+    /// Vimのコマンドライン (`:w` など)
+    Ex(ExCommand),
+    /// F1〜F4 のピルを挿入する
+    Pill(&'static str),
+    /// シェルのコマンドを現在行に入力する
+    Shell(String),
+}
+
+/// コマンドパレットに表示するコマンド
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteCommand {
+    /// 絞り込みに使う名前 (英字)
+    pub name: String,
+    pub title: String,
+    /// 割り当てたキー
+    pub keys: String,
+    pub action: PaletteAction,
+}
+
+impl PaletteCommand {
+    fn new(name: &str, title: &str, keys: &str, action: PaletteAction) -> PaletteCommand {
+        PaletteCommand {
+            name: name.to_string(),
+            title: title.to_string(),
+            keys: keys.to_string(),
+            action,
+        }
+    }
+}
+
+/// エディタのコマンド (キーマップ, Vimのコマンドライン, ピル)
+pub fn editor_commands(keymap: &Keymap) -> Vec<PaletteCommand> {
+    let mut commands: Vec<PaletteCommand> = EditorCommand::ALL
+        .iter()
+        .map(|(command, name, title)| {
+            let keys = keymap.keys_for(*command).unwrap_or_default();
+            PaletteCommand::new(name, title, &keys, PaletteAction::Editor(*command))
+        })
+        .collect();
+    commands.extend([
+        PaletteCommand::new("w", "保存 (Vim)", ":w", PaletteAction::Ex(ExCommand::Write)),
+        PaletteCommand::new(
+            "q",
+            "閉じる (Vim)",
+            ":q",
+            PaletteAction::Ex(ExCommand::Quit),
+        ),
+        PaletteCommand::new(
+            "wq",
+            "保存して閉じる (Vim)",
+            ":wq",
+            PaletteAction::Ex(ExCommand::WriteQuit),
+        ),
+    ]);
+    for pill in ["F1", "F2", "F3", "F4"] {
+        commands.push(PaletteCommand::new(
+            &format!("pill_{}", pill.to_lowercase()),
+            &format!("ピルを挿入 ({pill})"),
+            pill,
+            PaletteAction::Pill(pill),
+        ));
+    }
+    commands
+}
+
+/// HomeEditorのシェルのコマンド (`registry` に登録したもの)
+/// 引数が必要なコマンドは続けて入力できるようにスペースまで入力する
+pub fn shell_commands(registry: &CommandRegistry) -> Vec<PaletteCommand> {
+    registry
+        .commands()
+        .map(|command| {
+            let name = command.name();
+            let mut text = name.to_string();
+            if command.args().min > 0 {
+                text.push('\u{00A0}');
+            }
+            PaletteCommand::new(
+                &format!("shell_{name}"),
+                &format!("{name}: {}", command.help()),
+                "shell",
+                PaletteAction::Shell(text),
+            )
+        })
+        .collect()
+}
+
+/// コマンドパレット (Cmd + Shift + P)
+/// 入力でコマンドを絞り込み、選んだコマンドを `on_run` に渡す
+#[component]
+pub fn CommandPalette(
+    commands: Vec<PaletteCommand>,
+    on_run: EventHandler<PaletteAction>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let options: Vec<AutocompleteOption> = commands
+        .iter()
+        .map(|command| AutocompleteOption {
+            value: command.name.clone(),
+            label: command.title.clone(),
+            detail: command.keys.clone(),
+        })
+        .collect();
+
+    rsx! {
+        div { style: "{PALETTE_STYLE}",
+            Autocomplete {
+                options,
+                placeholder: "コマンドを検索",
+                on_select: move |option: AutocompleteOption| {
+                    if let Some(command) = commands.iter().find(|c| c.name == option.value) {
+                        on_run.call(command.action.clone());
+                    }
+                },
+                on_close,
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use dioxus::prelude::*;
+
+use crate::components::autocomplete::autocomplete_list::AutocompleteList;
+use crate::components::autocomplete::autocomplete_option::AutocompleteOption;
+use crate::components::autocomplete::use_autocomplete::AutocompleteMatch;
+use crate::r#fn::shell::completion::{candidate_label, common_prefix};
+
+const POPUP_STYLE: &str = r#"
+    position: absolute;
+    top: 100%;
+    left: 0;
+    min-width: 200px;
+    z-index: 10;
+    background-color: white;
+    border: 1px solid #ddd;
+    border-radius: 4px;
+    box-shadow: 0 4px 12px rgba(0, 0, 0, 0.15);
+    font-family: sans-serif;
+    font-size: 13px;
+"#;
+
+/// コマンドラインのTab補完の候補一覧 (現在行の下に表示する)
+/// 入力済みの部分を太字にし、選んだ候補を `on_select` に渡す
+#[component]
+pub fn CompletionPopup(
+    candidates: Vec<String>,
+    active: Option<usize>,
+    on_select: EventHandler<String>,
+) -> Element {
+    let typed = candidate_label(&common_prefix(&candidates)).chars().count();
+    let matches: Vec<AutocompleteMatch> = candidates
+        .iter()
+        .map(|candidate| AutocompleteMatch {
+            option: AutocompleteOption {
+                value: candidate.clone(),
+                label: candidate_label(candidate).to_string(),
+                detail: String::new(),
+            },
+            positions: (0..typed).collect(),
+            score: 0,
+        })
+        .collect();
+
+    rsx! {
+        div { style: "{POPUP_STYLE}",
+            AutocompleteList {
+                matches,
+                // 最初は共通部分だけを入力しているので、どれも選んでいない
+                active: active.unwrap_or(usize::MAX),
+                on_select: move |option: AutocompleteOption| on_select.call(option.value),
+                on_hover: |_| (),
+            }
+        }
+    }
+}
@@ -1,17 +1,37 @@
 use dioxus::prelude::*;
 use keyboard_types::{Code, Key, Modifiers};
 use regex::Regex;
-use serde_json::Value;
+use std::rc::Rc;
 
+use crate::components::command_palette::{editor_commands, CommandPalette, PaletteAction};
 use crate::components::editor_plugin::{
-    cursor_view::cursorView, markdown_view::markdownView, next_list_item::nextListItem,
+    caret_position::caret_from_point,
+    clipboard::{read_clipboard, write_clipboard},
+    cursor_view::cursorView,
+    keymap_settings::load_keymap,
+    markdown_view::markdownView,
+    memo_storage::sleep,
+    next_list_item::nextListItem,
+    selection_view::selectionView,
+    viewport::{scroll_line_into_view, visible_line_count},
 };
-use crate::config::constants::LOREM_IPSUM;
+use crate::components::merge_prompt::MergePrompt;
+use crate::components::search_bar::SearchBar;
 use crate::config::kana_map::KANA_MAP;
+use crate::context::jump_context::JumpTo;
+use crate::context::sync_context::MemoSync;
 use crate::context::theme_context::Theme;
+use crate::r#fn::editor_command::EditorCommand;
 use crate::r#fn::editor_state::EditorState;
+use crate::r#fn::keymap::{KeyChord, KeyInput, Keymap};
 use crate::r#fn::line::Line;
+use crate::r#fn::memo_storage::{Autosave, MemoSnapshot, MemoStorage, AUTOSAVE_DELAY_MS};
+use crate::r#fn::memo_sync::Resolution;
+use crate::r#fn::search::SearchQuery;
+use crate::r#fn::shell::command::ShellContext;
+use crate::r#fn::vim::{ExCommand, Vim, VimAction};
 use crate::types::enums::{Direction, Glyph};
+use crate::Route;
 
 macro_rules! code_events {
     ($event:ident, $editor:ident as $alias:ident,
@@ -40,10 +60,117 @@ pub fn Editor(id: i32) -> Element {
     // navigater
     let navigator = use_navigator();
 
-    let mut editor_state = use_signal(|| EditorState::from(LOREM_IPSUM));
-    // tracing::info!("editor_state :{:?}", editor_state.read());
+    // 読み込みが終わるまでは空のエディタ
+    let mut editor_state = use_signal(|| EditorState::from(""));
     let mut theme = use_context::<Signal<Theme>>();
     let mut is_ime = use_signal(|| false);
+    let mut search_open = use_signal(|| false);
+    let mut search_query = use_signal(SearchQuery::default);
+    let mut palette_open = use_signal(|| false);
+    let mut vim = use_signal(Vim::default);
+    let mut keymap = use_signal(Keymap::default);
+    // 設定に保存したキーマップを読み込む
+    use_future(move || async move {
+        keymap.set(load_keymap().await);
+    });
+    // メモの本文の保存先
+    let storage = use_context::<Rc<dyn MemoStorage>>();
+    // 保存した本文はサーバーにも送る
+    let sync = use_context::<MemoSync>();
+    // 読み込んだメモ。読み込みが終わるまでは `None` で、その間は保存しない
+    let mut loaded_id = use_signal(|| None::<i32>);
+    // 最後に保存した (読み込んだ) 内容。変わっていなければ保存しない
+    let mut saved = use_signal(|| None::<MemoSnapshot>);
+    let mut autosave = use_signal(Autosave::default);
+    // 開いたメモを読み込む。別のメモから移った場合は、保存を待っている編集を先に保存する
+    use_effect(use_reactive((&id,), {
+        let storage = storage.clone();
+        move |(id,)| {
+            let storage = storage.clone();
+            spawn(async move {
+                loaded_id.set(None);
+                if let Some((memo_id, snapshot)) = autosave.write().take() {
+                    sync.save(&*storage, memo_id, &snapshot).await;
+                }
+                let snapshot = storage
+                    .load(id)
+                    .await
+                    .unwrap_or_else(|| MemoSnapshot::from_state(&EditorState::from("")));
+                editor_state.set(snapshot.to_state());
+                saved.set(Some(snapshot));
+                loaded_id.set(Some(id));
+            });
+        }
+    }));
+    // 編集が止まってから `AUTOSAVE_DELAY_MS` 後に保存する
+    use_effect({
+        let storage = storage.clone();
+        move || {
+            let snapshot = MemoSnapshot::from_state(&editor_state.read());
+            let Some(memo_id) = loaded_id() else {
+                return;
+            };
+            if saved.peek().as_ref() == Some(&snapshot) {
+                return;
+            }
+            let generation = autosave.write().schedule(memo_id, snapshot);
+            let storage = storage.clone();
+            spawn(async move {
+                sleep(AUTOSAVE_DELAY_MS).await;
+                let Some((memo_id, snapshot)) = autosave.write().take_if_current(generation) else {
+                    return;
+                };
+                sync.save(&*storage, memo_id, &snapshot).await;
+                saved.set(Some(snapshot));
+            });
+        }
+    });
+    // エディタを閉じる前に、保存を待っている編集を保存する
+    use_drop({
+        let storage = storage.clone();
+        move || {
+            if let Some((memo_id, snapshot)) = autosave.write().take() {
+                spawn_forever(async move {
+                    sync.save(&*storage, memo_id, &snapshot).await;
+                });
+            }
+        }
+    });
+    // 待たずに保存する (:w・Cmd+S・C-x C-s)。保存を待っている編集はやめる
+    let save_now = use_callback({
+        let storage = storage.clone();
+        move |()| {
+            let Some(memo_id) = *loaded_id.peek() else {
+                return;
+            };
+            autosave.write().take();
+            let snapshot = MemoSnapshot::from_state(&editor_state.peek());
+            if saved.peek().as_ref() == Some(&snapshot) {
+                return;
+            }
+            saved.set(Some(snapshot.clone()));
+            let storage = storage.clone();
+            // :wq で閉じても保存を続ける
+            spawn_forever(async move {
+                sync.save(&*storage, memo_id, &snapshot).await;
+            });
+        }
+    });
+    // grep の結果から開いた場合は、読み込んだ後に一致した行にカーソルを置く
+    let mut jump = use_context::<Signal<Option<JumpTo>>>();
+    use_effect(move || {
+        let Some(memo_id) = loaded_id() else {
+            return;
+        };
+        let Some(target) = jump().filter(|target| target.memo_id == memo_id) else {
+            return;
+        };
+        editor_state.with_mut(|e| e.set_cursor(target.line, 1));
+        scroll_line_into_view(editor_state.peek().current_line);
+        jump.set(None);
+    });
+    // 検索バーを閉じた後にフォーカスを戻すためのエディタ本体
+    let mut editor_root: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
 
     // これいらない説
     let mut last_keys_vec: Signal<Vec<Code>> = use_signal(|| Vec::new());
@@ -82,42 +209,43 @@ pub fn Editor(id: i32) -> Element {
     let handle_composition_end = move |event: CompositionEvent| {
         tracing::info!("IME End: {:?}", event.data);
         let final_text = event.data().data();
-        editor_state.with_mut(|e| e.insert_text(&final_text));
+        editor_state.with_mut(|e| {
+            e.insert_text(&final_text);
+            // 確定した文字列は単独でundoできるようにする
+            e.break_undo_group();
+        });
         ime_buffer.set(String::new());
     };
 
     let handle_clicks = move |event: Event<MouseData>| {
-        // Use `use_eval` to create a runner for JavaScript execution
-        let mut eval = document::eval(
-            r#"
-            let ran = document.caretRangeFromPoint({x},{y});
-            let el = ran.startContainer;
-            let par = el.parentElement;
-            return [
-                parseInt(par.getAttribute('line')) || -1,
-                [...par.childNodes].indexOf(el) + 1,
-            ];
-            "#,
-        );
-
-        let coords = event.page_coordinates();
-        let x = coords.x;
-        let y = coords.y;
-
-        eval.send(format!("{} {}", x, y).to_string()).unwrap();
+        let coords = event.client_coordinates();
+        // Shift + クリックで選択範囲を広げる / Alt + クリックでカーソルを追加する
+        let extend = event.modifiers().contains(Modifiers::SHIFT);
+        let add_cursor = event.modifiers().contains(Modifiers::ALT);
 
         spawn(async move {
-            if let Ok(res) = eval.recv::<Value>().await {
-                let line = res.get(0).unwrap().as_i64().unwrap();
-                let cursor = res.get(1).unwrap().as_i64().unwrap();
-
-                if line < 0 {
-                    return;
+            if let Some((line, cursor)) = caret_from_point(coords.x, coords.y).await {
+                if add_cursor {
+                    editor_state.with_mut(|e| e.add_cursor(line, cursor));
+                } else if extend {
+                    editor_state.with_mut(|e| e.select_to(line, cursor));
+                } else {
+                    editor_state.with_mut(|e| {
+                        e.clear_cursors();
+                        e.set_cursor(line, cursor);
+                    });
                 }
+            }
+        });
+    };
 
-                editor_state.with_mut(|e| e.set_cursor(line as usize, cursor as usize));
+    // ドラッグを離した位置までを選択する
+    let handle_mouse_up = move |event: Event<MouseData>| {
+        let coords = event.client_coordinates();
 
-                println!("{line}x{cursor}");
+        spawn(async move {
+            if let Some((line, cursor)) = caret_from_point(coords.x, coords.y).await {
+                editor_state.with_mut(|e| e.select_to(line, cursor));
             }
         });
     };
@@ -128,26 +256,178 @@ pub fn Editor(id: i32) -> Element {
         number_list_re.is_match(line)
     }
 
-    let handle_global_keys = move |event: Event<KeyboardData>| {
-        // switch themes with Cmd + K
-        if event.modifiers().contains(Modifiers::META | Modifiers::ALT)
-            && event.code() == Code::KeyK
-        {
-            let new_theme = match *theme.read() {
-                Theme::Default => Theme::Readonly,
-                Theme::Readonly => Theme::Dev,
-                Theme::Dev => Theme::Default,
-            };
-            theme.set(new_theme);
-            event.stop_propagation();
+    // キーマップのコマンドを実行する
+    let mut run_command = move |command: EditorCommand, extend: bool| {
+        if editor_state.with_mut(|e| command.apply(e, extend)) {
             return;
         }
-        // toggle IME
-        if event.modifiers().contains(Modifiers::META) && event.code() == Code::KeyK {
-            let toggle = !*is_ime.read();
-            is_ime.set(toggle);
-            event.stop_propagation();
-            return;
+        match command {
+            EditorCommand::ToggleTheme => {
+                let new_theme = match *theme.read() {
+                    Theme::Default => Theme::Readonly,
+                    Theme::Readonly => Theme::Dev,
+                    Theme::Dev => Theme::Default,
+                };
+                theme.set(new_theme);
+            }
+            EditorCommand::ToggleIme => {
+                let toggle = !*is_ime.read();
+                is_ime.set(toggle);
+            }
+            EditorCommand::ToggleVim => vim.with_mut(|v| v.toggle()),
+            EditorCommand::OpenCommandPalette => palette_open.set(true),
+            // 検索バーを開く。1行以内の選択中の文字列を検索語にする
+            EditorCommand::Find => {
+                let selected = editor_state.read().selected_text();
+                if let Some(text) = selected.filter(|text| !text.contains('\n')) {
+                    search_query.with_mut(|q| q.pattern = text);
+                }
+                search_open.set(true);
+            }
+            EditorCommand::Copy | EditorCommand::CopyMarkdown | EditorCommand::Cut => {
+                let text = editor_state.with(|e| {
+                    if command == EditorCommand::CopyMarkdown {
+                        e.selected_markdown()
+                    } else {
+                        e.selected_text()
+                    }
+                });
+                if let Some(text) = text {
+                    write_clipboard(text);
+                    if command == EditorCommand::Cut {
+                        editor_state.with_mut(|e| e.delete_selection());
+                    }
+                }
+            }
+            EditorCommand::KillLine => {
+                let killed = editor_state.with_mut(|e| e.kill_line());
+                if !killed.is_empty() {
+                    write_clipboard(killed);
+                }
+            }
+            EditorCommand::Paste => {
+                spawn(async move {
+                    if let Some(text) = read_clipboard().await {
+                        editor_state.with_mut(|e| e.paste(&text));
+                    }
+                });
+            }
+            // 画面に収まる行数だけ移動する
+            EditorCommand::PageUp | EditorCommand::PageDown => {
+                let dir = if command == EditorCommand::PageDown {
+                    Direction::Forward
+                } else {
+                    Direction::Backward
+                };
+                spawn(async move {
+                    let count = visible_line_count().await;
+                    editor_state.with_mut(|e| {
+                        if extend {
+                            e.each_cursor(|e| e.select_with(|e| e.move_lines(dir, count)));
+                        } else {
+                            e.each_cursor(|e| e.move_lines(dir, count));
+                        }
+                    });
+                    scroll_line_into_view(editor_state.read().current_line);
+                });
+            }
+            EditorCommand::Save => save_now(()),
+            _ => (),
+        }
+    };
+
+    // Vimのコマンドライン・コマンドパレットのコマンドを実行する
+    let mut run_ex_command = move |command: ExCommand| match command {
+        ExCommand::Write => run_command(EditorCommand::Save, false),
+        ExCommand::Quit => {
+            navigator.push("/");
+        }
+        ExCommand::WriteQuit => {
+            run_command(EditorCommand::Save, false);
+            navigator.push("/");
+        }
+        // 保存した階層からメモの名前 (パス) で探し、なければ memo_id として開く
+        ExCommand::Edit(memo) => {
+            let storage = storage.clone();
+            spawn(async move {
+                let context = ShellContext {
+                    tree: storage.load_tree().await.unwrap_or_default(),
+                    ..ShellContext::default()
+                };
+                let found = context
+                    .find_memo(&memo)
+                    .ok()
+                    .or_else(|| memo.parse::<i32>().ok());
+                match found {
+                    Some(memo_id) => {
+                        navigator.push(Route::Editor { id: memo_id });
+                    }
+                    None => vim.with_mut(|v| v.message = format!("{memo} is no match found")),
+                }
+            });
+        }
+        ExCommand::Unknown(_) => (),
+    };
+
+    // コマンドパレットを閉じてエディタにフォーカスを戻す
+    let mut close_palette = move || {
+        palette_open.set(false);
+        spawn(async move {
+            if let Some(root) = editor_root() {
+                let _ = root.set_focus(true).await;
+            }
+        });
+    };
+
+    // コマンドパレットで選んだコマンドを実行する
+    let run_palette_action = {
+        to_owned![run_ex_command];
+        move |action: PaletteAction| {
+            close_palette();
+            match action {
+                PaletteAction::Editor(command) => run_command(command, false),
+                PaletteAction::Ex(command) => run_ex_command(command),
+                PaletteAction::Pill(pill) => editor_state.with_mut(|e| e.insert_pill(pill)),
+                PaletteAction::Shell(text) => editor_state.with_mut(|e| e.insert_text(&text)),
+            }
+        }
+    };
+
+    let handle_global_keys = move |event: Event<KeyboardData>| {
+        // Vimモード。Cmd を押している場合は通常のショートカットとして扱う
+        if !event.modifiers().contains(Modifiers::META) {
+            let ctrl = event.modifiers().contains(Modifiers::CONTROL);
+            let action =
+                vim.with_mut(|v| editor_state.with_mut(|e| v.handle_key(&event.key(), ctrl, e)));
+            match action {
+                VimAction::PassThrough => (),
+                VimAction::Handled => {
+                    scroll_line_into_view(editor_state.read().current_line);
+                    event.stop_propagation();
+                    return;
+                }
+                VimAction::Command(command) => {
+                    run_ex_command(command);
+                    event.stop_propagation();
+                    return;
+                }
+            }
+        }
+
+        // キーマップに割り当てたコマンド
+        let chord = KeyChord::new(event.code(), event.modifiers());
+        match keymap.with_mut(|k| k.feed(chord)) {
+            KeyInput::Command { command, extend } => {
+                run_command(command, extend);
+                event.stop_propagation();
+                return;
+            }
+            // 続きのキーを待つ (C-x C-s など)
+            KeyInput::Pending => {
+                event.stop_propagation();
+                return;
+            }
+            KeyInput::Unbound => (),
         }
 
         // TODO
@@ -157,13 +437,6 @@ pub fn Editor(id: i32) -> Element {
         //     return;
         // }
 
-        // TODO : maybe del
-        if event.modifiers().contains(Modifiers::META) && event.code() == Code::KeyA {
-            editor_state.with_mut(|e| e.insert_pill("C-A"));
-            event.stop_propagation();
-            return;
-        }
-
         // IME mode
         if *is_ime.read() {
             if *is_ime.read() {
@@ -205,17 +478,11 @@ pub fn Editor(id: i32) -> Element {
                         for Code::F2 => e.insert_pill("F2"),
                         for Code::F3 => e.insert_pill("F3"),
                         for Code::F4 => e.insert_pill("F4"),
-                        for Code::Delete => e.delete(Direction::Forward),
-                        for Code::Backspace => e.delete(Direction::Backward),
                         for Code::Space => {
                             e.insert_char(char::from_u32(0x00A0).unwrap());
                             let eval = document::eval("window.event.preventDefault();");
                             eval.send(serde_json::Value::Null).unwrap();
                         },
-                        for Code::ArrowUp => e.go_to_line(Direction::Backward),
-                        for Code::ArrowDown => e.go_to_line(Direction::Forward),
-                        for Code::ArrowRight => e.move_cursor(Direction::Forward),
-                        for Code::ArrowLeft => e.move_cursor(Direction::Backward),
                         for Code::Enter => e.next_line_or_new()
                 ]
             ];
@@ -229,17 +496,11 @@ pub fn Editor(id: i32) -> Element {
                         for Code::F2 => e.insert_pill("F2"),
                         for Code::F3 => e.insert_pill("F3"),
                         for Code::F4 => e.insert_pill("F4"),
-                        for Code::Delete => e.delete(Direction::Forward),
-                        for Code::Backspace => e.delete(Direction::Backward),
                         for Code::Space => {
                             e.insert_char(char::from_u32(0x00A0).unwrap());
                             let eval = document::eval("window.event.preventDefault();");
                             eval.send(serde_json::Value::Null).unwrap();
                         },
-                        for Code::ArrowUp => e.go_to_line(Direction::Backward),
-                        for Code::ArrowDown => e.go_to_line(Direction::Forward),
-                        for Code::ArrowRight => e.move_cursor(Direction::Forward),
-                        for Code::ArrowLeft => e.move_cursor(Direction::Backward),
                         for Code::Enter => {
                             // 改行前に現在行を確認
                             let current_line_idx = e.current_line;
@@ -309,6 +570,35 @@ pub fn Editor(id: i32) -> Element {
 
     let (current_line, current_position) =
         editor_state.with(|e| (e.current_line, e.cursor_position));
+    let vim_status = vim.read().status();
+    // 他の端末の更新と食い違っていれば、どちらを残すか選ぶ
+    let conflict = sync.state.read().conflict(id).cloned();
+    let resolve_conflict = move |resolution: Resolution| {
+        if let Some(text) = sync.resolve(id, resolution) {
+            let snapshot = editor_state.with(MemoSnapshot::from_state);
+            editor_state.set(MemoSnapshot { text, ..snapshot }.to_state());
+        }
+    };
+    let pending_edits = match sync.state.read().pending() {
+        0 => String::new(),
+        count => format!("未送信: {count}"),
+    };
+    let pending_keys = keymap.read().pending();
+    // コマンドパレットに表示するコマンド
+    let palette_commands = if *palette_open.read() {
+        editor_commands(&keymap.read())
+    } else {
+        Vec::new()
+    };
+    // 検索バーを開いている間は一致をハイライトする
+    let search_matches = if *search_open.read() {
+        editor_state
+            .read()
+            .find_matches(&search_query.read())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
 
     rsx! {
         div {
@@ -319,72 +609,110 @@ pub fn Editor(id: i32) -> Element {
             oncompositionupdate: handle_composition_update,
             oncompositionend: handle_composition_end,
             onkeydown: handle_global_keys,
-            {
-                editor_state
-                    .read()
-                    .iter()
-                    .map(|(line_number, line)| {
-                        let current = current_line == line_number;
-                        let background = if current {
-                            "background-color: #f6f6f6;"
-                        } else {
-                            "background-color: white;"
-                        };
-                        let opacity = if current { "100%" } else { "20%" };
-                        let (rendered_line, line_style): (Vec<(String, String)>, String) = {
-                            let line_content = cursorView(line, *is_ime.read());
-                            let (line_text, combined_style) = markdownView(
-                                &line_content,
-                                &navigator,
-                            );
-                            (line_text, combined_style)
-                        };
-                        rsx! {
-                            div { style: "padding-right: 5px; text-align: right;",
-                                span { style: "opacity: {opacity};", "{line_number}" }
+            onmouseup: handle_mouse_up,
+            onmounted: move |event| editor_root.set(Some(event.data())),
+            if *palette_open.read() {
+                CommandPalette {
+                    commands: palette_commands,
+                    on_run: run_palette_action,
+                    on_close: move |_| close_palette(),
+                }
+            }
+            if let Some(conflict) = conflict {
+                MergePrompt { conflict, on_resolve: resolve_conflict }
+            }
+            if *search_open.read() {
+                SearchBar {
+                    editor_state,
+                    query: search_query,
+                    on_close: move |_| {
+                        search_open.set(false);
+                        spawn(async move {
+                            if let Some(root) = editor_root() {
+                                let _ = root.set_focus(true).await;
                             }
-                            div {
-                                style: "{line_style} {background}",
-                                id: "L{line_number}",
-                                "line": "{line_number}",
-                                onmousedown: handle_clicks,
-                                // view convert TEXT
-                                {
-                                    rendered_line
-                                        .iter()
-                                        .map(|(text, style)| {
-                                            rsx! {
-                                                // TODO: fix
-                                                if text == "<Component>" {
-                                                    // Sampleコンポーネントを直接描画
-                                                    Sample {}
-                                                } else if text.contains("<") {
-                                                    // HTMLノードを描画
-                                                    span { dangerous_inner_html: "{text}" }
-                                                } else if text.contains(":\u{00A0}") {
-                                                    // 実装方針
-                                                    // ':' + '\u{00A0}'でAutocomplete表示
-                                                    // Autocomplete表示中はこれにフォーカスを優先
-                                                    // -> カーソルは常に1つを維持
-                                                    // 選択内容をクリック または enterで任意のコンポーネントを挿入
-                                                    // この部分で行う(できれば) -> insert_elementは不要になる想定
-                                                    // 2024/12/8 ん、やっぱりinsert_element使ったほうが綺麗な気がしてきた
-                                                    span { style: "{style}", "{text}" }
-                                                    Sample {}
-                                                } else {
-                                                    // 通常のテキストを描画
-                                                    // Sample {}
-                                                    span { style: "{style}", "{text}" }
+                        });
+                    },
+                }
+            }
+            if loaded_id().is_none() {
+                div { style: "grid-column: 1 / -1; opacity: 20%;", "読み込み中..." }
+            } else {
+                {
+                    editor_state
+                        .read()
+                        .iter()
+                        .map(|(line_number, line)| {
+                            let current = current_line == line_number;
+                            let background = if current {
+                                "background-color: #f6f6f6;"
+                            } else {
+                                "background-color: white;"
+                            };
+                            let opacity = if current { "100%" } else { "20%" };
+                            let selections = editor_state.read().selections_in_line(line_number);
+                            let highlights = editor_state.read().matches_in_line(&search_matches, line_number);
+                            let (rendered_line, line_style): (Vec<(String, String)>, String) = match selections.is_empty() && highlights.is_empty() {
+                                // 選択中の行は位置がずれないようにマークダウン変換せずに表示
+                                false => (selectionView(&line, &selections, &highlights, *is_ime.read()), String::new()),
+                                true => {
+                                    let line_content = cursorView(&line, *is_ime.read());
+                                    let (line_text, combined_style) = markdownView(
+                                        &line_content,
+                                        &navigator,
+                                    );
+                                    (line_text, combined_style)
+                                }
+                            };
+                            rsx! {
+                                div { style: "padding-right: 5px; text-align: right;",
+                                    span { style: "opacity: {opacity};", "{line_number}" }
+                                }
+                                div {
+                                    style: "{line_style} {background}",
+                                    id: "L{line_number}",
+                                    "line": "{line_number}",
+                                    onmousedown: handle_clicks,
+                                    // view convert TEXT
+                                    {
+                                        rendered_line
+                                            .iter()
+                                            .map(|(text, style)| {
+                                                rsx! {
+                                                    // TODO: fix
+                                                    if text == "<Component>" {
+                                                        // Sampleコンポーネントを直接描画
+                                                        Sample {}
+                                                    } else if text.contains("<") {
+                                                        // HTMLノードを描画
+                                                        span { dangerous_inner_html: "{text}" }
+                                                    } else if text.contains(":\u{00A0}") {
+                                                        // 実装方針
+                                                        // ':' + '\u{00A0}'でAutocomplete表示
+                                                        // Autocomplete表示中はこれにフォーカスを優先
+                                                        // -> カーソルは常に1つを維持
+                                                        // 選択内容をクリック または enterで任意のコンポーネントを挿入
+                                                        // この部分で行う(できれば) -> insert_elementは不要になる想定
+                                                        // 2024/12/8 ん、やっぱりinsert_element使ったほうが綺麗な気がしてきた
+                                                        span { style: "{style}", "{text}" }
+                                                        Sample {}
+                                                    } else {
+                                                        // 通常のテキストを描画
+                                                        // Sample {}
+                                                        span { style: "{style}", "{text}" }
+                                                    }
                                                 }
-                                            }
-                                        })
+                                            })
+                                    }
                                 }
                             }
-                        }
-                    })
+                        })
+                }
             }
         }
-        div { "Line: {current_line} Position: {current_position}" }
+        div {
+            "{vim_status} {pending_keys} Line: {current_line} Position: {current_position} {pending_edits}"
+        }
     }
 }
 
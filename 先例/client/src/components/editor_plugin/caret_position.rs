@@ -0,0 +1,41 @@
+use dioxus::prelude::*;
+use serde_json::Value;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 座標から行要素と、行頭からその位置までの文字列を取得するJS
+const CARET_FROM_POINT_JS: &str = r#"
+    const [x, y] = await dioxus.recv();
+    const ran = document.caretRangeFromPoint(x, y);
+    if (!ran) {
+        return [-1, ""];
+    }
+    const node = ran.startContainer;
+    const el = node.nodeType === Node.TEXT_NODE ? node.parentElement : node;
+    const lineEl = el && el.closest("[line]");
+    if (!lineEl) {
+        return [-1, ""];
+    }
+    const pre = document.createRange();
+    pre.selectNodeContents(lineEl);
+    pre.setEnd(ran.startContainer, ran.startOffset);
+    return [parseInt(lineEl.getAttribute("line")) || -1, pre.toString()];
+"#;
+
+/// 画面上の座標 (client座標) に対応する (行, 位置) を返す
+/// 行の外をクリックした場合はNone
+pub async fn caret_from_point(x: f64, y: f64) -> Option<(usize, usize)> {
+    let eval = document::eval(CARET_FROM_POINT_JS);
+    eval.send(vec![x, y]).ok()?;
+    let res = eval.join::<Value>().await.ok()?;
+
+    let line = res.get(0)?.as_i64()?;
+    if line < 0 {
+        return None;
+    }
+    // 位置は書記素クラスタ単位で数え、描画されたカーソル記号は含めない
+    // クラスタの途中を指した場合はそのクラスタの直後になる
+    let prefix = res.get(1)?.as_str()?;
+    let position = prefix.graphemes(true).filter(|g| *g != "❮").count() + 1;
+
+    Some((line as usize, position))
+}
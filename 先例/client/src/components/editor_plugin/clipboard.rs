@@ -0,0 +1,20 @@
+use dioxus::prelude::*;
+
+/// OSのクリップボードに文字列を書き込む
+pub fn write_clipboard(text: String) {
+    let eval = document::eval(
+        r#"
+        const text = await dioxus.recv();
+        await navigator.clipboard.writeText(text);
+        "#,
+    );
+    if let Err(err) = eval.send(text) {
+        tracing::info!("Failed to write clipboard: {:?}", err);
+    }
+}
+
+/// OSのクリップボードから文字列を読み込む
+pub async fn read_clipboard() -> Option<String> {
+    let eval = document::eval("return await navigator.clipboard.readText();");
+    eval.join::<String>().await.ok()
+}
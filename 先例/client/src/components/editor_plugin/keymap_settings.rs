@@ -0,0 +1,25 @@
+use dioxus::prelude::*;
+
+use crate::r#fn::keymap::{Keymap, KeymapConfig};
+
+/// キーマップ設定 (JSON / TOML) を保存しているlocalStorageのキー
+pub const KEYMAP_SETTINGS_KEY: &str = "mijinko.settings.keymap";
+
+/// 設定に保存したキーマップを読み込む
+/// 設定がない・読めない場合はデフォルトのキーマップを使う
+pub async fn load_keymap() -> Keymap {
+    let eval = document::eval(&format!(
+        "return localStorage.getItem('{KEYMAP_SETTINGS_KEY}');"
+    ));
+    let text = match eval.join::<Option<String>>().await {
+        Ok(Some(text)) => text,
+        _ => return Keymap::default(),
+    };
+    match KeymapConfig::parse(&text).and_then(|config| Keymap::from_config(&config)) {
+        Ok(keymap) => keymap,
+        Err(err) => {
+            tracing::info!("Failed to load keymap: {}", err);
+            Keymap::default()
+        }
+    }
+}
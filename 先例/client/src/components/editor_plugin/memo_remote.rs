@@ -0,0 +1,104 @@
+use dioxus::prelude::*;
+use serde::Deserialize;
+
+use crate::components::editor_plugin::memo_storage::{auth_token, get_json, js_string, set_json};
+use crate::config::constants::API_URL;
+use crate::r#fn::memo_storage::StorageFuture;
+use crate::r#fn::memo_sync::{MemoRemote, RemoteMemo, SyncOutcome, SyncRequest, SyncState};
+
+/// 送っていない編集とサーバーのバージョン (JSON) を保存しているlocalStorageのキー
+pub const MEMO_SYNC_KEY: &str = "mijinko.memo_sync";
+
+/// `/v1/memos` に送る (ログイン中のユーザー)
+pub struct HttpMemoRemote {
+    token: String,
+}
+
+impl HttpMemoRemote {
+    /// トークンを保存していればサーバーに送る。していなければ `None`
+    pub async fn detect() -> Option<HttpMemoRemote> {
+        auth_token().await.map(|token| HttpMemoRemote { token })
+    }
+}
+
+/// `fetch` の結果。つながらなければ `status` は0
+#[derive(Deserialize)]
+struct FetchResult {
+    status: u16,
+    memo: Option<RemoteMemo>,
+}
+
+impl MemoRemote for HttpMemoRemote {
+    fn send(&self, request: &SyncRequest) -> StorageFuture<'_, SyncOutcome> {
+        let (method, url) = match &request.remote_id {
+            Some(remote_id) => ("PUT", format!("{API_URL}/v1/memos/{remote_id}")),
+            None => ("POST", format!("{API_URL}/v1/memos")),
+        };
+        let body = serde_json::json!({
+            "title": request.edit.title,
+            "body": request.edit.body,
+            "version": request.version,
+        })
+        .to_string();
+        // 409の場合はサーバーの最新のメモも読む
+        let script = format!(
+            r#"
+            const url = {url};
+            const headers = {{ Authorization: 'Bearer ' + {token}, 'Content-Type': 'application/json' }};
+            try {{
+                const res = await fetch(url, {{ method: '{method}', headers, body: {body} }});
+                if (res.status === 409) {{
+                    const latest = await fetch(url, {{ headers }});
+                    if (!latest.ok) return {{ status: latest.status }};
+                    return {{ status: 409, memo: await latest.json() }};
+                }}
+                if (!res.ok) return {{ status: res.status }};
+                return {{ status: res.status, memo: await res.json() }};
+            }} catch (err) {{
+                return {{ status: 0 }};
+            }}
+            "#,
+            url = js_string(&url),
+            token = js_string(&self.token),
+            body = js_string(&body),
+        );
+
+        Box::pin(async move {
+            let result = match document::eval(&script).join::<FetchResult>().await {
+                Ok(result) => result,
+                Err(err) => {
+                    tracing::info!("Failed to sync memo: {:?}", err);
+                    return SyncOutcome::Offline;
+                }
+            };
+            match (result.status, result.memo) {
+                (200 | 201, Some(memo)) => SyncOutcome::Saved(memo),
+                (409, Some(memo)) => SyncOutcome::Conflict(memo),
+                (404, _) => SyncOutcome::Missing,
+                // つながらない・ログインし直す・サーバーのエラーは後で送り直す
+                (0 | 401, _) | (500.., _) => SyncOutcome::Offline,
+                (status, _) => {
+                    tracing::info!("Failed to sync memo: {}", status);
+                    SyncOutcome::Failed
+                }
+            }
+        })
+    }
+}
+
+/// 保存した同期の状態を読み込む。保存していない・読めない場合は `None`
+pub async fn load_sync_state() -> Option<SyncState> {
+    get_json(MEMO_SYNC_KEY).await
+}
+
+pub fn save_sync_state(state: &SyncState) {
+    set_json(MEMO_SYNC_KEY, state);
+}
+
+/// オンラインに戻るまで待つ
+pub async fn wait_online() {
+    let eval = document::eval(
+        "await new Promise((resolve) => window.addEventListener('online', resolve, { once: true })); return true;",
+    );
+    let _ = eval.join::<bool>().await;
+}
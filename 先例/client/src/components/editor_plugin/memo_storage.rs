@@ -0,0 +1,94 @@
+use dioxus::prelude::*;
+use memo_tree::{MemoTree, NodeId};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::r#fn::memo_storage::{MemoSnapshot, MemoStorage, StorageFuture};
+
+/// メモの階層 (JSON) を保存しているlocalStorageのキー
+pub const MEMO_TREE_KEY: &str = "mijinko.memo_tree";
+/// ログイン中のユーザーのトークンを保存しているlocalStorageのキー
+pub const AUTH_TOKEN_KEY: &str = "mijinko.token";
+
+/// メモの本文 (JSON) を保存しているlocalStorageのキー
+fn memo_key(memo_id: NodeId) -> String {
+    format!("mijinko.memo.{memo_id}")
+}
+
+/// localStorageに保存する (未ログインのユーザー)
+pub struct LocalMemoStorage;
+
+impl MemoStorage for LocalMemoStorage {
+    fn load(&self, memo_id: NodeId) -> StorageFuture<'_, Option<MemoSnapshot>> {
+        Box::pin(async move { get_json(&memo_key(memo_id)).await })
+    }
+
+    fn save(&self, memo_id: NodeId, snapshot: &MemoSnapshot) -> StorageFuture<'_, ()> {
+        set_json(&memo_key(memo_id), snapshot);
+        Box::pin(async {})
+    }
+
+    fn remove(&self, memo_id: NodeId) -> StorageFuture<'_, ()> {
+        document::eval(&format!(
+            "localStorage.removeItem('{}');",
+            memo_key(memo_id)
+        ));
+        Box::pin(async {})
+    }
+
+    fn load_tree(&self) -> StorageFuture<'_, Option<MemoTree>> {
+        Box::pin(async move { get_json(MEMO_TREE_KEY).await })
+    }
+
+    fn save_tree(&self, tree: &MemoTree) -> StorageFuture<'_, ()> {
+        set_json(MEMO_TREE_KEY, tree);
+        Box::pin(async {})
+    }
+}
+
+/// 保存したJSONを読み込む。保存していない・読めない場合は `None`
+pub async fn get_json<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let eval = document::eval(&format!("return localStorage.getItem('{key}');"));
+    let text = eval.join::<Option<String>>().await.ok()??;
+    match serde_json::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            tracing::info!("Failed to load {}: {}", key, err);
+            None
+        }
+    }
+}
+
+pub fn set_json<T: Serialize>(key: &str, value: &T) {
+    let json = match serde_json::to_string(value) {
+        Ok(json) => json,
+        Err(err) => {
+            tracing::info!("Failed to save {}: {}", key, err);
+            return;
+        }
+    };
+    let literal = js_string(&json);
+    document::eval(&format!("localStorage.setItem('{key}', {literal});"));
+}
+
+/// ログイン中のユーザーのトークン。ログインしていなければ `None`
+pub async fn auth_token() -> Option<String> {
+    let eval = document::eval(&format!("return localStorage.getItem('{AUTH_TOKEN_KEY}');"));
+    eval.join::<Option<String>>()
+        .await
+        .ok()
+        .flatten()
+        .filter(|token| !token.is_empty())
+}
+
+/// 文字列をJSの文字列リテラルとして埋め込む
+pub fn js_string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+/// `ms` ミリ秒待つ
+pub async fn sleep(ms: u32) {
+    let eval = document::eval(&format!(
+        "await new Promise((resolve) => setTimeout(resolve, {ms})); return true;"
+    ));
+    let _ = eval.join::<bool>().await;
+}
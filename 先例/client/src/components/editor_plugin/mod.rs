@@ -1,5 +1,12 @@
+pub mod caret_position;
+pub mod clipboard;
 pub mod codeblock;
-pub mod command_fn;
 pub mod cursor_view;
+pub mod keymap_settings;
 pub mod markdown_view;
+pub mod memo_remote;
+pub mod memo_storage;
 pub mod next_list_item;
+pub mod selection_view;
+pub mod shell_history_storage;
+pub mod viewport;
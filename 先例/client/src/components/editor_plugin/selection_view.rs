@@ -0,0 +1,57 @@
+use crate::{r#fn::line::Line, types::enums::Glyph};
+
+const SELECTION_STYLE: &str = "background-color: #b4d5fe;";
+const HIGHLIGHT_STYLE: &str = "background-color: #fff3a3;";
+
+/// 選択範囲を含む行を、選択部分とそれ以外の (テキスト, style) に分けて返す
+/// `ranges` はカーソルを除いた位置 [開始, 終了) (0始まり)。マルチカーソルでは複数になる
+/// `highlights` は検索の一致など、選択とは別の色で表示する範囲
+pub fn selectionView(
+    line: &Line,
+    ranges: &[(usize, usize)],
+    highlights: &[(usize, usize)],
+    is_ime: bool,
+) -> Vec<(String, String)> {
+    let mut segments: Vec<(String, String)> = Vec::new();
+    let mut column = 0;
+
+    for glyph in line.as_vec() {
+        let text = match glyph {
+            Glyph::Text(text) => text.clone(),
+            Glyph::Char(c) => c.to_string(),
+            Glyph::Cursor => {
+                if is_ime {
+                    "❮:IME".to_string()
+                } else {
+                    "❮".to_string()
+                }
+            }
+            Glyph::HTMLNode(value) => value.clone(),
+            Glyph::Component(_) => "<Component>".to_string(),
+        };
+        let is_cursor = matches!(glyph, Glyph::Cursor);
+        let contains = |(start, end): &(usize, usize)| column >= *start && column < *end;
+        let style = if !is_cursor && ranges.iter().any(contains) {
+            SELECTION_STYLE
+        } else if !is_cursor && highlights.iter().any(contains) {
+            HIGHLIGHT_STYLE
+        } else {
+            ""
+        };
+        if !is_cursor {
+            column += 1;
+        }
+
+        match segments.last_mut() {
+            Some((segment, segment_style)) if segment_style == style => segment.push_str(&text),
+            _ => segments.push((text, style.to_string())),
+        }
+    }
+
+    // 改行まで選択されている場合は行末に空白を表示する
+    if ranges.iter().any(|(_, end)| *end > column) {
+        segments.push(("\u{00A0}".to_string(), SELECTION_STYLE.to_string()));
+    }
+
+    segments
+}
@@ -0,0 +1,102 @@
+use dioxus::prelude::*;
+
+use crate::components::editor_plugin::memo_storage::{auth_token, js_string};
+use crate::config::constants::API_URL;
+use crate::r#fn::shell::history::{ShellHistory, MAX_ENTRIES};
+
+/// コマンドの履歴 (JSON) を保存しているlocalStorageのキー
+pub const SHELL_HISTORY_KEY: &str = "mijinko.shell_history";
+
+/// 履歴の保存先。ログインしていればサーバー、していなければlocalStorage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum HistoryStorage {
+    #[default]
+    Local,
+    Server {
+        token: String,
+    },
+}
+
+impl HistoryStorage {
+    /// トークンを保存していればサーバーに保存する
+    pub async fn detect() -> HistoryStorage {
+        match auth_token().await {
+            Some(token) => HistoryStorage::Server { token },
+            None => HistoryStorage::Local,
+        }
+    }
+
+    /// 保存した履歴を読み込む。保存していない・読めない場合は `None`
+    pub async fn load(&self) -> Option<ShellHistory> {
+        let script = match self {
+            HistoryStorage::Local => {
+                format!("return JSON.parse(localStorage.getItem('{SHELL_HISTORY_KEY}'));")
+            }
+            // サーバーは新しい順に返すので、古い順に並べ直す
+            HistoryStorage::Server { token } => format!(
+                r#"
+                const res = await fetch('{API_URL}/v1/history?limit={MAX_ENTRIES}', {{
+                    headers: {{ Authorization: 'Bearer ' + {token} }},
+                }});
+                if (!res.ok) return null;
+                return (await res.json()).map((entry) => entry.line).reverse();
+                "#,
+                token = js_string(token),
+            ),
+        };
+        let eval = document::eval(&script);
+        match eval.join::<Option<Vec<String>>>().await {
+            Ok(lines) => {
+                let mut history = ShellHistory::default();
+                for line in lines? {
+                    history.push(&line);
+                }
+                Some(history)
+            }
+            Err(err) => {
+                tracing::info!("Failed to load shell history: {:?}", err);
+                None
+            }
+        }
+    }
+
+    /// コマンドを実行した後の履歴を保存する
+    /// サーバーには追加した行だけを送り、`history -c` で空になった場合は消す
+    pub fn save(&self, before: &ShellHistory, after: &ShellHistory) {
+        let script = match self {
+            HistoryStorage::Local => {
+                let json = match serde_json::to_string(after) {
+                    Ok(json) => json,
+                    Err(err) => {
+                        tracing::info!("Failed to save shell history: {}", err);
+                        return;
+                    }
+                };
+                format!(
+                    "localStorage.setItem('{SHELL_HISTORY_KEY}', {});",
+                    js_string(&json)
+                )
+            }
+            HistoryStorage::Server { token } if after.is_empty() => format!(
+                "fetch('{API_URL}/v1/history', {{ method: 'DELETE', headers: {{ Authorization: 'Bearer ' + {} }} }});",
+                js_string(token),
+            ),
+            HistoryStorage::Server { token } => {
+                let Some(line) = after.entries().last().filter(|_| after != before) else {
+                    return;
+                };
+                let body = serde_json::json!({ "line": line }).to_string();
+                format!(
+                    r#"fetch('{API_URL}/v1/history', {{
+                        method: 'POST',
+                        headers: {{ Authorization: 'Bearer ' + {}, 'Content-Type': 'application/json' }},
+                        body: {},
+                    }});"#,
+                    js_string(token),
+                    js_string(&body),
+                )
+            }
+        };
+        document::eval(&script);
+    }
+}
@@ -0,0 +1,30 @@
+use dioxus::prelude::*;
+
+/// 画面に収まる行数を求めるJS。1行目の高さを行の高さとみなす
+const VISIBLE_LINE_COUNT_JS: &str = r#"
+    const line = document.querySelector("[line]");
+    const height = line ? line.getBoundingClientRect().height : 0;
+    return height > 0 ? Math.floor(window.innerHeight / height) : 1;
+"#;
+
+/// 画面に収まる行数 (PageUp / PageDown の移動量)
+pub async fn visible_line_count() -> usize {
+    let eval = document::eval(VISIBLE_LINE_COUNT_JS);
+    eval.join::<usize>().await.unwrap_or(1).max(1)
+}
+
+/// `line` 行目が画面外にある場合、見える位置までスクロールする
+pub fn scroll_line_into_view(line: usize) {
+    let eval = document::eval(
+        r#"
+        const line = await dioxus.recv();
+        const el = document.getElementById("L" + line);
+        if (el) {
+            el.scrollIntoView({ block: "nearest" });
+        }
+        "#,
+    );
+    if let Err(err) = eval.send(line) {
+        tracing::info!("Failed to scroll: {:?}", err);
+    }
+}
@@ -1,17 +1,45 @@
 use dioxus::prelude::*;
 use keyboard_types::{Code, Key, Modifiers};
+use memo_tree::{MemoTree, NodeKind};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::json;
 use std::collections::HashMap;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::components::command_palette::{
+    editor_commands, shell_commands, CommandPalette, PaletteAction,
+};
+use crate::components::completion_popup::CompletionPopup;
 use crate::components::editor_plugin::{
-    command_fn::apply_cd_command, cursor_view::cursorView, next_list_item::nextListItem,
+    caret_position::caret_from_point,
+    clipboard::{read_clipboard, write_clipboard},
+    cursor_view::cursorView,
+    keymap_settings::load_keymap,
+    selection_view::selectionView,
+    shell_history_storage::HistoryStorage,
+    viewport::{scroll_line_into_view, visible_line_count},
 };
-use crate::config::constants::LOREM_IPSUM;
+use crate::components::search_bar::SearchBar;
+use crate::components::terminal_output::TerminalOutput;
+use crate::context::jump_context::JumpTo;
+use crate::context::sync_context::MemoSync;
 use crate::context::theme_context::Theme;
+use crate::r#fn::editor_command::EditorCommand;
 use crate::r#fn::editor_state::EditorState;
+use crate::r#fn::keymap::{KeyChord, KeyInput, Keymap};
+use crate::r#fn::memo_storage::{copy_memo, removed_memos, MemoStorage};
+use crate::r#fn::search::SearchQuery;
+use crate::r#fn::shell::command::{MemoLink, ShellContext, ShellOutput};
+use crate::r#fn::shell::commands::grep::Grep;
+use crate::r#fn::shell::completion::{complete, CompletionMenu};
+use crate::r#fn::shell::history::{Recall, ReverseSearch};
+use crate::r#fn::shell::registry::CommandRegistry;
+use crate::r#fn::shell::terminal::{prompt, Terminal};
+use crate::r#fn::vim::{ExCommand, Vim, VimAction};
 use crate::types::enums::{Direction, Glyph};
+use crate::Route;
 
 macro_rules! code_events {
     ($event:ident, $editor:ident as $alias:ident,
@@ -40,10 +68,21 @@ pub fn HomeEditor() -> Element {
     // navigater
     let navigator = use_navigator();
 
-    let mut editor_state = use_signal(|| EditorState::from(LOREM_IPSUM));
-    tracing::info!("editor_state :{:?}", editor_state.read());
+    // プロンプトの1行
+    let mut editor_state = use_signal(|| EditorState::from(""));
     let mut theme = use_context::<Signal<Theme>>();
     let mut is_ime = use_signal(|| false);
+    let mut search_open = use_signal(|| false);
+    let mut search_query = use_signal(SearchQuery::default);
+    let mut palette_open = use_signal(|| false);
+    let mut vim = use_signal(Vim::default);
+    let mut keymap = use_signal(Keymap::default);
+    // 設定に保存したキーマップを読み込む
+    use_future(move || async move {
+        keymap.set(load_keymap().await);
+    });
+    // 検索バーを閉じた後にフォーカスを戻すためのエディタ本体
+    let mut editor_root: Signal<Option<Rc<MountedData>>> = use_signal(|| None);
     // let mut last_keys_vec: Signal<Vec<Code>> = use_signal(|| Vec::new());
 
     let mut ime_buffer = use_signal(String::new); // IMEの入力を一時的に保持するバッファ
@@ -171,61 +210,223 @@ pub fn HomeEditor() -> Element {
     // 全ての階層データを全て取得
     // (これも疑似ツリーをローカルストレージに保存)
     // これは階層情報として別で保存しておく
-    #[derive(Debug)]
-    enum MemoIdTree {
-        File(i32),
-        Directory(Vec<HashMap<i32, MemoIdTree>>),
-    }
-
-    let sample_allocate_dir_map: Vec<HashMap<i32, MemoIdTree>> = vec![
-        {
-            let mut map = HashMap::new();
-            map.insert(1, MemoIdTree::File(4));
-            map
-        },
-        {
-            let mut map = HashMap::new();
-            map.insert(
-                5,
-                MemoIdTree::Directory(vec![
-                    {
-                        let mut submap = HashMap::new();
-                        submap.insert(2, MemoIdTree::File(5));
-                        submap
-                    },
-                    {
-                        // 今回はさらに下があるかもしれないので、空の配列を入れている
-                        let mut submap = HashMap::new();
-                        submap.insert(3, MemoIdTree::Directory(vec![]));
-                        submap
-                    },
-                ]),
-            );
-            map
-        },
-    ];
-
-    tracing::info!("sample_allocate_dir_map: {:#?}", sample_allocate_dir_map);
-
-    // これいらなくなる予定
-    let sample_current_allocate_dir_map =
-        vec![HashMap::from([(1, "memo1")]), HashMap::from([(2, "memo2")])];
-
-    // lsコマンドで使う。HashMapをVecに変更
-    // ```rs
-    // println!("{:?}", converted); // ["memo1", "memo2"]
-    // ```
-    let converted: Vec<String> = sample_current_allocate_dir_map
-        .iter()
-        .flat_map(|map| map.values())
-        .map(|v| v.to_string())
-        .collect();
+    // (保存したものがなければこのサンプルを使う)
+    let sample_memo_tree = || {
+        let mut tree = MemoTree::new();
+        for (path, kind) in [
+            ("/memo1", NodeKind::Memo),
+            ("/memo2", NodeKind::Memo),
+            ("/notes", NodeKind::Folder),
+            ("/notes/memo3", NodeKind::Memo),
+        ] {
+            let _ = tree.create(path, kind);
+        }
+        tree
+    };
 
     // --------------------------------------------------------------------------------------------------
 
-    // pwd情報保持変数
+    // シェルのセッション (カレントディレクトリ, メモの階層)
     // グローバル管理した方が良い？
-    let mut pwd_info = use_signal(|| "".to_string());
+    let mut shell_context = use_signal(|| ShellContext {
+        tree: sample_memo_tree(),
+        ..ShellContext::default()
+    });
+    // メモの本文と階層の保存先
+    let storage = use_context::<Rc<dyn MemoStorage>>();
+    let sync = use_context::<MemoSync>();
+    // コマンドの履歴の保存先 (ログインしていればサーバー)
+    let mut history_storage = use_signal(HistoryStorage::default);
+    // 保存したメモの階層とコマンドの履歴を読み込む
+    use_future({
+        to_owned![storage];
+        move || {
+            let storage = storage.clone();
+            async move {
+                if let Some(tree) = storage.load_tree().await {
+                    shell_context.with_mut(|c| c.tree = tree);
+                }
+                let history = HistoryStorage::detect().await;
+                if let Some(loaded) = history.load().await {
+                    shell_context.with_mut(|c| c.history = loaded);
+                }
+                history_storage.set(history);
+            }
+        }
+    });
+    let shell_registry = use_hook(|| {
+        let mut registry = CommandRegistry::default();
+        registry.register(Grep::new(storage.clone()));
+        Rc::new(registry)
+    });
+    // grep の結果から開いた時のカーソルの位置
+    let mut jump = use_context::<Signal<Option<JumpTo>>>();
+    // 実行したコマンドと出力
+    let mut terminal = use_signal(Terminal::default);
+
+    // コマンドを実行し、コマンドと出力をプロンプトの上に積む
+    let run_shell = {
+        to_owned![shell_registry, storage];
+        move |line: String| {
+            let registry = shell_registry.clone();
+            let storage = storage.clone();
+            spawn(async move {
+                let mut context = shell_context();
+                let prompt = prompt(&context.pwd);
+                let result = registry.run(&line, &mut context).await;
+                if context.tree != shell_context.read().tree {
+                    storage.save_tree(&context.tree).await;
+                    // `rm` で消したメモの本文と、送っていない編集も消す
+                    let removed = removed_memos(&shell_context.read().tree, &context.tree);
+                    for memo_id in removed {
+                        storage.remove(memo_id).await;
+                        sync.forget(memo_id);
+                    }
+                }
+                if context.history != shell_context.read().history {
+                    history_storage
+                        .read()
+                        .save(&shell_context.read().history, &context.history);
+                }
+                shell_context.set(context);
+                match &result {
+                    Ok(ShellOutput::Open(memo_id)) => {
+                        navigator.push(Route::Editor { id: *memo_id });
+                    }
+                    // `cp` でコピーしたメモの本文も保存して送る
+                    Ok(ShellOutput::Copied(copied)) => {
+                        for &(memo_id, source_id) in copied {
+                            if let Some((title, snapshot)) =
+                                copy_memo(&*storage, memo_id, source_id).await
+                            {
+                                sync.enqueue(memo_id, &title, &snapshot.text);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                terminal.with_mut(|t| t.push(prompt, line, result));
+                scroll_line_into_view(editor_state.read().current_line);
+            });
+        }
+    };
+
+    // Tab補完の候補一覧。Tab以外のキーを押すと閉じる
+    let mut completion = use_signal(|| None::<CompletionMenu>);
+
+    // コマンドラインならカーソルの前の単語を補完する
+    // 補完できない行 (普通の文章) は false を返し、Tabでインデントする
+    let mut complete_shell = {
+        to_owned![shell_registry];
+        move |e: &mut EditorState| -> bool {
+            let cursor = (e.current_line, e.cursor_position);
+            // 続けてTabを押した場合は候補を順に切り替える
+            if let Some(menu) = completion.write().as_mut().filter(|m| m.cursor == cursor) {
+                let candidate = menu.select_next().to_string();
+                e.replace_before_cursor(menu.inserted, &candidate);
+                menu.inserted = candidate.graphemes(true).count();
+                menu.cursor = (e.current_line, e.cursor_position);
+                return true;
+            }
+
+            let Some(result) = complete(
+                &shell_registry,
+                &shell_context.read(),
+                &e.text_before_cursor(),
+            ) else {
+                completion.set(None);
+                return false;
+            };
+            match result.candidates.as_slice() {
+                [] => completion.set(None),
+                [candidate] => {
+                    e.replace_before_cursor(result.word_len(), candidate);
+                    completion.set(None);
+                }
+                _ => {
+                    // 共通部分まで入力し、候補を一覧にする
+                    let prefix = result.common_prefix();
+                    e.replace_before_cursor(result.word_len(), &prefix);
+                    completion.set(Some(CompletionMenu {
+                        candidates: result.candidates,
+                        active: None,
+                        inserted: prefix.graphemes(true).count(),
+                        cursor: (e.current_line, e.cursor_position),
+                    }));
+                }
+            }
+            true
+        }
+    };
+
+    // 上下キーで呼び出している履歴と Ctrl+R の検索
+    let mut recall = use_signal(Recall::default);
+    let mut reverse_search = use_signal(|| None::<ReverseSearch>);
+
+    // 上下キーと Ctrl+R でコマンドの履歴を呼び出す。処理したキーは true を返す
+    let mut handle_history_keys = move |event: &Event<KeyboardData>| -> bool {
+        let ctrl = event.modifiers().contains(Modifiers::CONTROL);
+        let context = shell_context.read();
+        let history = &context.history;
+
+        // 検索中はキー入力を検索語にする
+        if let Some(mut search) = reverse_search() {
+            let accepted = match event.key() {
+                Key::Character(c) if ctrl && c.eq_ignore_ascii_case("r") => {
+                    search.older(history);
+                    None
+                }
+                Key::Character(c) if !ctrl && !event.modifiers().contains(Modifiers::META) => {
+                    search.input(history, &c);
+                    None
+                }
+                Key::Backspace => {
+                    search.backspace(history);
+                    None
+                }
+                Key::Escape => {
+                    reverse_search.set(None);
+                    return true;
+                }
+                Key::Shift | Key::Control | Key::Alt | Key::Meta => return true,
+                // それ以外のキーで一致した行を現在行に入れる
+                _ => Some(search.matched_line(history).map(str::to_string)),
+            };
+            let Some(line) = accepted else {
+                reverse_search.set(Some(search));
+                return true;
+            };
+            reverse_search.set(None);
+            if let Some(line) = line {
+                editor_state.with_mut(|e| e.set_current_line_text(&line));
+            }
+            // Enter はそのまま実行する
+            return event.code() != Code::Enter;
+        }
+
+        if ctrl && event.code() == Code::KeyR {
+            reverse_search.set(Some(ReverseSearch::default()));
+            return true;
+        }
+
+        if !event.modifiers().is_empty() || !matches!(event.code(), Code::ArrowUp | Code::ArrowDown)
+        {
+            return false;
+        }
+        editor_state.with_mut(|e| {
+            let text = e.current_line_text();
+            let line = if event.code() == Code::ArrowUp {
+                recall.write().older(history, &text)
+            } else {
+                recall.write().newer(history)
+            };
+            if let Some(line) = &line {
+                e.set_current_line_text(line);
+            }
+            // 履歴がない場合は普通にカーソルを動かす
+            line.is_some() || recall.read().is_active()
+        })
+    };
 
     let handle_composition_start = move |event: CompositionEvent| {
         let start_data = event.data().data();
@@ -245,42 +446,44 @@ pub fn HomeEditor() -> Element {
     let handle_composition_end = move |event: CompositionEvent| {
         tracing::info!("IME End: {:?}", event.data);
         let final_text = event.data().data();
-        editor_state.with_mut(|e| e.insert_text(&final_text));
+        editor_state.with_mut(|e| {
+            e.insert_text(&final_text);
+            // 確定した文字列は単独でundoできるようにする
+            e.break_undo_group();
+        });
         ime_buffer.set(String::new());
     };
 
     let handle_clicks = move |event: Event<MouseData>| {
-        // Use `use_eval` to create a runner for JavaScript execution
-        let mut eval = document::eval(
-            r#"
-            let ran = document.caretRangeFromPoint({x},{y});
-            let el = ran.startContainer;
-            let par = el.parentElement;
-            return [
-                parseInt(par.getAttribute('line')) || -1,
-                [...par.childNodes].indexOf(el) + 1,
-            ];
-            "#,
-        );
-
-        let coords = event.page_coordinates();
-        let x = coords.x;
-        let y = coords.y;
-
-        eval.send(format!("{} {}", x, y).to_string()).unwrap();
+        completion.set(None);
+        let coords = event.client_coordinates();
+        // Shift + クリックで選択範囲を広げる / Alt + クリックでカーソルを追加する
+        let extend = event.modifiers().contains(Modifiers::SHIFT);
+        let add_cursor = event.modifiers().contains(Modifiers::ALT);
 
         spawn(async move {
-            if let Ok(res) = eval.recv::<Value>().await {
-                let line = res.get(0).unwrap().as_i64().unwrap();
-                let cursor = res.get(1).unwrap().as_i64().unwrap();
-
-                if line < 0 {
-                    return;
+            if let Some((line, cursor)) = caret_from_point(coords.x, coords.y).await {
+                if add_cursor {
+                    editor_state.with_mut(|e| e.add_cursor(line, cursor));
+                } else if extend {
+                    editor_state.with_mut(|e| e.select_to(line, cursor));
+                } else {
+                    editor_state.with_mut(|e| {
+                        e.clear_cursors();
+                        e.set_cursor(line, cursor);
+                    });
                 }
+            }
+        });
+    };
 
-                editor_state.with_mut(|e| e.set_cursor(line as usize, cursor as usize));
+    // ドラッグを離した位置までを選択する
+    let handle_mouse_up = move |event: Event<MouseData>| {
+        let coords = event.client_coordinates();
 
-                println!("{line}x{cursor}");
+        spawn(async move {
+            if let Some((line, cursor)) = caret_from_point(coords.x, coords.y).await {
+                editor_state.with_mut(|e| e.select_to(line, cursor));
             }
         });
     };
@@ -291,28 +494,182 @@ pub fn HomeEditor() -> Element {
         number_list_re.is_match(line)
     }
 
-    let handle_global_keys = move |event: Event<KeyboardData>| {
-        // switch themes with Cmd + K
-        if event.modifiers().contains(Modifiers::META | Modifiers::ALT)
-            && event.code() == Code::KeyK
-        {
-            let new_theme = match *theme.read() {
-                Theme::Default => Theme::Readonly,
-                Theme::Readonly => Theme::Dev,
-                Theme::Dev => Theme::Default,
-            };
-            theme.set(new_theme);
-            event.stop_propagation();
+    // キーマップのコマンドを実行する
+    let mut run_command = move |command: EditorCommand, extend: bool| {
+        if editor_state.with_mut(|e| command.apply(e, extend)) {
             return;
         }
-        // toggle IME
-        if event.modifiers().contains(Modifiers::META) && event.code() == Code::KeyK {
-            let toggle = !*is_ime.read();
-            is_ime.set(toggle);
+        match command {
+            EditorCommand::ToggleTheme => {
+                let new_theme = match *theme.read() {
+                    Theme::Default => Theme::Readonly,
+                    Theme::Readonly => Theme::Dev,
+                    Theme::Dev => Theme::Default,
+                };
+                theme.set(new_theme);
+            }
+            EditorCommand::ToggleIme => {
+                let toggle = !*is_ime.read();
+                is_ime.set(toggle);
+            }
+            EditorCommand::ToggleVim => vim.with_mut(|v| v.toggle()),
+            EditorCommand::OpenCommandPalette => palette_open.set(true),
+            // 検索バーを開く。1行以内の選択中の文字列を検索語にする
+            EditorCommand::Find => {
+                let selected = editor_state.read().selected_text();
+                if let Some(text) = selected.filter(|text| !text.contains('\n')) {
+                    search_query.with_mut(|q| q.pattern = text);
+                }
+                search_open.set(true);
+            }
+            EditorCommand::Copy | EditorCommand::CopyMarkdown | EditorCommand::Cut => {
+                let text = editor_state.with(|e| {
+                    if command == EditorCommand::CopyMarkdown {
+                        e.selected_markdown()
+                    } else {
+                        e.selected_text()
+                    }
+                });
+                if let Some(text) = text {
+                    write_clipboard(text);
+                    if command == EditorCommand::Cut {
+                        editor_state.with_mut(|e| e.delete_selection());
+                    }
+                }
+            }
+            EditorCommand::KillLine => {
+                let killed = editor_state.with_mut(|e| e.kill_line());
+                if !killed.is_empty() {
+                    write_clipboard(killed);
+                }
+            }
+            EditorCommand::Paste => {
+                spawn(async move {
+                    if let Some(text) = read_clipboard().await {
+                        editor_state.with_mut(|e| e.paste(&text));
+                    }
+                });
+            }
+            // 画面に収まる行数だけ移動する
+            EditorCommand::PageUp | EditorCommand::PageDown => {
+                let dir = if command == EditorCommand::PageDown {
+                    Direction::Forward
+                } else {
+                    Direction::Backward
+                };
+                spawn(async move {
+                    let count = visible_line_count().await;
+                    editor_state.with_mut(|e| {
+                        if extend {
+                            e.each_cursor(|e| e.select_with(|e| e.move_lines(dir, count)));
+                        } else {
+                            e.each_cursor(|e| e.move_lines(dir, count));
+                        }
+                    });
+                    scroll_line_into_view(editor_state.read().current_line);
+                });
+            }
+            // ホームはコマンドを実行するたびに階層を保存するので、Save で保存するものはない
+            _ => (),
+        }
+    };
+
+    // Vimのコマンドライン・コマンドパレットのコマンドを実行する
+    let mut run_ex_command = move |command: ExCommand| match command {
+        ExCommand::Write => (),
+        ExCommand::Quit | ExCommand::WriteQuit => {
+            navigator.push("/");
+        }
+        ExCommand::Edit(memo) => {
+            let found = shell_context.read().find_memo(&memo);
+            match found {
+                Ok(memo_id) => {
+                    navigator.push(Route::Editor { id: memo_id });
+                }
+                Err(err) => vim.with_mut(|v| v.message = err.to_string()),
+            }
+        }
+        ExCommand::Unknown(_) => (),
+    };
+
+    // コマンドパレットを閉じてエディタにフォーカスを戻す
+    let mut close_palette = move || {
+        palette_open.set(false);
+        spawn(async move {
+            if let Some(root) = editor_root() {
+                let _ = root.set_focus(true).await;
+            }
+        });
+    };
+
+    // コマンドパレットで選んだコマンドを実行する
+    let run_palette_action = {
+        to_owned![run_ex_command];
+        move |action: PaletteAction| {
+            close_palette();
+            match action {
+                PaletteAction::Editor(command) => run_command(command, false),
+                PaletteAction::Ex(command) => run_ex_command(command),
+                PaletteAction::Pill(pill) => editor_state.with_mut(|e| e.insert_pill(pill)),
+                PaletteAction::Shell(text) => editor_state.with_mut(|e| e.insert_text(&text)),
+            }
+        }
+    };
+
+    let handle_global_keys = move |event: Event<KeyboardData>| {
+        // Tab以外のキーで補完の一覧を閉じる (修飾キーだけの場合は閉じない)
+        let modifier_only = matches!(
+            event.key(),
+            Key::Shift | Key::Control | Key::Alt | Key::Meta
+        );
+        if event.code() != Code::Tab && !modifier_only && completion.read().is_some() {
+            completion.set(None);
+        }
+
+        // Vimモード。Cmd を押している場合は通常のショートカットとして扱う
+        if !event.modifiers().contains(Modifiers::META) {
+            let ctrl = event.modifiers().contains(Modifiers::CONTROL);
+            let action =
+                vim.with_mut(|v| editor_state.with_mut(|e| v.handle_key(&event.key(), ctrl, e)));
+            match action {
+                VimAction::PassThrough => (),
+                VimAction::Handled => {
+                    scroll_line_into_view(editor_state.read().current_line);
+                    event.stop_propagation();
+                    return;
+                }
+                VimAction::Command(command) => {
+                    run_ex_command(command);
+                    event.stop_propagation();
+                    return;
+                }
+            }
+        }
+
+        // コマンドの履歴
+        if handle_history_keys(&event) {
+            scroll_line_into_view(editor_state.read().current_line);
+            event.prevent_default();
             event.stop_propagation();
             return;
         }
 
+        // キーマップに割り当てたコマンド
+        let chord = KeyChord::new(event.code(), event.modifiers());
+        match keymap.with_mut(|k| k.feed(chord)) {
+            KeyInput::Command { command, extend } => {
+                run_command(command, extend);
+                event.stop_propagation();
+                return;
+            }
+            // 続きのキーを待つ (C-x C-s など)
+            KeyInput::Pending => {
+                event.stop_propagation();
+                return;
+            }
+            KeyInput::Unbound => (),
+        }
+
         // TODO
         // if event.modifiers().contains(Modifiers::SHIFT) && event.code() == Code::Enter {
         //     editor_state.with_mut(|e| e.insert_element());
@@ -320,13 +677,6 @@ pub fn HomeEditor() -> Element {
         //     return;
         // }
 
-        // TODO : maybe del
-        if event.modifiers().contains(Modifiers::META) && event.code() == Code::KeyA {
-            editor_state.with_mut(|e| e.insert_pill("C-A"));
-            event.stop_propagation();
-            return;
-        }
-
         // IME mode
         if *is_ime.read() {
             // IME使わない想定
@@ -340,85 +690,26 @@ pub fn HomeEditor() -> Element {
                         for Code::F2 => e.insert_pill("F2"),
                         for Code::F3 => e.insert_pill("F3"),
                         for Code::F4 => e.insert_pill("F4"),
-                        for Code::Delete => e.delete(Direction::Forward),
-                        for Code::Backspace => e.delete(Direction::Backward),
                         for Code::Space => {
                             e.insert_char(char::from_u32(0x00A0).unwrap());
                             let eval = document::eval("window.event.preventDefault();");
                             eval.send(serde_json::Value::Null).unwrap();
                         },
-                        for Code::ArrowUp => e.go_to_line(Direction::Backward),
-                        for Code::ArrowDown => e.go_to_line(Direction::Forward),
-                        for Code::ArrowRight => e.move_cursor(Direction::Forward),
-                        for Code::ArrowLeft => e.move_cursor(Direction::Backward),
                         for Code::Enter => {
-                            // 改行前に現在行を確認
-                            let current_line_idx = e.current_line;
-                            let current_line_content = e.get_line_content(current_line_idx);
-
-                            // # コマンド関連
-                            //    - ls
-                            //    - pwd
-                            //    - cd
-                            //    - vim
-                            //    - mkdir
-                            //    - touch
-                            if current_line_content == "ls❮" {
-                              e.insert_ls(&converted);
-                            }
-                            if current_line_content == "pwd❮" {
-                              todo!()
-                            }
-                            if current_line_content.starts_with("cd\u{00A0}") {
-                              if let Some(stripped) = current_line_content.strip_prefix("cd\u{00A0}") {
-                                  if let Some(idx) = stripped.find('❮') {
-                                      // ❮より前の部分を抜き出し (例: ".", "..", "../..", "some_dir", "dir/subdir", etc.)
-                                      let extracted = &stripped[..idx];
-
-                                      // 現在のパスを取得
-                                      let current = pwd_info.read().to_string();
-
-                                      // 新しいパスを計算
-                                      let new_path = apply_cd_command(&current, extracted);
-
-                                      // pwd_info を更新
-                                      pwd_info.set(new_path);
-                                  }
-                              }
-                            }
-                            if current_line_content.starts_with("vim\u{00A0}") {
-                                if let Some(stripped) = current_line_content.strip_prefix("vim\u{00A0}") {
-                                    if let Some(idx) = stripped.find('❮') {
-                                        // 'vim\u{00A0}'から'❮' シンボルまでの文字抽出
-                                        let extracted = &stripped[..idx];
-
-                                        if let Some(found_key) = sample_current_allocate_dir_map.iter().find_map(|hm| {
-                                            hm.iter()
-                                                .find_map(|(k, v)| if *v == extracted { Some(k) } else { None })
-                                        }) {
-                                            navigator.push(format!("memo/{}", found_key));
-                                        } else {
-                                            e.insert_text_not_match(extracted ," is no match found");
-                                        }
-                                    }
-                                }
-                            }
-                            if current_line_content == "mkdir\u{00A0}❮" {
-                              todo!()
-                            }
-                            if current_line_content == "touch\u{00A0}❮" {
-                              todo!()
-                            }
-
-                            // 改行処理
-                            e.next_line_or_new();
-
-                            // 改行時に前の行がリストなら、次の行もリストを挿入
-                            if let Some(next_item) = nextListItem(&current_line_content) {
-                                e.insert_text(&next_item);
-                            }
+                            // プロンプトの行を実行し、入力を空に戻す
+                            recall.write().reset();
+                            let command_line = e.current_line_text();
+                            *e = EditorState::from("");
+                            run_shell(command_line);
                         },
                         for Code::Tab => {
+                            // コマンドラインではコマンド名とパスを補完する
+                            if complete_shell(e) {
+                                let eval = document::eval("window.event.preventDefault();");
+                                eval.send(serde_json::Value::Null).unwrap();
+                                return;
+                            }
+
                             // Tabキー押下時の処理を改修
                             let current_line_idx = e.current_line;
                             let current_line_content = e.get_line_content(current_line_idx);
@@ -465,6 +756,36 @@ pub fn HomeEditor() -> Element {
 
     let (current_line, current_position) =
         editor_state.with(|e| (e.current_line, e.cursor_position));
+    let vim_status = vim.read().status();
+    let prompt_label = prompt(&shell_context.read().pwd);
+    let pending_keys = keymap.read().pending();
+    let reverse_search_status = reverse_search.read().as_ref().map(|search| {
+        let history = &shell_context.read().history;
+        match search.matched_line(history) {
+            Some(line) => format!("(reverse-i-search)`{}': {line}", search.query),
+            None if search.query.is_empty() => "(reverse-i-search)`': ".to_string(),
+            None => format!("(failed reverse-i-search)`{}': ", search.query),
+        }
+    });
+    // コマンドパレットに表示するコマンド
+    let palette_commands = if *palette_open.read() {
+        {
+            let mut commands = editor_commands(&keymap.read());
+            commands.extend(shell_commands(&shell_registry));
+            commands
+        }
+    } else {
+        Vec::new()
+    };
+    // 検索バーを開いている間は一致をハイライトする
+    let search_matches = if *search_open.read() {
+        editor_state
+            .read()
+            .find_matches(&search_query.read())
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
 
     rsx! {
         div {
@@ -475,6 +796,39 @@ pub fn HomeEditor() -> Element {
             oncompositionupdate: handle_composition_update,
             oncompositionend: handle_composition_end,
             onkeydown: handle_global_keys,
+            onmouseup: handle_mouse_up,
+            onmounted: move |event| editor_root.set(Some(event.data())),
+            if *palette_open.read() {
+                CommandPalette {
+                    commands: palette_commands,
+                    on_run: run_palette_action,
+                    on_close: move |_| close_palette(),
+                }
+            }
+            if *search_open.read() {
+                SearchBar {
+                    editor_state,
+                    query: search_query,
+                    on_close: move |_| {
+                        search_open.set(false);
+                        spawn(async move {
+                            if let Some(root) = editor_root() {
+                                let _ = root.set_focus(true).await;
+                            }
+                        });
+                    },
+                }
+            }
+            TerminalOutput {
+                terminal,
+                on_open: move |link: MemoLink| {
+                    jump.set(link.line.map(|line| JumpTo {
+                        memo_id: link.memo_id,
+                        line,
+                    }));
+                    navigator.push(Route::Editor { id: link.memo_id });
+                },
+            }
             {
                 editor_state
                     .read()
@@ -487,17 +841,26 @@ pub fn HomeEditor() -> Element {
                             "background-color: white;"
                         };
                         let opacity = if current { "100%" } else { "20%" };
-                        let (rendered_line, line_style): (Vec<(String, String)>, String) = {
-                            let line_content = cursorView(line, *is_ime.read());
-                            let (line_text, combined_style) = markdown_view(&line_content);
-                            (line_text, combined_style)
+                        // 1行目にプロンプトを表示する
+                        let line_prompt = if line_number == 1 { prompt_label.as_str() } else { "" };
+                        let selections = editor_state.read().selections_in_line(line_number);
+                        let highlights = editor_state.read().matches_in_line(&search_matches, line_number);
+                        // Tab補完の候補は現在行の下に表示する
+                        let menu = completion.read().clone().filter(|_| current);
+                        let (rendered_line, line_style): (Vec<(String, String)>, String) = match selections.is_empty() && highlights.is_empty() {
+                            false => (selectionView(&line, &selections, &highlights, *is_ime.read()), String::new()),
+                            true => {
+                                let line_content = cursorView(&line, *is_ime.read());
+                                let (line_text, combined_style) = markdown_view(&line_content);
+                                (line_text, combined_style)
+                            }
                         };
                         rsx! {
                             div { style: "padding-right: 5px; text-align: right;",
-                                span { style: "opacity: {opacity};", "{line_number}" }
+                                span { style: "opacity: {opacity}; white-space: pre;", "{line_prompt}" }
                             }
                             div {
-                                style: "{line_style} {background}",
+                                style: "{line_style} {background} position: relative;",
                                 id: "L{line_number}",
                                 "line": "{line_number}",
                                 onmousedown: handle_clicks,
@@ -522,15 +885,25 @@ pub fn HomeEditor() -> Element {
                                             }
                                         })
                                 }
+                                if let Some(menu) = menu {
+                                    CompletionPopup {
+                                        candidates: menu.candidates,
+                                        active: menu.active,
+                                        on_select: move |candidate: String| {
+                                            editor_state.with_mut(|e| e.replace_before_cursor(menu.inserted, &candidate));
+                                            completion.set(None);
+                                        },
+                                    }
+                                }
                             }
                         }
                     })
             }
         }
-        // ------------デバッグ用-------------------
-        div { "pwd_info : {pwd_info}" }
-        // ---------------------------------------
-        div { "Line: {current_line} Position: {current_position}" }
+        div { "{vim_status} {pending_keys} Line: {current_line} Position: {current_position}" }
+        if let Some(status) = reverse_search_status {
+            div { "{status}" }
+        }
     }
 }
 
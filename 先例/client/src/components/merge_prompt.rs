@@ -0,0 +1,50 @@
+use dioxus::prelude::*;
+
+use crate::r#fn::memo_sync::{Conflict, Resolution};
+
+const PROMPT_STYLE: &str = r#"
+    grid-column: 1 / -1;
+    position: sticky;
+    top: 0;
+    z-index: 1;
+    display: flex;
+    flex-wrap: wrap;
+    gap: 4px;
+    align-items: center;
+    padding: 4px;
+    background-color: #fef3c7;
+    border-bottom: 1px solid #f59e0b;
+    font-family: sans-serif;
+    font-size: 12px;
+"#;
+
+/// サーバーのメモが他の端末で更新されていた場合に、どちらを残すか選ぶ
+/// 選ぶまではこのメモの編集をサーバーに送らない
+#[component]
+pub fn MergePrompt(conflict: Conflict, on_resolve: EventHandler<Resolution>) -> Element {
+    let local_lines = conflict.local.lines().count();
+    let remote_lines = conflict.remote.body.lines().count();
+
+    rsx! {
+        div { style: "{PROMPT_STYLE}",
+            span {
+                "他の端末で更新されています (この端末: {local_lines} 行 / サーバー: {remote_lines} 行)"
+            }
+            button {
+                title: "この端末の内容でサーバーを上書き",
+                onclick: move |_| on_resolve.call(Resolution::KeepLocal),
+                "この端末の内容を残す"
+            }
+            button {
+                title: "この端末の編集を捨てる",
+                onclick: move |_| on_resolve.call(Resolution::KeepRemote),
+                "サーバーの内容を使う"
+            }
+            button {
+                title: "違う行を両方残して編集する",
+                onclick: move |_| on_resolve.call(Resolution::Merge),
+                "マージ"
+            }
+        }
+    }
+}
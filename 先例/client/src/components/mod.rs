@@ -1,5 +1,10 @@
 pub mod autocomplete;
+pub mod command_palette;
+pub mod completion_popup;
 pub mod editor;
 pub mod editor_plugin;
 pub mod home_editor;
+pub mod merge_prompt;
+pub mod search_bar;
+pub mod terminal_output;
 pub mod theme_switcher;
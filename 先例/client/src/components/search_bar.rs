@@ -0,0 +1,186 @@
+use dioxus::prelude::*;
+use keyboard_types::{Code, Modifiers};
+
+use crate::components::editor_plugin::viewport::scroll_line_into_view;
+use crate::r#fn::editor_state::EditorState;
+use crate::r#fn::search::SearchQuery;
+use crate::types::enums::Direction;
+
+const BAR_STYLE: &str = r#"
+    grid-column: 1 / -1;
+    position: sticky;
+    top: 0;
+    z-index: 1;
+    display: flex;
+    flex-wrap: wrap;
+    gap: 4px;
+    align-items: center;
+    padding: 4px;
+    background-color: #f6f6f6;
+    border-bottom: 1px solid #ddd;
+    font-family: sans-serif;
+    font-size: 12px;
+"#;
+
+fn toggle_style(active: bool) -> &'static str {
+    if active {
+        "border: 1px solid #3b82f6; background-color: #dbeafe;"
+    } else {
+        "border: 1px solid #ccc; background-color: white;"
+    }
+}
+
+/// エディタ内の検索・置換バー (Cmd + F)
+/// 置換は `EditorState` を通すのでundoできる
+#[component]
+pub fn SearchBar(
+    editor_state: Signal<EditorState>,
+    query: Signal<SearchQuery>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let mut replacement = use_signal(String::new);
+    let mut show_replace = use_signal(|| false);
+
+    let matches = editor_state.read().find_matches(&query.read());
+    let invalid = matches.is_err();
+    let count = matches.map(|matches| matches.len()).unwrap_or(0);
+    let status = if invalid {
+        "正規表現が不正です".to_string()
+    } else if query.read().is_empty() {
+        String::new()
+    } else {
+        format!("{count} 件")
+    };
+    let input_style = if invalid {
+        "border: 1px solid #ef4444;"
+    } else {
+        "border: 1px solid #ccc;"
+    };
+
+    let mut find = move |rel: Direction| {
+        let found = editor_state.with_mut(|e| e.find_next(&query.read(), rel));
+        if let Ok(true) = found {
+            scroll_line_into_view(editor_state.read().current_line);
+        }
+    };
+
+    let mut replace_one = move || {
+        let replaced =
+            editor_state.with_mut(|e| e.replace_current(&query.read(), &replacement.read()));
+        if let Ok(true) = replaced {
+            scroll_line_into_view(editor_state.read().current_line);
+        }
+    };
+
+    let mut replace_all = move || {
+        let replaced = editor_state.with_mut(|e| e.replace_all(&query.read(), &replacement.read()));
+        tracing::info!("Replaced: {:?}", replaced);
+    };
+
+    // 入力欄のキー入力はエディタに渡さない
+    let handle_find_keys = move |event: Event<KeyboardData>| {
+        event.stop_propagation();
+        match event.code() {
+            Code::Enter if event.modifiers().contains(Modifiers::SHIFT) => {
+                find(Direction::Backward)
+            }
+            Code::Enter => find(Direction::Forward),
+            Code::Escape => on_close.call(()),
+            _ => (),
+        }
+    };
+
+    let handle_replace_keys = move |event: Event<KeyboardData>| {
+        event.stop_propagation();
+        match event.code() {
+            // Cmd(Ctrl) + Enter: すべて置換
+            Code::Enter
+                if event.modifiers().contains(Modifiers::META)
+                    || event.modifiers().contains(Modifiers::CONTROL) =>
+            {
+                replace_all()
+            }
+            Code::Enter => replace_one(),
+            Code::Escape => on_close.call(()),
+            _ => (),
+        }
+    };
+
+    let (case_sensitive, whole_word, regex) =
+        query.with(|q| (q.case_sensitive, q.whole_word, q.regex));
+
+    rsx! {
+        div { style: "{BAR_STYLE}",
+            button {
+                title: "置換を表示",
+                onclick: move |_| show_replace.toggle(),
+                if *show_replace.read() { "▾" } else { "▸" }
+            }
+            input {
+                style: "{input_style}",
+                placeholder: "検索",
+                value: "{query.read().pattern}",
+                onmounted: move |event| async move {
+                    let _ = event.set_focus(true).await;
+                },
+                oninput: move |event| query.with_mut(|q| q.pattern = event.value()),
+                onkeydown: handle_find_keys,
+            }
+            button {
+                title: "大文字と小文字を区別",
+                style: toggle_style(case_sensitive),
+                onclick: move |_| query.with_mut(|q| q.case_sensitive = !q.case_sensitive),
+                "Aa"
+            }
+            button {
+                title: "単語単位",
+                style: toggle_style(whole_word),
+                onclick: move |_| query.with_mut(|q| q.whole_word = !q.whole_word),
+                "W"
+            }
+            button {
+                title: "正規表現",
+                style: toggle_style(regex),
+                onclick: move |_| query.with_mut(|q| q.regex = !q.regex),
+                ".*"
+            }
+            span { "{status}" }
+            button {
+                title: "前の一致 (Shift + Enter)",
+                disabled: count == 0,
+                onclick: move |_| find(Direction::Backward),
+                "↑"
+            }
+            button {
+                title: "次の一致 (Enter)",
+                disabled: count == 0,
+                onclick: move |_| find(Direction::Forward),
+                "↓"
+            }
+            button { title: "閉じる (Esc)", onclick: move |_| on_close.call(()), "×" }
+            if *show_replace.read() {
+                div { style: "flex-basis: 100%; display: flex; gap: 4px; padding-left: 22px;",
+                    input {
+                        style: "border: 1px solid #ccc;",
+                        placeholder: "置換",
+                        value: "{replacement}",
+                        oninput: move |event| replacement.set(event.value()),
+                        onkeydown: handle_replace_keys,
+                    }
+                    button {
+                        title: "置換 (Enter)",
+                        disabled: count == 0,
+                        onclick: move |_| replace_one(),
+                        "置換"
+                    }
+                    button {
+                        title: "すべて置換 (Cmd + Enter)",
+                        disabled: count == 0,
+                        onclick: move |_| replace_all(),
+                        "すべて置換"
+                    }
+                }
+            }
+        }
+    }
+}
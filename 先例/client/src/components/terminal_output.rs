@@ -0,0 +1,86 @@
+use dioxus::prelude::*;
+
+use crate::r#fn::shell::command::{MemoLink, OutputBlock};
+use crate::r#fn::shell::terminal::Terminal;
+
+/// 実行したコマンドと出力の一覧 (編集できない)
+/// メモへのリンクを選ぶと `on_open` に渡す
+#[component]
+pub fn TerminalOutput(terminal: Signal<Terminal>, on_open: EventHandler<MemoLink>) -> Element {
+    let entries = terminal.read().entries().to_vec();
+
+    rsx! {
+        div { style: "grid-column: 1 / -1; user-select: text;",
+            for entry in entries {
+                div {
+                    div {
+                        span { style: "color: #888; margin-right: 8px;", "{entry.prompt}" }
+                        span { style: "white-space: pre;", "{entry.command}" }
+                    }
+                    for block in entry.output {
+                        OutputBlockView { block, on_open }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 出力の1ブロック
+#[component]
+fn OutputBlockView(block: OutputBlock, on_open: EventHandler<MemoLink>) -> Element {
+    match block {
+        OutputBlock::Text(lines) => rsx! {
+            for line in lines {
+                div { style: "white-space: pre; min-height: 1em;", "{line}" }
+            }
+        },
+        OutputBlock::Error(message) => rsx! {
+            div { style: "white-space: pre; color: #d32f2f;", "{message}" }
+        },
+        OutputBlock::Table(rows) => rsx! {
+            table { style: "border-collapse: collapse;",
+                for row in rows {
+                    tr {
+                        for cell in row {
+                            td { style: "padding: 0 12px 0 0; white-space: pre; {cell_align(&cell)}",
+                                "{cell}"
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        OutputBlock::Links(links) => rsx! {
+            {
+                links
+                    .into_iter()
+                    .map(|link| {
+                        let label = link.label.clone();
+                        rsx! {
+                            div {
+                                a {
+                                    href: "#",
+                                    style: "color: #1a73e8; white-space: pre;",
+                                    onclick: move |event| {
+                                        event.prevent_default();
+                                        on_open.call(link.clone());
+                                    },
+                                    "{label}"
+                                }
+                            }
+                        }
+                    })
+            }
+        },
+    }
+}
+
+/// 数値のセルは右に寄せる (`ls -l` のサイズなど)
+fn cell_align(cell: &str) -> &'static str {
+    if !cell.is_empty() && cell.chars().all(|c| c.is_ascii_digit()) {
+        "text-align: right;"
+    } else {
+        ""
+    }
+}
@@ -1,4 +1,5 @@
-pub const LOREM_IPSUM: &str = r#""#;
+/// APIサーバーのURL
+pub const API_URL: &str = "http://localhost:8080";
 
 pub const HEAD: &str = r#"
 <style type="text/css">
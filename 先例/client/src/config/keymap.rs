@@ -0,0 +1,85 @@
+use crate::r#fn::editor_command::EditorCommand::{self, *};
+
+/// デフォルトのキー割り当て
+/// Shift 付きの割り当てがないカーソル移動は、Shift を押すと選択範囲を広げる
+pub const DEFAULT_KEYMAP: &[(&str, EditorCommand)] = &[
+    // 表示
+    ("Cmd+Alt+K", ToggleTheme),
+    ("Cmd+K", ToggleIme),
+    ("Cmd+Alt+V", ToggleVim),
+    ("Cmd+Shift+P", OpenCommandPalette),
+    ("Cmd+S", Save),
+    // 履歴
+    ("Cmd+Z", Undo),
+    ("Ctrl+Z", Undo),
+    ("Cmd+Shift+Z", Redo),
+    ("Ctrl+Shift+Z", Redo),
+    // 検索・選択
+    ("Cmd+F", Find),
+    ("Ctrl+F", Find),
+    ("Cmd+A", SelectAll),
+    // クリップボード
+    ("Cmd+C", Copy),
+    ("Ctrl+C", Copy),
+    ("Cmd+Shift+C", CopyMarkdown),
+    ("Ctrl+Shift+C", CopyMarkdown),
+    ("Cmd+X", Cut),
+    ("Ctrl+X", Cut),
+    ("Cmd+V", Paste),
+    ("Ctrl+V", Paste),
+    // マルチカーソル
+    ("Alt+Shift+ArrowUp", AddCursorAbove),
+    ("Alt+Shift+ArrowDown", AddCursorBelow),
+    ("Cmd+D", AddNextOccurrence),
+    ("Escape", ClearCursors),
+    // カーソル移動
+    ("ArrowLeft", MoveLeft),
+    ("ArrowRight", MoveRight),
+    ("ArrowUp", MoveUp),
+    ("ArrowDown", MoveDown),
+    ("Alt+ArrowLeft", WordLeft),
+    ("Alt+ArrowRight", WordRight),
+    ("Cmd+ArrowLeft", LineStart),
+    ("Cmd+ArrowRight", LineEnd),
+    ("Cmd+ArrowUp", DocumentStart),
+    ("Cmd+ArrowDown", DocumentEnd),
+    ("Home", LineStart),
+    ("End", LineEnd),
+    ("Cmd+Home", DocumentStart),
+    ("Cmd+End", DocumentEnd),
+    ("PageUp", PageUp),
+    ("PageDown", PageDown),
+    // 削除
+    ("Backspace", DeleteBackward),
+    ("Delete", DeleteForward),
+    ("Alt+Backspace", DeleteWordBackward),
+    ("Ctrl+Backspace", DeleteWordBackward),
+    ("Alt+Delete", DeleteWordForward),
+    ("Ctrl+Delete", DeleteWordForward),
+    ("Cmd+Backspace", DeleteToLineStart),
+    ("Cmd+Delete", DeleteToLineEnd),
+];
+
+/// Emacsのキー割り当て。デフォルトに重ねて使う
+pub const EMACS_KEYMAP: &[(&str, EditorCommand)] = &[
+    ("C-a", LineStart),
+    ("C-e", LineEnd),
+    ("C-f", MoveRight),
+    ("C-b", MoveLeft),
+    ("C-n", MoveDown),
+    ("C-p", MoveUp),
+    ("M-f", WordRight),
+    ("M-b", WordLeft),
+    ("C-v", PageDown),
+    ("M-v", PageUp),
+    ("C-d", DeleteForward),
+    ("M-d", DeleteWordForward),
+    ("C-k", KillLine),
+    ("C-y", Paste),
+    ("M-w", Copy),
+    ("C-w", Cut),
+    ("C-/", Undo),
+    ("C-g", ClearCursors),
+    ("C-x C-s", Save),
+    ("C-x h", SelectAll),
+];
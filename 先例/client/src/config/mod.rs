@@ -1,2 +1,3 @@
 pub mod constants;
 pub mod kana_map;
+pub mod keymap;
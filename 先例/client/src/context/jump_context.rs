@@ -0,0 +1,24 @@
+use dioxus::prelude::*;
+
+/// 開いた後にカーソルを置く位置 (`grep` の結果から開いた場合)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JumpTo {
+    pub memo_id: i32,
+    /// 1から数える行
+    pub line: usize,
+}
+
+#[derive(Clone, Props, PartialEq)]
+pub struct JumpProviderProps {
+    children: Element,
+}
+
+/// 画面をまたいでカーソルの位置を渡す。`Editor` が開いた時に読んで消す
+#[component]
+pub fn JumpProvider(props: JumpProviderProps) -> Element {
+    use_context_provider(|| Signal::new(None::<JumpTo>));
+
+    rsx! {
+        {&props.children}
+    }
+}
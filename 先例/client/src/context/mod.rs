@@ -1 +1,4 @@
+pub mod jump_context;
+pub mod storage_context;
+pub mod sync_context;
 pub mod theme_context;
@@ -0,0 +1,21 @@
+use dioxus::prelude::*;
+use std::rc::Rc;
+
+use crate::components::editor_plugin::memo_storage::LocalMemoStorage;
+use crate::r#fn::memo_storage::MemoStorage;
+
+#[derive(Clone, Props, PartialEq)]
+pub struct StorageProviderProps {
+    children: Element,
+}
+
+/// メモの保存先。`use_context::<Rc<dyn MemoStorage>>()` で取得する
+/// 今はログインしていないユーザー向けのlocalStorageだけ
+#[component]
+pub fn StorageProvider(props: StorageProviderProps) -> Element {
+    use_context_provider(|| Rc::new(LocalMemoStorage) as Rc<dyn MemoStorage>);
+
+    rsx! {
+        {&props.children}
+    }
+}
@@ -0,0 +1,95 @@
+use dioxus::prelude::*;
+use memo_tree::NodeId;
+
+use crate::components::editor_plugin::memo_remote::{
+    load_sync_state, save_sync_state, wait_online, HttpMemoRemote,
+};
+use crate::r#fn::memo_storage::{save_memo, MemoSnapshot, MemoStorage};
+use crate::r#fn::memo_sync::{replay, Resolution, SyncState};
+
+/// サーバーとの同期。`use_context::<MemoSync>()` で取得する
+/// ログインしていない間・オフラインの間の編集は `state` に溜めておき、後で送る
+#[derive(Clone, Copy, PartialEq)]
+pub struct MemoSync {
+    pub state: Signal<SyncState>,
+    /// 送っている間は次の同期を始めない (送っている間の編集も続けて送る)
+    running: Signal<bool>,
+}
+
+impl MemoSync {
+    /// メモを保存して送る
+    pub async fn save(self, storage: &dyn MemoStorage, memo_id: NodeId, snapshot: &MemoSnapshot) {
+        if let Some(title) = save_memo(storage, memo_id, snapshot).await {
+            self.enqueue(memo_id, &title, &snapshot.text);
+        }
+    }
+
+    /// 保存したメモを送る
+    pub fn enqueue(self, memo_id: NodeId, title: &str, body: &str) {
+        let mut state = self.state;
+        state.write().enqueue(memo_id, title, body);
+        self.sync();
+    }
+
+    /// 食い違いを解消する。エディタの本文を置き換える場合はその本文を返す
+    pub fn resolve(self, memo_id: NodeId, resolution: Resolution) -> Option<String> {
+        let mut state = self.state;
+        let text = state.write().resolve(memo_id, resolution);
+        self.sync();
+        text
+    }
+
+    /// `rm` で消したメモは送らない
+    pub fn forget(self, memo_id: NodeId) {
+        let mut state = self.state;
+        state.write().forget(memo_id);
+        save_sync_state(&state.peek());
+    }
+
+    /// 溜まった編集を送る。ログインしていなければ保存だけする
+    pub fn sync(self) {
+        save_sync_state(&self.state.peek());
+        if *self.running.peek() {
+            return;
+        }
+        let mut running = self.running;
+        running.set(true);
+        // エディタを閉じても送り続ける
+        spawn_forever(async move {
+            if let Some(remote) = HttpMemoRemote::detect().await {
+                // 書き込みは `replay` の中で1回ずつで、awaitをまたがない
+                replay(&remote, || self.state.write_unchecked()).await;
+                save_sync_state(&self.state.peek());
+            }
+            running.set(false);
+        });
+    }
+}
+
+#[derive(Clone, Props, PartialEq)]
+pub struct SyncProviderProps {
+    children: Element,
+}
+
+/// 起動した時とオンラインに戻った時に、溜まった編集を送る
+#[component]
+pub fn SyncProvider(props: SyncProviderProps) -> Element {
+    let sync = use_context_provider(|| MemoSync {
+        state: Signal::new(SyncState::default()),
+        running: Signal::new(false),
+    });
+    use_future(move || async move {
+        if let Some(loaded) = load_sync_state().await {
+            let mut state = sync.state;
+            state.write().restore(loaded);
+        }
+        loop {
+            sync.sync();
+            wait_online().await;
+        }
+    });
+
+    rsx! {
+        {&props.children}
+    }
+}
@@ -0,0 +1,101 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::r#fn::grapheme;
+use crate::types::enums::Glyph;
+
+/// HTMLNode のタグ
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+/// 見出しの `#`
+static HEADING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^#{1,4} ").unwrap());
+
+/// エディタ内の空白はノーブレークスペースで保持しているので、外部に出す時に戻す
+fn glyph_source(glyph: &Glyph) -> String {
+    match glyph {
+        Glyph::Text(t) => t.replace('\u{00A0}', " "),
+        Glyph::Char('\u{00A0}') => " ".to_string(),
+        Glyph::Char(c) => c.to_string(),
+        Glyph::HTMLNode(v) => v.trim().to_string(),
+        Glyph::Cursor | Glyph::Component(_) => "".to_string(),
+    }
+}
+
+/// Glyph列をMarkdownとして書き出す
+/// HTMLNode はインラインHTMLとしてそのまま残す
+pub fn to_markdown(glyphs: &[Glyph]) -> String {
+    glyphs.iter().map(glyph_source).collect()
+}
+
+/// Glyph列を画面に表示されている通りのプレーンテキストとして書き出す
+/// HTMLNode はタグを除いた文字列、見出しの `#` は取り除く
+pub fn to_plain_text(glyphs: &[Glyph]) -> String {
+    let markdown: String = glyphs
+        .iter()
+        .map(|glyph| match glyph {
+            Glyph::HTMLNode(v) => TAG_RE.replace_all(v, "").trim().to_string(),
+            glyph => glyph_source(glyph),
+        })
+        .collect();
+
+    markdown
+        .split('\n')
+        .map(|line| HEADING_RE.replace(line, "").to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// クリップボードの文字列をエディタに挿入するGlyph列に変換する
+/// 改行は行の区切り、空白とタブはノーブレークスペースにする
+pub fn from_text(text: &str) -> Vec<Glyph> {
+    let mut glyphs = Vec::with_capacity(text.len());
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    for cluster in text.graphemes(true) {
+        match cluster {
+            " " => glyphs.push(Glyph::Char('\u{00A0}')),
+            // Tabキーと同じく4つのノーブレークスペース
+            "\t" => glyphs.extend((0..4).map(|_| Glyph::Char('\u{00A0}'))),
+            cluster => glyphs.push(grapheme::glyph(cluster)),
+        }
+    }
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_spaces_as_nbsp() {
+        let glyphs = from_text("a b\tc\r\nd");
+        assert_eq!(glyphs.len(), 10);
+        assert!(matches!(glyphs[1], Glyph::Char('\u{00A0}')));
+        assert_eq!(to_markdown(&glyphs), "a b    c\nd");
+        assert_eq!(to_plain_text(&glyphs), "a b    c\nd");
+        assert_eq!(to_markdown(&from_text("x y")), "x y");
+    }
+
+    #[test]
+    fn pills_keep_html_only_in_markdown() {
+        let mut glyphs = from_text("press ");
+        glyphs.push(Glyph::HTMLNode(
+            r#"
+                <button style="color: #fff;">F1</button>
+            "#
+            .to_string(),
+        ));
+        glyphs.push(Glyph::Cursor);
+        assert_eq!(
+            to_markdown(&glyphs),
+            r#"press <button style="color: #fff;">F1</button>"#
+        );
+        assert_eq!(to_plain_text(&glyphs), "press F1");
+    }
+
+    #[test]
+    fn plain_text_drops_heading_marks() {
+        let glyphs = from_text("## Title\nbody #1\n##### deep");
+        assert_eq!(to_markdown(&glyphs), "## Title\nbody #1\n##### deep");
+        assert_eq!(to_plain_text(&glyphs), "Title\nbody #1\n##### deep");
+    }
+}
@@ -0,0 +1,215 @@
+use serde::Deserialize;
+
+use crate::r#fn::editor_state::EditorState;
+use crate::types::enums::Direction;
+
+/// キーマップから呼び出すエディタのコマンド
+/// 設定ファイルでは `move_left` のようなsnake_caseの名前で指定する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditorCommand {
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    DocumentStart,
+    DocumentEnd,
+    PageUp,
+    PageDown,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    DeleteToLineStart,
+    DeleteToLineEnd,
+    /// 行末まで削除してクリップボードに送る (Emacsの C-k)
+    KillLine,
+    Undo,
+    Redo,
+    SelectAll,
+    Copy,
+    CopyMarkdown,
+    Cut,
+    Paste,
+    Find,
+    AddCursorAbove,
+    AddCursorBelow,
+    AddNextOccurrence,
+    ClearCursors,
+    Save,
+    ToggleTheme,
+    ToggleIme,
+    ToggleVim,
+    OpenCommandPalette,
+    /// 設定ファイルで既存の割り当てを外す
+    Unbind,
+}
+
+impl EditorCommand {
+    /// コマンドパレットに表示するコマンド (コマンド, 設定ファイルでの名前, 表示名)
+    pub const ALL: &'static [(EditorCommand, &'static str, &'static str)] = &[
+        (EditorCommand::MoveLeft, "move_left", "カーソルを左へ移動"),
+        (EditorCommand::MoveRight, "move_right", "カーソルを右へ移動"),
+        (EditorCommand::MoveUp, "move_up", "カーソルを上へ移動"),
+        (EditorCommand::MoveDown, "move_down", "カーソルを下へ移動"),
+        (EditorCommand::WordLeft, "word_left", "前の単語へ移動"),
+        (EditorCommand::WordRight, "word_right", "次の単語へ移動"),
+        (EditorCommand::LineStart, "line_start", "行頭へ移動"),
+        (EditorCommand::LineEnd, "line_end", "行末へ移動"),
+        (
+            EditorCommand::DocumentStart,
+            "document_start",
+            "文書の先頭へ移動",
+        ),
+        (
+            EditorCommand::DocumentEnd,
+            "document_end",
+            "文書の末尾へ移動",
+        ),
+        (EditorCommand::PageUp, "page_up", "1画面上へ移動"),
+        (EditorCommand::PageDown, "page_down", "1画面下へ移動"),
+        (
+            EditorCommand::DeleteBackward,
+            "delete_backward",
+            "前の文字を削除",
+        ),
+        (
+            EditorCommand::DeleteForward,
+            "delete_forward",
+            "次の文字を削除",
+        ),
+        (
+            EditorCommand::DeleteWordBackward,
+            "delete_word_backward",
+            "前の単語を削除",
+        ),
+        (
+            EditorCommand::DeleteWordForward,
+            "delete_word_forward",
+            "次の単語を削除",
+        ),
+        (
+            EditorCommand::DeleteToLineStart,
+            "delete_to_line_start",
+            "行頭まで削除",
+        ),
+        (
+            EditorCommand::DeleteToLineEnd,
+            "delete_to_line_end",
+            "行末まで削除",
+        ),
+        (EditorCommand::KillLine, "kill_line", "行末まで切り取り"),
+        (EditorCommand::Undo, "undo", "元に戻す"),
+        (EditorCommand::Redo, "redo", "やり直す"),
+        (EditorCommand::SelectAll, "select_all", "すべて選択"),
+        (EditorCommand::Copy, "copy", "コピー"),
+        (
+            EditorCommand::CopyMarkdown,
+            "copy_markdown",
+            "Markdownとしてコピー",
+        ),
+        (EditorCommand::Cut, "cut", "切り取り"),
+        (EditorCommand::Paste, "paste", "貼り付け"),
+        (EditorCommand::Find, "find", "検索・置換"),
+        (
+            EditorCommand::AddCursorAbove,
+            "add_cursor_above",
+            "上の行にカーソルを追加",
+        ),
+        (
+            EditorCommand::AddCursorBelow,
+            "add_cursor_below",
+            "下の行にカーソルを追加",
+        ),
+        (
+            EditorCommand::AddNextOccurrence,
+            "add_next_occurrence",
+            "次の一致にカーソルを追加",
+        ),
+        (
+            EditorCommand::ClearCursors,
+            "clear_cursors",
+            "追加したカーソルを解除",
+        ),
+        (EditorCommand::Save, "save", "保存"),
+        (
+            EditorCommand::ToggleTheme,
+            "toggle_theme",
+            "テーマを切り替え",
+        ),
+        (EditorCommand::ToggleIme, "toggle_ime", "IMEを切り替え"),
+        (
+            EditorCommand::ToggleVim,
+            "toggle_vim",
+            "Vimモードを切り替え",
+        ),
+    ];
+
+    /// カーソル移動のコマンド。Shift を押している場合は選択範囲を広げる
+    pub fn motion(self) -> Option<fn(&mut EditorState)> {
+        let motion: fn(&mut EditorState) = match self {
+            EditorCommand::MoveLeft => |e| e.move_cursor(Direction::Backward),
+            EditorCommand::MoveRight => |e| e.move_cursor(Direction::Forward),
+            EditorCommand::MoveUp => |e| e.go_to_line(Direction::Backward),
+            EditorCommand::MoveDown => |e| e.go_to_line(Direction::Forward),
+            EditorCommand::WordLeft => |e| e.move_word(Direction::Backward),
+            EditorCommand::WordRight => |e| e.move_word(Direction::Forward),
+            EditorCommand::LineStart => |e| e.set_cursor_start_of_line(),
+            EditorCommand::LineEnd => |e| e.set_cursor_end_of_line(),
+            EditorCommand::DocumentStart => |e| e.set_cursor_start_of_document(),
+            EditorCommand::DocumentEnd => |e| e.set_cursor_end_of_document(),
+            _ => return None,
+        };
+        Some(motion)
+    }
+
+    /// `EditorState` だけで完結するコマンドを実行する
+    /// クリップボードや画面の状態が必要なコマンドは実行せずに `false` を返す
+    pub fn apply(self, e: &mut EditorState, extend: bool) -> bool {
+        if let Some(motion) = self.motion() {
+            if extend {
+                e.each_cursor(|e| e.select_with(motion));
+            } else {
+                e.each_cursor(motion);
+            }
+            return true;
+        }
+        match self {
+            EditorCommand::DeleteBackward => e.delete(Direction::Backward),
+            EditorCommand::DeleteForward => e.delete(Direction::Forward),
+            EditorCommand::DeleteWordBackward => e.delete_word(Direction::Backward),
+            EditorCommand::DeleteWordForward => e.delete_word(Direction::Forward),
+            EditorCommand::DeleteToLineStart => e.delete_to_line_boundary(Direction::Backward),
+            EditorCommand::DeleteToLineEnd => e.delete_to_line_boundary(Direction::Forward),
+            EditorCommand::Undo => e.undo(),
+            EditorCommand::Redo => e.redo(),
+            EditorCommand::SelectAll => e.select_all(),
+            EditorCommand::AddCursorAbove => e.add_cursor_vertical(Direction::Backward),
+            EditorCommand::AddCursorBelow => e.add_cursor_vertical(Direction::Forward),
+            EditorCommand::AddNextOccurrence => e.add_next_occurrence(),
+            EditorCommand::ClearCursors => {
+                e.clear_cursors();
+                e.selection_anchor = None;
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_match_config_names() {
+        for (command, name, _) in EditorCommand::ALL {
+            let parsed: EditorCommand = serde_json::from_str(&format!("\"{name}\"")).unwrap();
+            assert_eq!(parsed, *command);
+        }
+    }
+}
@@ -1,44 +1,122 @@
 use crate::r#fn::line::Line;
+use crate::r#fn::text_buffer::TextBuffer;
 use crate::types::enums::Glyph;
 
-#[derive(Debug)]
+/// エディタの本文
+///
+/// 本文は `TextBuffer` (ロープ) に保持し、行・位置はすべて1始まりで扱う。
+/// カーソルは含まない。
+#[derive(Debug, Clone, Default)]
 pub struct EditorData {
-    pub lines: Vec<Line>,
+    pub buffer: TextBuffer,
 }
 
 impl EditorData {
     pub fn new() -> EditorData {
         EditorData {
-            lines: Vec::<Line>::new(),
+            buffer: TextBuffer::new(),
         }
     }
 
+    /// 行数
     pub fn len(&self) -> usize {
-        self.lines.len()
+        self.buffer.len_lines()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// `line` 行目の `pos` 番目のGlyphのoffset
+    pub fn offset(&self, line: usize, pos: usize) -> usize {
+        self.buffer.line_to_offset(line - 1) + pos - 1
+    }
+
+    /// offsetを (行, 位置) に変換する
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let line = self.buffer.offset_to_line(offset);
+        let start = self.buffer.line_to_offset(line);
+        (line + 1, offset - start + 1)
+    }
+
+    /// char offsetを (行, 位置) に変換する
+    pub fn char_to_position(&self, char_idx: usize) -> (usize, usize) {
+        self.position(self.buffer.char_to_offset(char_idx))
+    }
+
+    /// byte offsetを (行, 位置) に変換する
+    pub fn byte_to_position(&self, byte_idx: usize) -> (usize, usize) {
+        self.position(self.buffer.byte_to_offset(byte_idx))
+    }
+
+    /// `line` 行目の長さ(改行を含まない)
+    pub fn line_len(&self, line: usize) -> usize {
+        let start = self.buffer.line_to_offset(line - 1);
+        let end = self.line_end(line);
+        end - start
+    }
+
+    /// `line` 行目の末尾(改行の直前)のoffset
+    pub fn line_end(&self, line: usize) -> usize {
+        if line >= self.len() {
+            self.buffer.len()
+        } else {
+            self.buffer.line_to_offset(line) - 1
+        }
+    }
+
+    pub fn line(&self, line: usize) -> Line {
+        let start = self.buffer.line_to_offset(line - 1);
+        let end = self.line_end(line);
+        Line::from(self.buffer.slice(start..end))
+    }
+
+    /// 全行を先頭から順に返す
+    pub fn lines(&self) -> Vec<Line> {
+        let mut lines = vec![Line::new()];
+        for glyph in self.buffer.glyphs() {
+            match glyph {
+                Glyph::Char('\n') => lines.push(Line::new()),
+                glyph => lines.last_mut().unwrap().as_vec_mut().push(glyph),
+            }
+        }
+        lines
+    }
+
+    /// `position` 行目の後ろに空行を追加する
     pub fn add_empty_line(&mut self, position: usize) {
-        self.lines.insert(position, Line::new())
+        let at = self.line_end(position);
+        self.buffer.insert(at, vec![Glyph::Char('\n')]);
+    }
+
+    /// `line` 行目の末尾で改行し、次の行と結合する
+    pub fn join_with_next(&mut self, line: usize) {
+        if line >= self.len() {
+            return;
+        }
+        let at = self.line_end(line);
+        self.buffer.remove(at..at + 1);
     }
 
     pub fn insert(&mut self, line: usize, cursor_pos: usize, glyph: Glyph) {
-        self.lines[line - 1]
-            .as_vec_mut()
-            .insert(cursor_pos - 1, glyph);
+        let at = self.offset(line, cursor_pos);
+        self.buffer.insert(at, vec![glyph]);
+    }
+
+    /// `line` 行目の `pos` 番目のGlyphを削除する
+    pub fn remove(&mut self, line: usize, pos: usize) -> Option<Glyph> {
+        if pos < 1 || pos > self.line_len(line) {
+            return None;
+        }
+        let at = self.offset(line, pos);
+        self.buffer.remove(at..at + 1).pop()
     }
 }
 
 impl From<&str> for EditorData {
     fn from(value: &str) -> EditorData {
-        let mut st = EditorData::new();
-        let mut cursor_line: Line = Line::new();
-        cursor_line.as_vec_mut().push(Glyph::Cursor);
-        st.lines.push(cursor_line);
-
-        for line in value.split('\n') {
-            st.lines.push(line.into());
+        EditorData {
+            buffer: TextBuffer::from(value),
         }
-
-        st
     }
 }
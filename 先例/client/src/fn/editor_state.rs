@@ -1,16 +1,38 @@
 use dioxus::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
 
+use std::ops::Range;
+
+use crate::r#fn::clipboard_text;
 use crate::r#fn::editor_data::EditorData;
+use crate::r#fn::grapheme;
+use crate::r#fn::history::{Edit, EditKind, History};
 use crate::r#fn::line::Line;
+use crate::r#fn::search::{self, SearchQuery};
+use crate::r#fn::word;
 use crate::types::enums::{Direction, Glyph};
 
+/// 主カーソル以外に追加したカーソル (マルチカーソル)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    /// (行, 位置)
+    pub head: (usize, usize),
+    /// 選択範囲の起点 (行, 位置)
+    pub anchor: Option<(usize, usize)>,
+}
+
 #[derive(Debug)]
 pub struct EditorState {
     pub contents: EditorData,
     pub current_line: usize,
     pub cursor_position: usize,
-    // pub selection_start: usize,
-    // pub selection_end: usize,
+    pub history: History,
+    /// 選択範囲の起点 (行, 位置)。終点は常にカーソル位置
+    pub selection_anchor: Option<(usize, usize)>,
+    /// 上下移動で維持したい位置。短い行を通過しても元の位置に戻れるようにする
+    pub desired_column: Option<usize>,
+    /// 追加のカーソル。編集操作はすべてのカーソルに適用する
+    pub cursors: Vec<Caret>,
 }
 
 impl From<&'_ str> for EditorState {
@@ -19,161 +41,874 @@ impl From<&'_ str> for EditorState {
         EditorState {
             cursor_position: 1,
             current_line: 1,
-            // selection_start: 0, // 選択範囲の初期位置（カーソルと同じ）
-            // selection_end: 0,   // 選択範囲の終了位置（カーソルと同じ）
+            history: History::new(),
+            selection_anchor: None,
+            desired_column: None,
+            cursors: Vec::new(),
             contents: data,
         }
     }
 }
 
-macro_rules! with_cursor {
-    (|$self:ident| $e:tt) => {
-        let cursor_position = ($self.current_line, $self.cursor_position);
-        $self.clear_cursor(&cursor_position);
-        $e;
-        $self.insert_cursor()
-    };
-}
 impl EditorState {
-    pub fn get_line_content(&self, line_idx: usize) -> String {
-        if let Some(line) = self.contents.lines.get(line_idx - 1) {
-            line.as_vec()
-                .iter()
-                .map(|glyph| match glyph {
-                    Glyph::Text(t) => t.clone(),
-                    Glyph::Char(c) => c.to_string(),
-                    Glyph::Cursor => "❮".to_string(),
-                    Glyph::HTMLNode(v) => v.clone(),
-                    Glyph::Component(_) => "<Component>".to_string(),
-                })
-                .collect()
+    /// 本文への挿入はすべてここを通し、undo履歴に記録する
+    fn edit_insert(&mut self, at: usize, glyphs: Vec<Glyph>, kind: EditKind) {
+        let cursor = (self.current_line, self.cursor_position);
+        self.contents.buffer.insert(at, glyphs.clone());
+        self.history
+            .record(kind, Edit::Insert { at, glyphs }, cursor);
+    }
+
+    /// 本文からの削除はすべてここを通し、undo履歴に記録する
+    fn edit_remove(&mut self, range: Range<usize>, kind: EditKind) -> Vec<Glyph> {
+        let cursor = (self.current_line, self.cursor_position);
+        let at = range.start;
+        let glyphs = self.contents.buffer.remove(range);
+        if !glyphs.is_empty() {
+            self.history.record(
+                kind,
+                Edit::Remove {
+                    at,
+                    glyphs: glyphs.clone(),
+                },
+                cursor,
+            );
+        }
+        glyphs
+    }
+
+    /// カーソル位置にGlyphを1つ挿入し、カーソルを進める
+    /// 選択範囲がある場合は置き換える
+    fn insert_glyph(&mut self, glyph: Glyph, kind: EditKind) {
+        self.delete_selection();
+        let at = self
+            .contents
+            .offset(self.current_line, self.cursor_position);
+        self.edit_insert(at, vec![glyph], kind);
+        self.cursor_position += 1;
+    }
+
+    fn apply(&mut self, edit: &Edit) {
+        match edit {
+            Edit::Insert { at, glyphs } => self.contents.buffer.insert(*at, glyphs.clone()),
+            Edit::Remove { at, glyphs } => {
+                self.contents.buffer.remove(*at..*at + glyphs.len());
+            }
+        }
+    }
+
+    /// 直前の変更グループを取り消し、カーソルを変更前の位置に戻す
+    pub fn undo(&mut self) {
+        self.cursors.clear();
+        if let Some(group) = self.history.pop_undo() {
+            for edit in group.edits.iter().rev() {
+                self.apply(&edit.inverse());
+            }
+            let (line, pos) = group.cursor_before;
+            self.set_cursor(line, pos);
+        }
+    }
+
+    /// 取り消した変更グループをやり直し、カーソルを変更の直後に置く
+    pub fn redo(&mut self) {
+        self.cursors.clear();
+        if let Some(group) = self.history.pop_redo() {
+            for edit in &group.edits {
+                self.apply(edit);
+            }
+            if let Some(edit) = group.edits.last() {
+                let (line, pos) = self.contents.position(edit.end());
+                self.set_cursor(line, pos);
+            }
+        }
+    }
+
+    /// 入力のまとまりを区切る(IME確定時など)
+    pub fn break_undo_group(&mut self) {
+        self.history.seal();
+    }
+
+    /// カーソルを飛ばす操作の前に呼ぶ。undoグループを区切り、選択を解除する
+    fn jump(&mut self) {
+        self.history.seal();
+        self.selection_anchor = None;
+        self.desired_column = None;
+    }
+
+    /// 選択範囲のoffset。起点と終点が同じ場合はNone
+    pub fn selection_range(&self) -> Option<Range<usize>> {
+        let (line, pos) = self.selection_anchor?;
+        let anchor = self.contents.offset(line, pos);
+        let head = self
+            .contents
+            .offset(self.current_line, self.cursor_position);
+        match anchor.cmp(&head) {
+            std::cmp::Ordering::Less => Some(anchor..head),
+            std::cmp::Ordering::Greater => Some(head..anchor),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// `line` 行目のうち選択されている位置 [開始, 終了) (0始まり)
+    pub fn selection_in_line(&self, line: usize) -> Option<(usize, usize)> {
+        self.range_in_line(self.selection_range()?, line)
+    }
+
+    /// 全カーソルの選択範囲のうち `line` 行目にかかる部分
+    pub fn selections_in_line(&self, line: usize) -> Vec<(usize, usize)> {
+        self.carets()
+            .into_iter()
+            .filter_map(|caret| {
+                let anchor = caret.anchor?;
+                let anchor = self.contents.offset(anchor.0, anchor.1);
+                let head = self.contents.offset(caret.head.0, caret.head.1);
+                self.range_in_line(anchor.min(head)..anchor.max(head), line)
+            })
+            .collect()
+    }
+
+    fn range_in_line(&self, range: Range<usize>, line: usize) -> Option<(usize, usize)> {
+        if range.is_empty() {
+            return None;
+        }
+        let (start_line, start_pos) = self.contents.position(range.start);
+        let (end_line, end_pos) = self.contents.position(range.end);
+        if line < start_line || line > end_line {
+            return None;
+        }
+        let start = if line == start_line { start_pos - 1 } else { 0 };
+        let end = if line == end_line {
+            end_pos - 1
         } else {
-            "".to_string()
+            // 改行も選択されていることが分かるように1つ余分に含める
+            self.contents.line_len(line) + 1
+        };
+        Some((start, end))
+    }
+
+    /// 選択範囲を保ったままカーソルを動かす (Shift + 矢印など)
+    pub fn select_with(&mut self, f: impl FnOnce(&mut EditorState)) {
+        let anchor = self
+            .selection_anchor
+            .unwrap_or((self.current_line, self.cursor_position));
+        f(self);
+        self.history.seal();
+        if anchor != (self.current_line, self.cursor_position) {
+            self.selection_anchor = Some(anchor);
         }
     }
 
-    pub fn next_line_or_new(&mut self) {
-        self.contents.add_empty_line(self.current_line);
+    /// カーソル位置から (行, 位置) までを選択する (Shift + クリック, ドラッグ)
+    pub fn select_to(&mut self, line: usize, cursor: usize) {
+        self.select_with(|e| e.set_cursor(line, cursor));
+    }
+
+    pub fn select_all(&mut self) {
+        self.cursors.clear();
+        self.set_cursor(1, 1);
+        self.select_with(|e| {
+            let last = e.contents.len();
+            e.set_cursor(last, e.contents.line_len(last) + 1);
+        });
+    }
+
+    /// 選択範囲の文字列を削除する。削除した場合はtrue
+    pub fn delete_selection(&mut self) -> bool {
+        let range = match self.selection_range() {
+            Some(range) => range,
+            None => {
+                self.selection_anchor = None;
+                return false;
+            }
+        };
+        let start = range.start;
+        self.edit_remove(range, EditKind::Other);
+        let (line, pos) = self.contents.position(start);
+        self.set_cursor(line, pos);
+        true
+    }
+
+    /// 主カーソルを含むすべてのカーソル
+    pub fn carets(&self) -> Vec<Caret> {
+        let mut carets = vec![Caret {
+            head: (self.current_line, self.cursor_position),
+            anchor: self.selection_anchor,
+        }];
+        carets.extend(self.cursors.iter().copied());
+        carets
+    }
+
+    pub fn clear_cursors(&mut self) {
+        self.cursors.clear();
+    }
+
+    /// すべてのカーソルで `f` を実行する
+    /// 後ろのカーソルから順に実行し、その編集でずれた後ろのカーソルのoffsetを補正する。
+    /// 全体を1回のundoで戻せるようにまとめる
+    pub fn each_cursor(&mut self, f: impl Fn(&mut EditorState)) {
+        if self.cursors.is_empty() {
+            f(self);
+            return;
+        }
+
+        // (主カーソルかどうか, カーソルのoffset, 起点のoffset)
+        let mut carets: Vec<(bool, usize, Option<usize>)> = self
+            .carets()
+            .into_iter()
+            .enumerate()
+            .map(|(idx, caret)| {
+                (
+                    idx == 0,
+                    self.contents.offset(caret.head.0, caret.head.1),
+                    caret
+                        .anchor
+                        .map(|(line, pos)| self.contents.offset(line, pos)),
+                )
+            })
+            .collect();
+        carets.sort_by_key(|caret| std::cmp::Reverse(caret.1));
+        self.cursors.clear();
+
+        self.history
+            .begin((self.current_line, self.cursor_position));
+        let mut done: Vec<(bool, usize, Option<usize>)> = Vec::with_capacity(carets.len());
+        for (primary, head, anchor) in carets {
+            (self.current_line, self.cursor_position) = self.contents.position(head);
+            self.selection_anchor = anchor.map(|at| self.contents.position(at));
+            self.desired_column = None;
+
+            let before = self.contents.buffer.len() as isize;
+            f(self);
+            let delta = self.contents.buffer.len() as isize - before;
+            let shift = |at: usize| (at as isize + delta).max(0) as usize;
+            for caret in done.iter_mut() {
+                caret.1 = shift(caret.1);
+                caret.2 = caret.2.map(shift);
+            }
+
+            done.push((
+                primary,
+                self.contents
+                    .offset(self.current_line, self.cursor_position),
+                self.selection_anchor
+                    .map(|(line, pos)| self.contents.offset(line, pos)),
+            ));
+        }
+        self.history.end();
+
+        // 同じ位置に重なったカーソルは1つにまとめる
+        // 重なった範囲を削除した場合にoffsetが末尾を越えないように丸める
+        let len = self.contents.buffer.len();
+        let primary_head = done.iter().find(|c| c.0).map(|c| c.1.min(len)).unwrap_or(0);
+        let mut heads = vec![primary_head];
+        for (primary, head, anchor) in done {
+            let head = head.min(len);
+            let anchor = anchor.map(|at| self.contents.position(at.min(len)));
+            if primary {
+                (self.current_line, self.cursor_position) = self.contents.position(head);
+                self.selection_anchor = anchor;
+            } else if !heads.contains(&head) {
+                heads.push(head);
+                self.cursors.push(Caret {
+                    head: self.contents.position(head),
+                    anchor,
+                });
+            }
+        }
+        self.desired_column = None;
+    }
+
+    /// (行, 位置) にカーソルを追加し、主カーソルにする (Alt + クリック)
+    /// 既存のカーソルの位置を指定した場合はそのカーソルを取り除く
+    pub fn add_cursor(&mut self, line: usize, pos: usize) {
+        let line = line.clamp(1, self.contents.len());
+        let pos = pos.clamp(1, self.contents.line_len(line) + 1);
+        if let Some(idx) = self.cursors.iter().position(|c| c.head == (line, pos)) {
+            self.cursors.remove(idx);
+            return;
+        }
+        if (line, pos) == (self.current_line, self.cursor_position) {
+            return;
+        }
+        self.cursors.push(Caret {
+            head: (self.current_line, self.cursor_position),
+            anchor: self.selection_anchor,
+        });
+        self.set_cursor(line, pos);
+    }
+
+    /// 一番上 (下) のカーソルの上 (下) の行にカーソルを追加する (Alt + Shift + Up / Down)
+    pub fn add_cursor_vertical(&mut self, rel: Direction) {
+        let heads = self.carets().into_iter().map(|caret| caret.head);
+        let edge = match rel {
+            Direction::Forward => heads.max(),
+            Direction::Backward => heads.min(),
+        };
+        let Some((line, pos)) = edge else {
+            return;
+        };
+        let line = line as isize + rel as isize;
+        if line < 1 || line as usize > self.contents.len() {
+            return;
+        }
+        let line = line as usize;
+        let head = (line, pos.min(self.contents.line_len(line) + 1));
+        self.history.seal();
+        self.cursors.push(Caret { head, anchor: None });
+    }
+
+    /// 選択中の文字列が次に現れる位置を選択したカーソルを追加する (Cmd + D)
+    /// 選択範囲がない場合は、まずカーソル位置の単語を選択する
+    pub fn add_next_occurrence(&mut self) {
+        let Some(range) = self.selection_range() else {
+            let glyphs = self.current_line().as_vec().clone();
+            let column = self.cursor_position - 1;
+            let word = word::word_segments(&glyphs)
+                .into_iter()
+                .find(|(start, end, is_space)| !is_space && *start <= column && column <= *end);
+            if let Some((start, end, _)) = word {
+                let line = self.current_line;
+                self.set_cursor(line, start + 1);
+                self.select_to(line, end + 1);
+            }
+            return;
+        };
+
+        let text: Vec<String> = self
+            .contents
+            .buffer
+            .glyphs()
+            .iter()
+            .map(glyph_source)
+            .collect();
+        let needle = &text[range.clone()];
+        let selected: Vec<usize> = self
+            .carets()
+            .into_iter()
+            .filter_map(|caret| {
+                let anchor = caret.anchor?;
+                let anchor = self.contents.offset(anchor.0, anchor.1);
+                let head = self.contents.offset(caret.head.0, caret.head.1);
+                Some(anchor.min(head))
+            })
+            .collect();
+
+        // 主カーソルの後ろから探し、末尾まで見つからなければ先頭から探す
+        let last = text.len() - needle.len();
+        let found = (range.end..=last)
+            .chain(0..range.start.min(last + 1))
+            .find(|start| {
+                !selected.contains(start) && text[*start..*start + needle.len()] == *needle
+            });
+        if let Some(start) = found {
+            self.cursors.push(Caret {
+                head: (self.current_line, self.cursor_position),
+                anchor: self.selection_anchor,
+            });
+            let anchor = self.contents.position(start);
+            let (line, pos) = self.contents.position(start + needle.len());
+            self.set_cursor(line, pos);
+            self.selection_anchor = Some(anchor);
+        }
+    }
+
+    /// 本文中の一致 (glyph offset)
+    /// 書記素クラスタの途中で始まる・終わる一致は除く
+    pub fn find_matches(&self, query: &SearchQuery) -> Result<Vec<Range<usize>>, regex::Error> {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let re = query.build()?;
+        let text = self.contents.buffer.text();
+        Ok(search::find_all(&re, &text)
+            .into_iter()
+            .filter_map(|range| self.byte_range_to_offsets(&range))
+            .collect())
+    }
+
+    fn byte_range_to_offsets(&self, range: &Range<usize>) -> Option<Range<usize>> {
+        let buffer = &self.contents.buffer;
+        let start = buffer.byte_to_offset(range.start);
+        let end = buffer.byte_to_offset(range.end);
+        let aligned =
+            buffer.offset_to_byte(start) == range.start && buffer.offset_to_byte(end) == range.end;
+        aligned.then_some(start..end)
+    }
+
+    /// 一致のうち `line` 行目にかかる部分 [開始, 終了) (0始まり)
+    pub fn matches_in_line(&self, matches: &[Range<usize>], line: usize) -> Vec<(usize, usize)> {
+        matches
+            .iter()
+            .filter_map(|range| self.range_in_line(range.clone(), line))
+            .collect()
+    }
+
+    /// 次 (前) の一致を選択する。末尾 (先頭) まで見つからなければ反対側から探す
+    /// 一致があればtrue
+    pub fn find_next(&mut self, query: &SearchQuery, rel: Direction) -> Result<bool, regex::Error> {
+        let matches = self.find_matches(query)?;
+        let cursor = self
+            .contents
+            .offset(self.current_line, self.cursor_position);
+        let selection = self.selection_range();
+        let found = match rel {
+            Direction::Forward => {
+                let from = selection.map(|r| r.end).unwrap_or(cursor);
+                matches.iter().find(|m| m.start >= from).or(matches.first())
+            }
+            Direction::Backward => {
+                let to = selection.map(|r| r.start).unwrap_or(cursor);
+                matches
+                    .iter()
+                    .rev()
+                    .find(|m| m.start < to)
+                    .or(matches.last())
+            }
+        };
+        let Some(found) = found.cloned() else {
+            return Ok(false);
+        };
+        self.select_range(found);
+        Ok(true)
+    }
 
-        with_cursor!(|self| {
-            self.current_line += 1;
-            self.cursor_position = 1;
+    /// offsetの範囲を選択する。カーソルは範囲の末尾に置く
+    fn select_range(&mut self, range: Range<usize>) {
+        self.cursors.clear();
+        let (line, pos) = self.contents.position(range.start);
+        self.set_cursor(line, pos);
+        let (line, pos) = self.contents.position(range.end);
+        self.select_to(line, pos);
+    }
+
+    /// 選択中の一致を置換して次の一致を選択する
+    /// 一致を選択していない場合は次の一致を選択するだけにする
+    pub fn replace_current(
+        &mut self,
+        query: &SearchQuery,
+        replacement: &str,
+    ) -> Result<bool, regex::Error> {
+        let re = query.build()?;
+        let text = self.contents.buffer.text();
+        let current = self.selection_range().and_then(|selection| {
+            search::find_all(&re, &text)
+                .into_iter()
+                .find(|range| self.byte_range_to_offsets(range) == Some(selection.clone()))
         });
+        if let Some(range) = current {
+            let with = query.expand(&re, &text, &range, replacement);
+            let at = self.contents.buffer.byte_to_offset(range.start);
+            let end = self.contents.buffer.byte_to_offset(range.end);
+            self.history
+                .begin((self.current_line, self.cursor_position));
+            let end = self.replace_range(at..end, &with);
+            self.history.end();
+            let (line, pos) = self.contents.position(end);
+            self.set_cursor(line, pos);
+        }
+        self.find_next(query, Direction::Forward)
     }
 
-    pub fn clear_cursor(&mut self, (line, pos): &(usize, usize)) {
-        self.contents.lines[line - 1].as_vec_mut().remove(pos - 1);
+    /// すべての一致を置換する。1回のundoで戻せる
+    /// 置換した数を返す
+    pub fn replace_all(
+        &mut self,
+        query: &SearchQuery,
+        replacement: &str,
+    ) -> Result<usize, regex::Error> {
+        if query.is_empty() {
+            return Ok(0);
+        }
+        let re = query.build()?;
+        let text = self.contents.buffer.text();
+        let replacements: Vec<(Range<usize>, String)> = search::find_all(&re, &text)
+            .into_iter()
+            .filter_map(|range| {
+                let offsets = self.byte_range_to_offsets(&range)?;
+                Some((offsets, query.expand(&re, &text, &range, replacement)))
+            })
+            .collect();
+        if replacements.is_empty() {
+            return Ok(0);
+        }
+
+        let cursor = (self.current_line, self.cursor_position);
+        self.cursors.clear();
+        self.history.begin(cursor);
+        // 後ろから置換することで前の一致の位置がずれないようにする
+        for (range, with) in replacements.iter().rev() {
+            self.replace_range(range.clone(), with);
+        }
+        self.history.end();
+        self.set_cursor(cursor.0, cursor.1);
+        Ok(replacements.len())
+    }
+
+    /// カーソルの前の `count` 文字を `text` に置き換える (コマンドラインの補完)
+    pub fn replace_before_cursor(&mut self, count: usize, text: &str) {
+        let end = self
+            .contents
+            .offset(self.current_line, self.cursor_position);
+        let at = end - count.min(self.cursor_position - 1);
+        self.cursors.clear();
+        self.history
+            .begin((self.current_line, self.cursor_position));
+        let end = self.replace_range(at..end, text);
+        self.history.end();
+        let (line, pos) = self.contents.position(end);
+        self.set_cursor(line, pos);
+    }
+
+    /// 現在行を `text` に置き換え、カーソルを行末に置く (コマンドの履歴の呼び出し)
+    pub fn set_current_line_text(&mut self, text: &str) {
+        let len = self.current_line().as_vec().len();
+        self.set_cursor(self.current_line, len + 1);
+        self.replace_before_cursor(len, text);
+    }
+
+    /// offsetの範囲を文字列で置き換え、置き換えた後の末尾のoffsetを返す
+    fn replace_range(&mut self, range: Range<usize>, text: &str) -> usize {
+        let at = range.start;
+        self.edit_remove(range, EditKind::Other);
+        let glyphs = clipboard_text::from_text(text);
+        let end = at + glyphs.len();
+        if !glyphs.is_empty() {
+            self.edit_insert(at, glyphs, EditKind::Other);
+        }
+        end
+    }
+
+    pub fn get_line_content(&self, line_idx: usize) -> String {
+        if line_idx < 1 || line_idx > self.contents.len() {
+            return "".to_string();
+        }
+        self.line_with_cursor(line_idx)
+            .as_vec()
+            .iter()
+            .map(glyph_source)
+            .collect()
+    }
+
+    /// 描画用に、カーソルのある行には `Glyph::Cursor` を差し込んだLineを返す
+    fn line_with_cursor(&self, line_idx: usize) -> Line {
+        let mut line = self.contents.line(line_idx);
+        let mut positions: Vec<usize> = self
+            .carets()
+            .into_iter()
+            .filter(|caret| caret.head.0 == line_idx)
+            .map(|caret| caret.head.1)
+            .collect();
+        // 後ろから差し込むことで前の位置がずれないようにする
+        positions.sort_unstable_by(|a, b| b.cmp(a));
+        positions.dedup();
+        for pos in positions {
+            let at = (pos - 1).min(line.as_vec().len());
+            line.as_vec_mut().insert(at, Glyph::Cursor);
+        }
+        line
+    }
+
+    /// 選択範囲をプレーンテキストとして返す (コピー用)
+    pub fn selected_text(&self) -> Option<String> {
+        let range = self.selection_range()?;
+        Some(clipboard_text::to_plain_text(
+            &self.contents.buffer.slice(range),
+        ))
     }
 
-    pub fn insert_cursor(&mut self) {
-        self.contents.lines[self.current_line - 1]
-            .as_vec_mut()
-            .insert(self.cursor_position - 1, Glyph::Cursor)
+    /// 選択範囲をMarkdownとして返す (コピー用)
+    pub fn selected_markdown(&self) -> Option<String> {
+        let range = self.selection_range()?;
+        Some(clipboard_text::to_markdown(
+            &self.contents.buffer.slice(range),
+        ))
+    }
+
+    /// 貼り付け。複数行の文字列は行に分けて挿入し、カーソルは貼り付けた文字列の直後に置く
+    pub fn paste(&mut self, text: &str) {
+        self.insert_glyphs(clipboard_text::from_text(text));
+    }
+
+    /// カーソル位置にGlyph列を挿入し、カーソルを挿入した直後に置く
+    /// 選択範囲がある場合は置き換える
+    pub fn insert_glyphs(&mut self, glyphs: Vec<Glyph>) {
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.insert_glyphs(glyphs.clone()));
+            return;
+        }
+        if glyphs.is_empty() {
+            return;
+        }
+        // 選択範囲の削除と貼り付けを1回のundoで戻せるようにする
+        self.history
+            .begin((self.current_line, self.cursor_position));
+        self.delete_selection();
+        let at = self
+            .contents
+            .offset(self.current_line, self.cursor_position);
+        let end = at + glyphs.len();
+        self.edit_insert(at, glyphs, EditKind::Other);
+        self.history.end();
+        let (line, pos) = self.contents.position(end);
+        self.set_cursor(line, pos);
+    }
+
+    /// 現在行の上に空行を挿入し、カーソルをその行に置く (Vimの `O`)
+    pub fn open_line_above(&mut self) {
+        self.delete_selection();
+        let at = self.contents.offset(self.current_line, 1);
+        self.edit_insert(at, vec![Glyph::Char('\n')], EditKind::Other);
+        self.cursor_position = 1;
+    }
+
+    /// 現在行から `count` 行分のGlyph (行の間の改行を含み、末尾の改行は含まない)
+    pub fn yank_lines(&self, count: usize) -> Vec<Glyph> {
+        let last = (self.current_line + count.max(1) - 1).min(self.contents.len());
+        let start = self.contents.offset(self.current_line, 1);
+        self.contents
+            .buffer
+            .slice(start..self.contents.line_end(last))
+    }
+
+    /// 現在行から `count` 行を削除し、削除した行のGlyphを返す (Vimの `dd`)
+    pub fn delete_lines(&mut self, count: usize) -> Vec<Glyph> {
+        let first = self.current_line;
+        let last = (first + count.max(1) - 1).min(self.contents.len());
+        let removed = self.yank_lines(count);
+        let start = self.contents.offset(first, 1);
+        let end = self.contents.line_end(last);
+        // 行の改行も合わせて削除する。最終行を含む場合は前の行の改行を削除する
+        let range = if last < self.contents.len() {
+            start..end + 1
+        } else if first > 1 {
+            start - 1..end
+        } else {
+            start..end
+        };
+        self.edit_remove(range, EditKind::Other);
+        self.set_cursor(first, 1);
+        removed
+    }
+
+    /// 行単位のGlyphを現在行の下 (上) に挿入し、カーソルを挿入した先頭に置く (Vimの `p` / `P`)
+    pub fn put_lines(&mut self, glyphs: Vec<Glyph>, below: bool) {
+        let mut inserted = glyphs;
+        let (at, line) = if below {
+            inserted.insert(0, Glyph::Char('\n'));
+            (
+                self.contents.line_end(self.current_line),
+                self.current_line + 1,
+            )
+        } else {
+            inserted.push(Glyph::Char('\n'));
+            (
+                self.contents.offset(self.current_line, 1),
+                self.current_line,
+            )
+        };
+        self.edit_insert(at, inserted, EditKind::Other);
+        self.set_cursor(line, 1);
+    }
+
+    /// offsetの範囲を削除し、カーソルを削除した位置に置く
+    pub fn delete_range(&mut self, range: Range<usize>) -> Vec<Glyph> {
+        let start = range.start;
+        let removed = self.edit_remove(range, EditKind::Other);
+        let (line, pos) = self.contents.position(start);
+        self.set_cursor(line, pos);
+        removed
+    }
+
+    pub fn next_line_or_new(&mut self) {
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.next_line_or_new());
+            return;
+        }
+        self.delete_selection();
+        let at = self.contents.line_end(self.current_line);
+        self.edit_insert(at, vec![Glyph::Char('\n')], EditKind::Other);
+        self.current_line += 1;
+        self.cursor_position = 1;
     }
 
     pub fn insert_char(&mut self, ch: char) {
         println!("Inserting char!");
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-        self.contents
-            .insert(self.current_line, position, Glyph::Char(ch));
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.insert_char(ch));
+            return;
+        }
+        self.insert_cluster(&ch.to_string());
     }
 
     pub fn insert_text(&mut self, text: &str) {
         println!("Inserting text!");
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-        self.contents
-            .insert(self.current_line, position, Glyph::Text(text.to_string()));
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.insert_text(text));
+            return;
+        }
+        for cluster in text.graphemes(true) {
+            self.insert_cluster(cluster);
+        }
     }
 
     pub fn insert(&mut self, string: &str) {
-        for ch in string.chars() {
-            self.insert_text(&ch.to_string())
+        self.insert_text(string);
+    }
+
+    /// 書記素クラスタを1つ入力する
+    /// 直前のGlyphと合わせて1つのクラスタになる場合は置き換え、カーソルは動かさない
+    fn insert_cluster(&mut self, cluster: &str) {
+        if self.selection_range().is_none() && self.cursor_position > 1 {
+            let at = self
+                .contents
+                .offset(self.current_line, self.cursor_position - 1);
+            let joined = self
+                .contents
+                .buffer
+                .get(at)
+                .and_then(|prev| grapheme::join(prev, cluster));
+            if let Some(glyph) = joined {
+                self.history
+                    .begin((self.current_line, self.cursor_position));
+                self.edit_remove(at..at + 1, EditKind::Other);
+                self.edit_insert(at, vec![glyph], EditKind::Other);
+                self.history.end();
+                return;
+            }
         }
+        self.insert_glyph(grapheme::glyph(cluster), EditKind::Typing);
     }
 
     pub fn current_line_length(&self) -> usize {
-        self.contents.lines[self.current_line - 1].as_vec().len()
+        self.contents.line_len(self.current_line)
     }
 
     pub fn go_to_line(&mut self, rel: Direction) {
-        let mut current_line = self.current_line;
-        current_line = (current_line as isize + rel as isize) as usize;
+        self.move_lines(rel, 1);
+    }
 
-        if current_line < 1 || current_line > self.contents.len() {
+    /// `count` 行上下に移動する。位置は `desired_column` を保ち、短い行では行末に丸める
+    pub fn move_lines(&mut self, rel: Direction, count: usize) {
+        let current_line = match rel {
+            Direction::Forward => (self.current_line + count).min(self.contents.len()),
+            Direction::Backward => self.current_line.saturating_sub(count).max(1),
+        };
+        if current_line == self.current_line {
             return;
         }
 
-        with_cursor!(|self| {
-            self.current_line = current_line;
-            self.set_cursor_start_of_line();
-        });
+        let column = self.desired_column.unwrap_or(self.cursor_position);
+        self.jump();
+        self.current_line = current_line;
+        self.cursor_position = column.min(self.current_line_length() + 1);
+        self.desired_column = Some(column);
+    }
+
+    /// 文書の先頭に移動する (Cmd + Up)
+    pub fn set_cursor_start_of_document(&mut self) {
+        self.jump();
+        self.current_line = 1;
+        self.cursor_position = 1;
+    }
+
+    /// 文書の末尾に移動する (Cmd + Down)
+    pub fn set_cursor_end_of_document(&mut self) {
+        self.jump();
+        self.current_line = self.contents.len();
+        self.cursor_position = self.current_line_length() + 1;
+    }
+
+    /// 単語単位で移動する (Alt + Left / Right)
+    /// 行頭・行末では前後の行に移る
+    pub fn move_word(&mut self, rel: Direction) {
+        let glyphs = self.current_line().as_vec().clone();
+        let column = self.cursor_position - 1;
+        match rel {
+            Direction::Backward if column > 0 => {
+                self.jump();
+                self.cursor_position = word::prev_word_start(&glyphs, column) + 1;
+            }
+            Direction::Forward if column < glyphs.len() => {
+                self.jump();
+                self.cursor_position = word::next_word_end(&glyphs, column) + 1;
+            }
+            rel => self.move_cursor(rel),
+        }
     }
 
     pub fn set_cursor_end_of_line(&mut self) {
-        self.cursor_position = self.current_line().as_vec().len() + 1;
+        self.jump();
+        self.cursor_position = self.current_line_length() + 1;
     }
 
     pub fn set_cursor_start_of_line(&mut self) {
+        self.jump();
         self.cursor_position = 1;
     }
 
     pub fn move_cursor(&mut self, rel: Direction) {
+        self.jump();
         if let Direction::Backward = rel {
             let new_position = self.cursor_position - 1;
             if new_position < 1 && self.current_line < 2 {
                 return;
             }
 
-            with_cursor!(|self| {
-                if new_position >= 1 {
-                    self.cursor_position = new_position
-                }
-                if new_position < 1 {
-                    self.current_line -= 1;
-                    self.set_cursor_end_of_line();
-                }
-            });
+            if new_position >= 1 {
+                self.cursor_position = new_position
+            }
+            if new_position < 1 {
+                self.current_line -= 1;
+                self.set_cursor_end_of_line();
+            }
         }
 
         if let Direction::Forward = rel {
             let new_position = self.cursor_position + 1;
-            let end_of_line = self.cursor_position == self.current_line().as_vec().len();
-            let last_line = self.current_line == self.contents.lines.len();
+            let end_of_line = self.cursor_position == self.current_line_length() + 1;
+            let last_line = self.current_line == self.contents.len();
             if end_of_line && last_line {
                 return;
             }
-            with_cursor!(|self| {
-                if end_of_line {
-                    self.cursor_position = 1;
-                    self.current_line += 1;
-                }
-                if !end_of_line {
-                    self.cursor_position = new_position;
-                }
-            });
+            if end_of_line {
+                self.cursor_position = 1;
+                self.current_line += 1;
+            }
+            if !end_of_line {
+                self.cursor_position = new_position;
+            }
         }
     }
 
+    /// 範囲外の指定は最も近い位置に丸める
     pub fn set_cursor(&mut self, line: usize, cursor: usize) {
-        with_cursor!(|self| {
-            self.current_line = line;
-            self.cursor_position = cursor;
-        });
+        self.jump();
+        self.current_line = line.clamp(1, self.contents.len());
+        self.cursor_position = cursor.clamp(1, self.current_line_length() + 1);
     }
 
-    #[allow(unreachable_code)]
     pub fn delete(&mut self, dir: Direction) {
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.delete(dir));
+            return;
+        }
+        if self.delete_selection() {
+            return;
+        }
         match dir {
             Direction::Forward => {
-                todo!(); // TODO
-                if self.cursor_position == (self.current_line_length() + 1) {
-                    return;
+                let end_of_line = self.cursor_position > self.current_line_length();
+                let last_line = self.current_line == self.contents.len();
+                match (end_of_line, last_line) {
+                    (true, true) => (),
+                    (true, false) => self.join_next_line(),
+                    _ => {
+                        let at = self
+                            .contents
+                            .offset(self.current_line, self.cursor_position);
+                        self.edit_remove(at..at + 1, EditKind::Delete);
+                    }
                 }
-                self.current_line()
-                    .as_vec_mut()
-                    .remove(self.cursor_position - 1);
             }
             Direction::Backward => match (self.cursor_position, self.current_line) {
                 (pos, lin) if pos <= 1 && lin > 1 => self.join_lines(),
@@ -183,100 +918,171 @@ impl EditorState {
         }
     }
 
+    /// 単語単位で削除する (Alt + Backspace / Delete)
+    /// 行頭・行末では前後の行と結合する
+    pub fn delete_word(&mut self, dir: Direction) {
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.delete_word(dir));
+            return;
+        }
+        if self.delete_selection() {
+            return;
+        }
+        let glyphs = self.current_line().as_vec().clone();
+        let column = self.cursor_position - 1;
+        match dir {
+            Direction::Backward if column > 0 => {
+                let start = word::prev_word_start(&glyphs, column);
+                self.remove_columns(start..column);
+            }
+            Direction::Forward if column < glyphs.len() => {
+                let end = word::next_word_end(&glyphs, column);
+                self.remove_columns(column..end);
+            }
+            dir => self.delete(dir),
+        }
+    }
+
+    /// 行頭・行末まで削除する (Cmd + Backspace / Delete)
+    /// 行頭・行末では前後の行と結合する
+    pub fn delete_to_line_boundary(&mut self, dir: Direction) {
+        if !self.cursors.is_empty() {
+            self.each_cursor(|e| e.delete_to_line_boundary(dir));
+            return;
+        }
+        if self.delete_selection() {
+            return;
+        }
+        let column = self.cursor_position - 1;
+        let length = self.current_line_length();
+        match dir {
+            Direction::Backward if column > 0 => self.remove_columns(0..column),
+            Direction::Forward if column < length => self.remove_columns(column..length),
+            dir => self.delete(dir),
+        }
+    }
+
+    /// カーソル位置から行末までを削除し、削除した文字列を返す (Emacsの C-k)
+    /// 行末では改行を削除して次の行と結合する
+    pub fn kill_line(&mut self) -> String {
+        let killed = match self.selected_text() {
+            Some(text) => text,
+            None => {
+                let start = self
+                    .contents
+                    .offset(self.current_line, self.cursor_position);
+                let end = self.contents.line_end(self.current_line);
+                let end = if start == end {
+                    (end + 1).min(self.contents.buffer.len())
+                } else {
+                    end
+                };
+                clipboard_text::to_plain_text(&self.contents.buffer.slice(start..end))
+            }
+        };
+        self.delete_to_line_boundary(Direction::Forward);
+        killed
+    }
+
+    /// 現在行の [開始, 終了) (0始まり) を削除し、カーソルを開始位置に置く
+    fn remove_columns(&mut self, columns: Range<usize>) {
+        let line_start = self.contents.offset(self.current_line, 1);
+        self.edit_remove(
+            line_start + columns.start..line_start + columns.end,
+            EditKind::Delete,
+        );
+        self.cursor_position = columns.start + 1;
+    }
+
+    /// 現在行の末尾の改行を削除し、次の行と結合する
+    pub fn join_next_line(&mut self) {
+        let at = self.contents.line_end(self.current_line);
+        self.edit_remove(at..at + 1, EditKind::Delete);
+    }
+
     pub fn join_lines(&mut self) {
-        with_cursor!(|self| {
-            let mut line = self.contents.lines.remove(self.current_line - 1);
-            self.current_line -= 1;
-            let position = self.current_line().as_vec().len() + 1;
-            self.current_line_mut()
-                .as_vec_mut()
-                .append(line.as_vec_mut());
-            self.cursor_position = position;
-        });
+        let at = self.contents.line_end(self.current_line - 1);
+        self.edit_remove(at..at + 1, EditKind::Delete);
+        self.current_line -= 1;
+        self.cursor_position = at - self.contents.offset(self.current_line, 1) + 1;
     }
 
+    /// カーソルの直前の1文字 (書記素クラスタ) を削除する
     pub fn remove_char(&mut self) {
-        self.contents.lines[self.current_line - 1]
-            .as_vec_mut()
-            .remove(self.cursor_position - 2);
+        let at = self
+            .contents
+            .offset(self.current_line, self.cursor_position - 1);
+        self.edit_remove(at..at + 1, EditKind::Delete);
         self.cursor_position -= 1;
     }
 
-    pub fn current_line(&mut self) -> &Line {
-        &mut self.contents.lines[self.current_line - 1]
+    pub fn current_line(&self) -> Line {
+        self.contents.line(self.current_line)
     }
 
-    pub fn current_line_mut(&mut self) -> &mut Line {
-        &mut self.contents.lines[self.current_line - 1]
+    /// カーソルを含まない現在行の文字列
+    pub fn current_line_text(&self) -> String {
+        self.current_line()
+            .as_vec()
+            .iter()
+            .map(glyph_source)
+            .collect()
     }
 
-    pub fn iter(&self) -> std::vec::IntoIter<(usize, &Line)> {
-        let mut line_number = 0;
-
-        let result: Vec<(usize, &Line)> = self
-            .contents
-            .lines
+    /// 現在行のカーソルより前の文字列
+    pub fn text_before_cursor(&self) -> String {
+        self.current_line()
+            .as_vec()
             .iter()
-            .map(|line| {
-                line_number += 1;
-                (line_number, line)
-            })
+            .take(self.cursor_position - 1)
+            .map(glyph_source)
+            .collect()
+    }
+
+    pub fn iter(&self) -> std::vec::IntoIter<(usize, Line)> {
+        let mut lines = self.contents.lines();
+        for caret in self.carets() {
+            let line_idx = caret.head.0;
+            if let Some(line) = lines.get_mut(line_idx - 1) {
+                *line = self.line_with_cursor(line_idx);
+            }
+        }
+
+        let result: Vec<(usize, Line)> = lines
+            .into_iter()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line))
             .collect();
         result.into_iter()
     }
 
     pub fn insert_pill(&mut self, text: &str) {
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-        self.contents.insert(
-            self.current_line,
-            position,
+        self.insert_glyph(
             Glyph::HTMLNode(format!(r#"
                 <button style="appearance: none; border: none; background: none; padding: 0; margin: 0; font: inherit; border-radius: 4px; background-color: #7f00ff; color: #fff; text-transform: uppercase; font-weight: bold; letter-spacing: 1px; padding: 6px 12px; font-size: 12px; line-height: 1.5; cursor: pointer; transition: background-color 0.2s ease-in-out;" onmouseover="this.style.backgroundColor='#5f00cc'" onmouseout="this.style.backgroundColor='#7f00ff'">{text}</button>
-            "#))
+            "#)),
+            EditKind::Other,
         );
     }
 
     // TODO: 2024/12/7 これ不要(のはず)
     pub fn insert_element(&mut self) {
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-
-        self.contents.insert(
-            self.current_line,
-            position,
+        self.insert_glyph(
             // 引数一個追加してここでmatch式入れる
             Glyph::Component(Sample), // Sampleコンポーネントを追加
+            EditKind::Other,
         );
     }
+}
 
-    pub fn insert_ls(&mut self, file_list: &[String]) {
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-        let list_items = file_list
-            .iter()
-            .map(|file| format!("<span>{} / </span>", file))
-            .collect::<Vec<String>>()
-            .join("");
-        self.contents.insert(
-            self.current_line,
-            position,
-            Glyph::HTMLNode(format!(r#"<div>{}</div>"#, list_items)),
-        );
-    }
-
-    pub fn insert_text_not_match(&mut self, file_name: &str, message: &str) {
-        let position = self.cursor_position;
-        self.cursor_position += 1;
-        self.contents.insert(
-            self.current_line,
-            position,
-            Glyph::HTMLNode(format!(
-                r#"
-                <div><span style="font-weight: bold;">{file_name}</span>{message}</div>
-                "#
-            )),
-        );
+/// Glyphを元の文字列にする。カーソルは「❮」で表す
+fn glyph_source(glyph: &Glyph) -> String {
+    match glyph {
+        Glyph::Text(t) => t.clone(),
+        Glyph::Char(c) => c.to_string(),
+        Glyph::Cursor => "❮".to_string(),
+        Glyph::HTMLNode(v) => v.clone(),
+        Glyph::Component(_) => "<Component>".to_string(),
     }
 }
 
@@ -289,3 +1095,327 @@ fn Sample() -> Element {
         button { onclick: move |_| count -= 1, "Down low!" }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_forward_removes_char_under_cursor() {
+        let mut e = EditorState::from("abc");
+        e.set_cursor(1, 2);
+        e.delete(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "a❮c");
+    }
+
+    #[test]
+    fn delete_forward_joins_next_line_at_end_of_line() {
+        let mut e = EditorState::from("ab\ncd");
+        e.set_cursor(1, 3);
+        e.delete(Direction::Forward);
+        assert_eq!(e.contents.len(), 1);
+        assert_eq!(e.get_line_content(1), "ab❮cd");
+
+        // 最終行の末尾では何もしない
+        e.set_cursor_end_of_line();
+        e.delete(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "abcd❮");
+    }
+
+    #[test]
+    fn delete_word_backward_and_forward() {
+        let mut e = EditorState::from("foo\u{00A0}bar\u{00A0}baz");
+        e.set_cursor(1, 8);
+        e.delete_word(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "foo\u{00A0}❮\u{00A0}baz");
+
+        e.set_cursor(1, 1);
+        e.delete_word(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "❮\u{00A0}\u{00A0}baz");
+    }
+
+    #[test]
+    fn replace_before_cursor_completes_word() {
+        let mut e = EditorState::from("vim\u{00A0}me");
+        e.set_cursor_end_of_line();
+        assert_eq!(e.text_before_cursor(), "vim\u{00A0}me");
+        e.replace_before_cursor(2, "memo1 ");
+        assert_eq!(e.get_line_content(1), "vim\u{00A0}memo1\u{00A0}❮");
+
+        // 1回のundoで補完前に戻る
+        e.undo();
+        assert_eq!(e.current_line_text(), "vim\u{00A0}me");
+
+        e.set_cursor(1, 1);
+        e.set_current_line_text("ls -l");
+        assert_eq!(e.get_line_content(1), "ls\u{00A0}-l❮");
+    }
+
+    #[test]
+    fn delete_word_handles_cjk() {
+        let mut e = EditorState::from("日本語のメモ");
+        e.set_cursor_end_of_line();
+        e.delete_word(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "日本語の❮");
+        e.delete_word(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "日本語❮");
+        e.delete_word(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "❮");
+    }
+
+    #[test]
+    fn delete_word_at_line_start_joins_lines() {
+        let mut e = EditorState::from("ab\ncd");
+        e.set_cursor(2, 1);
+        e.delete_word(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "ab❮cd");
+    }
+
+    #[test]
+    fn delete_to_line_boundary() {
+        let mut e = EditorState::from("hello\u{00A0}world");
+        e.set_cursor(1, 6);
+        e.delete_to_line_boundary(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "hello❮");
+        e.delete_to_line_boundary(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "❮");
+    }
+
+    #[test]
+    fn kill_line_returns_removed_text() {
+        let mut e = EditorState::from("ab\ncd");
+        e.set_cursor(1, 2);
+        assert_eq!(e.kill_line(), "b");
+        assert_eq!(e.get_line_content(1), "a❮");
+        assert_eq!(e.kill_line(), "\n");
+        assert_eq!(e.get_line_content(1), "a❮cd");
+    }
+
+    #[test]
+    fn consecutive_deletes_undo_together() {
+        let mut e = EditorState::from("abcd");
+        e.set_cursor(1, 1);
+        e.delete(Direction::Forward);
+        e.delete(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "❮cd");
+        e.undo();
+        assert_eq!(e.get_line_content(1), "❮abcd");
+    }
+
+    #[test]
+    fn vertical_moves_keep_desired_column() {
+        let mut e = EditorState::from("abcdef\nab\nabcdef");
+        e.set_cursor(1, 5);
+        e.go_to_line(Direction::Forward);
+        assert_eq!(e.get_line_content(2), "ab❮");
+        e.go_to_line(Direction::Forward);
+        assert_eq!(e.get_line_content(3), "abcd❮ef");
+
+        // 横移動で維持する位置をリセットする
+        e.move_cursor(Direction::Backward);
+        e.move_lines(Direction::Backward, 2);
+        assert_eq!(e.get_line_content(1), "abc❮def");
+    }
+
+    #[test]
+    fn move_word_crosses_lines() {
+        let mut e = EditorState::from("foo\u{00A0}bar\nbaz");
+        e.set_cursor(1, 1);
+        e.move_word(Direction::Forward);
+        assert_eq!((e.current_line, e.cursor_position), (1, 4));
+        e.move_word(Direction::Forward);
+        assert_eq!((e.current_line, e.cursor_position), (1, 8));
+        e.move_word(Direction::Forward);
+        assert_eq!((e.current_line, e.cursor_position), (2, 1));
+        e.move_word(Direction::Backward);
+        assert_eq!((e.current_line, e.cursor_position), (1, 8));
+        e.move_word(Direction::Backward);
+        assert_eq!((e.current_line, e.cursor_position), (1, 5));
+    }
+
+    #[test]
+    fn document_boundaries_and_pages() {
+        let mut e = EditorState::from("a\nb\nc\nd\ne");
+        e.set_cursor_end_of_document();
+        assert_eq!((e.current_line, e.cursor_position), (5, 2));
+        e.move_lines(Direction::Backward, 3);
+        assert_eq!(e.current_line, 2);
+        e.move_lines(Direction::Backward, 3);
+        assert_eq!(e.current_line, 1);
+        e.set_cursor_start_of_document();
+        assert_eq!((e.current_line, e.cursor_position), (1, 1));
+    }
+
+    #[test]
+    fn cursor_never_splits_grapheme_clusters() {
+        let mut e = EditorState::from("a👨\u{200D}👩\u{200D}👧🇯🇵b");
+        assert_eq!(e.current_line_length(), 4);
+        e.set_cursor(1, 4);
+        e.move_cursor(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "a👨\u{200D}👩\u{200D}👧❮🇯🇵b");
+        e.delete(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "a❮🇯🇵b");
+        e.delete(Direction::Forward);
+        assert_eq!(e.get_line_content(1), "a❮b");
+    }
+
+    #[test]
+    fn combining_mark_joins_previous_cluster() {
+        let mut e = EditorState::from("");
+        e.insert_char('か');
+        e.insert_char('\u{3099}');
+        assert_eq!(e.current_line_length(), 1);
+        assert_eq!(e.cursor_position, 2);
+        e.undo();
+        assert_eq!(e.get_line_content(1), "か❮");
+    }
+
+    #[test]
+    fn multi_cursor_typing_and_deletion() {
+        let mut e = EditorState::from("- a\n- b\n- c");
+        e.set_cursor(1, 4);
+        e.add_cursor_vertical(Direction::Forward);
+        e.add_cursor_vertical(Direction::Forward);
+        e.insert_text("xy");
+        assert_eq!(e.contents.lines().len(), 3);
+        assert_eq!(e.get_line_content(1), "- axy❮");
+        assert_eq!(e.get_line_content(3), "- cxy❮");
+        e.delete(Direction::Backward);
+        assert_eq!(e.get_line_content(2), "- bx❮");
+
+        // 1回のundoで全カーソルの編集を戻す
+        e.undo();
+        assert!(e.cursors.is_empty());
+        assert_eq!(e.contents.line(3).as_vec().len(), 5);
+    }
+
+    #[test]
+    fn multi_cursor_on_one_line_adjusts_offsets() {
+        let mut e = EditorState::from("ab");
+        e.set_cursor(1, 1);
+        e.add_cursor(1, 2);
+        e.add_cursor(1, 3);
+        e.insert_char('|');
+        assert_eq!(e.get_line_content(1), "|❮a|❮b|❮");
+        e.delete(Direction::Backward);
+        assert_eq!(e.get_line_content(1), "❮a❮b❮");
+    }
+
+    #[test]
+    fn add_next_occurrence_selects_matches() {
+        let mut e = EditorState::from("foo\u{00A0}bar\u{00A0}foo\nfoo");
+        e.set_cursor(1, 2);
+        e.add_next_occurrence();
+        assert_eq!(e.selected_text(), Some("foo".to_string()));
+        e.add_next_occurrence();
+        e.add_next_occurrence();
+        assert_eq!(e.cursors.len(), 2);
+        e.insert_char('X');
+        assert_eq!(e.get_line_content(1), "X❮\u{00A0}bar\u{00A0}X❮");
+        assert_eq!(e.get_line_content(2), "X❮");
+    }
+
+    #[test]
+    fn find_next_wraps_and_selects() {
+        let query = SearchQuery {
+            pattern: "ab".to_string(),
+            ..SearchQuery::default()
+        };
+        let mut e = EditorState::from("ab\nxab");
+        e.set_cursor(1, 2);
+        assert_eq!(e.find_next(&query, Direction::Forward), Ok(true));
+        assert_eq!(e.selection_range(), Some(4..6));
+        e.find_next(&query, Direction::Forward).unwrap();
+        assert_eq!(e.selection_range(), Some(0..2));
+        e.find_next(&query, Direction::Backward).unwrap();
+        assert_eq!(e.selection_range(), Some(4..6));
+    }
+
+    #[test]
+    fn replace_is_undoable() {
+        let query = SearchQuery {
+            pattern: r"(\w+)@".to_string(),
+            regex: true,
+            ..SearchQuery::default()
+        };
+        let mut e = EditorState::from("a@\u{00A0}b@\nc@");
+        assert_eq!(e.replace_all(&query, "<$1> "), Ok(3));
+        assert_eq!(e.get_line_content(1), "❮<a>\u{00A0}\u{00A0}<b>\u{00A0}");
+        assert_eq!(e.get_line_content(2), "<c>\u{00A0}");
+        e.undo();
+        assert_eq!(e.get_line_content(2), "c@");
+
+        e.set_cursor(1, 1);
+        e.find_next(&query, Direction::Forward).unwrap();
+        e.replace_current(&query, "$1").unwrap();
+        assert_eq!(e.get_line_content(1), "a\u{00A0}b@❮");
+        assert_eq!(e.selected_text(), Some("b@".to_string()));
+    }
+
+    #[test]
+    fn matches_skip_partial_clusters() {
+        let query = SearchQuery {
+            pattern: "か".to_string(),
+            ..SearchQuery::default()
+        };
+        let e = EditorState::from("か\u{3099}か");
+        let matches = e.find_matches(&query).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0], 1..2);
+    }
+
+    #[test]
+    fn selection_orders_anchor_and_head() {
+        let mut e = EditorState::from("hello\nworld");
+        e.set_cursor(2, 3);
+        e.select_to(1, 2);
+        // 後ろから前に選択しても範囲は前から後ろ
+        assert_eq!(e.selection_range(), Some(1..8));
+        assert_eq!(e.selected_text(), Some("ello\nwo".to_string()));
+
+        e.select_to(2, 3);
+        assert_eq!(e.selection_range(), None);
+        e.select_with(|e| e.set_cursor(2, 5));
+        assert_eq!(e.selected_text(), Some("rl".to_string()));
+        assert_eq!(e.selection_anchor, Some((2, 3)));
+    }
+
+    #[test]
+    fn delete_selection_across_lines() {
+        let mut e = EditorState::from("abc\ndef\nghi");
+        e.set_cursor(1, 2);
+        e.select_to(3, 2);
+        assert!(e.delete_selection());
+        assert_eq!(e.contents.buffer.text(), "ahi");
+        assert_eq!((e.current_line, e.cursor_position), (1, 2));
+        assert_eq!(e.selection_anchor, None);
+        assert!(!e.delete_selection());
+
+        e.undo();
+        assert_eq!(e.contents.buffer.text(), "abc\ndef\nghi");
+
+        e.select_all();
+        assert!(e.delete_selection());
+        assert_eq!(e.contents.buffer.text(), "");
+    }
+
+    #[test]
+    fn range_in_line_covers_each_line() {
+        let mut e = EditorState::from("abc\ndef\nghi");
+        e.set_cursor(1, 2);
+        e.select_to(3, 3);
+        let range = e.selection_range().unwrap();
+        // 先頭行は開始位置から改行まで、途中の行は改行まで、最終行は終了位置まで
+        assert_eq!(e.range_in_line(range.clone(), 1), Some((1, 4)));
+        assert_eq!(e.range_in_line(range.clone(), 2), Some((0, 4)));
+        assert_eq!(e.range_in_line(range.clone(), 3), Some((0, 2)));
+        assert_eq!(e.range_in_line(range.clone(), 4), None);
+        assert_eq!(e.range_in_line(2..2, 1), None);
+
+        // 1行の中の選択
+        e.set_cursor(2, 1);
+        e.select_to(2, 3);
+        assert_eq!(e.selection_in_line(1), None);
+        assert_eq!(e.selection_in_line(2), Some((0, 2)));
+    }
+}
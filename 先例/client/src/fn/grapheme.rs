@@ -0,0 +1,63 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::types::enums::Glyph;
+
+/// 書記素クラスタ1つ分のGlyph
+/// 1文字で表せる場合は `Glyph::Char`、絵文字や結合文字などは `Glyph::Text` にまとめる
+pub fn glyph(cluster: &str) -> Glyph {
+    let mut chars = cluster.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Glyph::Char(ch),
+        // CRLFは1つのクラスタになるが、行の区切りとして扱う
+        _ if cluster == "\r\n" => Glyph::Char('\n'),
+        _ => Glyph::Text(cluster.to_string()),
+    }
+}
+
+/// 文字列を書記素クラスタごとのGlyph列にする
+/// カーソルはGlyph単位で動くため、クラスタの途中に入ることはない
+pub fn glyphs(text: &str) -> Vec<Glyph> {
+    text.graphemes(true).map(glyph).collect()
+}
+
+/// `prev` の直後に `next` を入力したとき、1つの書記素クラスタになる場合はその結合後のGlyph
+/// (濁点の結合文字、ZWJで繋ぐ絵文字、国旗など)
+pub fn join(prev: &Glyph, next: &str) -> Option<Glyph> {
+    let prev = match prev {
+        Glyph::Char('\n') => return None,
+        Glyph::Char(ch) => ch.to_string(),
+        Glyph::Text(text) => text.clone(),
+        _ => return None,
+    };
+    let joined = format!("{prev}{next}");
+    let mut clusters = joined.graphemes(true);
+    match (clusters.next(), clusters.next()) {
+        (Some(cluster), None) => Some(glyph(cluster)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clusters_are_single_glyphs() {
+        // 家族の絵文字 / 国旗 / 結合文字の濁点
+        let text = "a👨\u{200D}👩\u{200D}👧🇯🇵か\u{3099}";
+        assert_eq!(glyphs(text).len(), 4);
+        assert_eq!(glyphs("a\r\nb").len(), 3);
+        assert!(matches!(glyphs("a\r\nb")[1], Glyph::Char('\n')));
+    }
+
+    #[test]
+    fn join_combining_marks() {
+        assert!(matches!(
+            join(&Glyph::Char('か'), "\u{3099}"),
+            Some(Glyph::Text(t)) if t == "か\u{3099}"
+        ));
+        assert!(join(&Glyph::Text("🇯".to_string()), "🇵").is_some());
+        assert!(join(&Glyph::Char('a'), "b").is_none());
+        assert!(join(&Glyph::Char('\n'), "\u{3099}").is_none());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::types::enums::Glyph;
+
+/// 保持するundoグループの上限
+const MAX_GROUPS: usize = 1000;
+
+/// 本文への変更1件(offsetは `TextBuffer` のglyph offset)
+#[derive(Debug, Clone)]
+pub enum Edit {
+    Insert { at: usize, glyphs: Vec<Glyph> },
+    Remove { at: usize, glyphs: Vec<Glyph> },
+}
+
+impl Edit {
+    /// この変更を取り消す変更
+    pub fn inverse(&self) -> Edit {
+        match self {
+            Edit::Insert { at, glyphs } => Edit::Remove {
+                at: *at,
+                glyphs: glyphs.clone(),
+            },
+            Edit::Remove { at, glyphs } => Edit::Insert {
+                at: *at,
+                glyphs: glyphs.clone(),
+            },
+        }
+    }
+
+    /// 変更を適用した後のカーソル位置(glyph offset)
+    pub fn end(&self) -> usize {
+        match self {
+            Edit::Insert { at, glyphs } => at + glyphs.len(),
+            Edit::Remove { at, .. } => *at,
+        }
+    }
+}
+
+/// 連続した入力を1つのundoにまとめるための変更の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// 文字入力。連続していれば1グループにまとめる
+    Typing,
+    /// Backspace。連続していれば1グループにまとめる
+    Delete,
+    /// 改行・ペースト・置換など。常に単独のグループになる
+    Other,
+}
+
+/// 1回のundo/redoで戻す変更のまとまり
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub kind: EditKind,
+    pub edits: Vec<Edit>,
+    /// 変更前のカーソル (行, 位置)
+    pub cursor_before: (usize, usize),
+    sealed: bool,
+}
+
+impl Group {
+    fn accepts(&self, kind: EditKind, edit: &Edit) -> bool {
+        if self.sealed || self.kind != kind || kind == EditKind::Other {
+            return false;
+        }
+        match (self.edits.last(), edit) {
+            // 直前の入力の直後に続けて入力した場合
+            (Some(Edit::Insert { .. }), Edit::Insert { at, .. }) => {
+                self.edits.last().map(Edit::end) == Some(*at)
+            }
+            // 直前の削除位置の直前 (Backspace) または同じ位置 (Delete) を削除した場合
+            (Some(Edit::Remove { at: last, .. }), Edit::Remove { at, glyphs }) => {
+                at + glyphs.len() == *last || at == last
+            }
+            _ => false,
+        }
+    }
+}
+
+/// undo/redo履歴
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    undo: Vec<Group>,
+    redo: Vec<Group>,
+    /// `begin` から `end` までの変更を1グループにまとめる
+    transaction: Option<Group>,
+    /// `begin` の入れ子の深さ。一番外側の `end` でグループを閉じる
+    depth: usize,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// 変更を記録する。条件が合えば直前のグループにまとめる
+    pub fn record(&mut self, kind: EditKind, edit: Edit, cursor_before: (usize, usize)) {
+        self.redo.clear();
+
+        if let Some(group) = self.transaction.as_mut() {
+            group.edits.push(edit);
+            return;
+        }
+
+        if let Some(group) = self.undo.last_mut() {
+            if group.accepts(kind, &edit) {
+                group.edits.push(edit);
+                return;
+            }
+            group.sealed = true;
+        }
+
+        self.undo.push(Group {
+            kind,
+            edits: vec![edit],
+            cursor_before,
+            sealed: kind == EditKind::Other,
+        });
+        if self.undo.len() > MAX_GROUPS {
+            self.undo.remove(0);
+        }
+    }
+
+    /// 以降 `end` までの変更を1回のundoで戻せるようにまとめる
+    pub fn begin(&mut self, cursor_before: (usize, usize)) {
+        self.depth += 1;
+        if self.transaction.is_none() {
+            self.seal();
+            self.transaction = Some(Group {
+                kind: EditKind::Other,
+                edits: Vec::new(),
+                cursor_before,
+                sealed: true,
+            });
+        }
+    }
+
+    pub fn end(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+        if self.depth > 0 {
+            return;
+        }
+        if let Some(group) = self.transaction.take() {
+            if !group.edits.is_empty() {
+                self.undo.push(group);
+                if self.undo.len() > MAX_GROUPS {
+                    self.undo.remove(0);
+                }
+            }
+        }
+    }
+
+    /// 現在のグループを閉じ、次の変更を新しいグループにする
+    /// カーソル移動やIMEの確定時に呼ぶ
+    pub fn seal(&mut self) {
+        if let Some(group) = self.undo.last_mut() {
+            group.sealed = true;
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Group> {
+        let mut group = self.undo.pop()?;
+        group.sealed = true;
+        self.redo.push(group.clone());
+        Some(group)
+    }
+
+    pub fn pop_redo(&mut self) -> Option<Group> {
+        let group = self.redo.pop()?;
+        self.undo.push(group.clone());
+        Some(group)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.transaction = None;
+        self.depth = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(at: usize, text: &str) -> Edit {
+        Edit::Insert {
+            at,
+            glyphs: text.chars().map(Glyph::Char).collect(),
+        }
+    }
+
+    fn remove(at: usize, text: &str) -> Edit {
+        Edit::Remove {
+            at,
+            glyphs: text.chars().map(Glyph::Char).collect(),
+        }
+    }
+
+    fn group_len(group: Option<Group>) -> usize {
+        group.map_or(0, |group| group.edits.len())
+    }
+
+    #[test]
+    fn groups_typing_until_delete() {
+        let mut history = History::new();
+        history.record(EditKind::Typing, insert(0, "a"), (1, 1));
+        history.record(EditKind::Typing, insert(1, "b"), (1, 2));
+        history.record(EditKind::Typing, insert(2, "c"), (1, 3));
+        // Backspaceで新しいグループになり、続けたBackspaceはまとまる
+        history.record(EditKind::Delete, remove(2, "c"), (1, 4));
+        history.record(EditKind::Delete, remove(1, "b"), (1, 3));
+        // 離れた位置への入力はまとめない
+        history.record(EditKind::Typing, insert(5, "d"), (1, 6));
+
+        assert_eq!(group_len(history.pop_undo()), 1);
+        assert_eq!(group_len(history.pop_undo()), 2);
+        let typing = history.pop_undo().unwrap();
+        assert_eq!((typing.edits.len(), typing.cursor_before), (3, (1, 1)));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn other_edits_and_seal_break_groups() {
+        let mut history = History::new();
+        history.record(EditKind::Typing, insert(0, "a"), (1, 1));
+        history.seal();
+        history.record(EditKind::Typing, insert(1, "b"), (1, 2));
+        history.record(EditKind::Other, insert(2, "\n"), (1, 3));
+        history.record(EditKind::Other, insert(3, "\n"), (2, 1));
+        assert_eq!(group_len(history.pop_undo()), 1);
+        assert_eq!(group_len(history.pop_undo()), 1);
+        assert_eq!(group_len(history.pop_undo()), 1);
+        assert_eq!(group_len(history.pop_undo()), 1);
+    }
+
+    #[test]
+    fn nested_transactions_make_one_group() {
+        let mut history = History::new();
+        history.record(EditKind::Typing, insert(0, "a"), (1, 1));
+        history.begin((1, 2));
+        history.record(EditKind::Typing, insert(1, "b"), (1, 2));
+        history.begin((1, 3));
+        history.record(EditKind::Delete, remove(0, "a"), (1, 3));
+        history.end();
+        // 内側の `end` ではまだ閉じない
+        history.record(EditKind::Other, insert(1, "c"), (1, 2));
+        history.end();
+
+        let group = history.pop_undo().unwrap();
+        assert_eq!((group.edits.len(), group.cursor_before), (3, (1, 2)));
+        assert_eq!(group_len(history.pop_undo()), 1);
+
+        // 変更のないトランザクションは残さない
+        history.clear();
+        history.begin((1, 1));
+        history.end();
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut history = History::new();
+        history.record(EditKind::Other, insert(0, "a"), (1, 1));
+        history.record(EditKind::Other, insert(1, "b"), (1, 2));
+        history.pop_undo();
+        assert!(history.can_redo());
+        assert_eq!(group_len(history.pop_redo()), 1);
+        assert!(!history.can_redo());
+
+        history.pop_undo();
+        history.record(EditKind::Typing, insert(1, "c"), (1, 2));
+        assert!(history.pop_redo().is_none());
+    }
+}
@@ -0,0 +1,429 @@
+use keyboard_types::{Code, Modifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::keymap::{DEFAULT_KEYMAP, EMACS_KEYMAP};
+use crate::r#fn::editor_command::EditorCommand;
+
+/// 修飾キーを含めた1回のキー入力
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChord {
+    pub code: Code,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+    pub shift: bool,
+}
+
+impl KeyChord {
+    pub fn new(code: Code, modifiers: Modifiers) -> KeyChord {
+        KeyChord {
+            code,
+            ctrl: modifiers.contains(Modifiers::CONTROL),
+            alt: modifiers.contains(Modifiers::ALT),
+            meta: modifiers.contains(Modifiers::META),
+            shift: modifiers.contains(Modifiers::SHIFT),
+        }
+    }
+
+    /// `C-x` `M-f` のようなEmacs表記と `Cmd+Shift+Z` のような表記の両方を受け付ける
+    pub fn parse(chord: &str) -> Result<KeyChord, KeymapError> {
+        let invalid = || KeymapError::InvalidKey(chord.to_string());
+        let mut key = KeyChord {
+            code: Code::Unidentified,
+            ctrl: false,
+            alt: false,
+            meta: false,
+            shift: false,
+        };
+
+        let name = if chord.len() > 1 && chord.contains('+') {
+            let mut parts: Vec<&str> = chord.split('+').collect();
+            let name = parts.pop().ok_or_else(invalid)?;
+            for modifier in parts {
+                match modifier.to_ascii_lowercase().as_str() {
+                    "ctrl" | "control" => key.ctrl = true,
+                    "alt" | "option" | "opt" => key.alt = true,
+                    "cmd" | "command" | "meta" | "super" => key.meta = true,
+                    "shift" => key.shift = true,
+                    _ => return Err(invalid()),
+                }
+            }
+            name
+        } else {
+            let mut rest = chord;
+            while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+                match &rest[..1] {
+                    "C" => key.ctrl = true,
+                    "M" => key.alt = true,
+                    "s" => key.meta = true,
+                    "S" => key.shift = true,
+                    _ => return Err(invalid()),
+                }
+                rest = &rest[2..];
+            }
+            rest
+        };
+
+        key.code = parse_code(name).ok_or_else(invalid)?;
+        Ok(key)
+    }
+
+    fn without_shift(self) -> KeyChord {
+        KeyChord {
+            shift: false,
+            ..self
+        }
+    }
+}
+
+/// キー名から `Code` を求める。1文字のキーは配列に関係なく物理キーとして扱う
+fn parse_code(name: &str) -> Option<Code> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        let code = match ch.to_ascii_lowercase() {
+            c @ 'a'..='z' => format!("Key{}", c.to_ascii_uppercase()),
+            c @ '0'..='9' => format!("Digit{c}"),
+            '/' => "Slash".to_string(),
+            ',' => "Comma".to_string(),
+            '.' => "Period".to_string(),
+            ';' => "Semicolon".to_string(),
+            '-' => "Minus".to_string(),
+            '=' => "Equal".to_string(),
+            '[' => "BracketLeft".to_string(),
+            ']' => "BracketRight".to_string(),
+            _ => return None,
+        };
+        return Code::from_str(&code).ok();
+    }
+    let alias = match name.to_ascii_lowercase().as_str() {
+        "left" => "ArrowLeft",
+        "right" => "ArrowRight",
+        "up" => "ArrowUp",
+        "down" => "ArrowDown",
+        "esc" => "Escape",
+        "del" => "Delete",
+        "ret" | "return" => "Enter",
+        "spc" => "Space",
+        "tab" => "Tab",
+        _ => name,
+    };
+    match Code::from_str(alias) {
+        Ok(Code::Unidentified) | Err(_) => None,
+        Ok(code) => Some(code),
+    }
+}
+
+impl fmt::Display for KeyChord {
+    /// `Ctrl+Shift+Z` の形式で表示する (ステータスラインやコマンドパレット)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pressed, modifier) in [
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.meta, "Cmd+"),
+            (self.shift, "Shift+"),
+        ] {
+            if pressed {
+                write!(f, "{modifier}")?;
+            }
+        }
+        let code = self.code.to_string();
+        let name = code
+            .strip_prefix("Key")
+            .or_else(|| code.strip_prefix("Digit"))
+            .unwrap_or(&code);
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapError {
+    /// 設定ファイルを読めない
+    Parse(String),
+    /// キーの表記が不正
+    InvalidKey(String),
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Parse(message) => write!(f, "keymap parse error: {message}"),
+            KeymapError::InvalidKey(key) => write!(f, "invalid key: {key}"),
+        }
+    }
+}
+
+/// 組み込みのキーマップ
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeymapProfile {
+    #[default]
+    Default,
+    /// デフォルトにEmacsのキーバインドを重ねたもの
+    Emacs,
+}
+
+/// ユーザーのキーマップ設定 (JSON / TOML)
+///
+/// ```toml
+/// profile = "emacs"
+///
+/// [bindings]
+/// "C-x C-s" = "save"
+/// "Cmd+K" = "unbind"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct KeymapConfig {
+    pub profile: KeymapProfile,
+    /// キー列 (空白区切り) とコマンド名
+    pub bindings: BTreeMap<String, EditorCommand>,
+}
+
+impl KeymapConfig {
+    /// `{` で始まる場合はJSON、それ以外はTOMLとして読む
+    pub fn parse(text: &str) -> Result<KeymapConfig, KeymapError> {
+        if text.trim_start().starts_with('{') {
+            serde_json::from_str(text).map_err(|err| KeymapError::Parse(err.to_string()))
+        } else {
+            toml::from_str(text).map_err(|err| KeymapError::Parse(err.to_string()))
+        }
+    }
+}
+
+/// キー入力を解決した結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyInput {
+    /// 割り当てられたコマンド。`extend` は Shift による選択範囲の拡張
+    Command {
+        command: EditorCommand,
+        extend: bool,
+    },
+    /// `C-x` のように続きのキーを待っている
+    Pending,
+    /// 割り当てがない。通常のキー処理に任せる
+    Unbound,
+}
+
+/// キー列とコマンドの対応表
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Vec<KeyChord>, EditorCommand)>,
+    /// 入力途中のキー列
+    pending: Vec<KeyChord>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::from_profile(KeymapProfile::Default)
+    }
+}
+
+impl Keymap {
+    pub fn from_profile(profile: KeymapProfile) -> Keymap {
+        let mut keymap = Keymap {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+        };
+        let tables: &[&[(&str, EditorCommand)]] = match profile {
+            KeymapProfile::Default => &[DEFAULT_KEYMAP],
+            KeymapProfile::Emacs => &[DEFAULT_KEYMAP, EMACS_KEYMAP],
+        };
+        for (keys, command) in tables.iter().flat_map(|table| table.iter()) {
+            keymap
+                .bind(keys, *command)
+                .expect("built-in keymap must be valid");
+        }
+        keymap
+    }
+
+    /// 設定のプロファイルに設定のキー割り当てを重ねる
+    pub fn from_config(config: &KeymapConfig) -> Result<Keymap, KeymapError> {
+        let mut keymap = Keymap::from_profile(config.profile);
+        for (keys, command) in &config.bindings {
+            keymap.bind(keys, *command)?;
+        }
+        Ok(keymap)
+    }
+
+    /// キー列 (空白区切り) にコマンドを割り当てる
+    /// 前方一致するキー列 (`C-x` と `C-x C-s` など) は後から割り当てた方を残す
+    pub fn bind(&mut self, keys: &str, command: EditorCommand) -> Result<(), KeymapError> {
+        let sequence = keys
+            .split_whitespace()
+            .map(KeyChord::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if sequence.is_empty() {
+            return Err(KeymapError::InvalidKey(keys.to_string()));
+        }
+        self.bindings
+            .retain(|(bound, _)| !(bound.starts_with(&sequence) || sequence.starts_with(bound)));
+        if command != EditorCommand::Unbind {
+            self.bindings.push((sequence, command));
+        }
+        Ok(())
+    }
+
+    /// コマンドに割り当てたキー列。複数ある場合は最初に割り当てたもの
+    pub fn keys_for(&self, command: EditorCommand) -> Option<String> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == command)
+            .map(|(sequence, _)| sequence_label(sequence))
+    }
+
+    /// 入力途中のキー列 (ステータスライン用)
+    pub fn pending(&self) -> String {
+        sequence_label(&self.pending)
+    }
+
+    /// キー入力を1つ受け取り、割り当てられたコマンドを探す
+    pub fn feed(&mut self, chord: KeyChord) -> KeyInput {
+        let mut sequence = std::mem::take(&mut self.pending);
+        let is_prefix = !sequence.is_empty();
+        sequence.push(chord);
+
+        if let Some(command) = self.lookup(&sequence) {
+            return KeyInput::Command {
+                command,
+                extend: false,
+            };
+        }
+        if self
+            .bindings
+            .iter()
+            .any(|(bound, _)| bound.len() > sequence.len() && bound.starts_with(&sequence))
+        {
+            self.pending = sequence;
+            return KeyInput::Pending;
+        }
+        // Shift 付きの割り当てがなければ Shift なしのコマンドを使う
+        // カーソル移動の場合は選択範囲を広げる
+        if chord.shift && !is_prefix {
+            if let Some(command) = self.lookup(&[chord.without_shift()]) {
+                return KeyInput::Command {
+                    command,
+                    extend: command.motion().is_some(),
+                };
+            }
+        }
+        KeyInput::Unbound
+    }
+
+    fn lookup(&self, sequence: &[KeyChord]) -> Option<EditorCommand> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == sequence)
+            .map(|(_, command)| *command)
+    }
+}
+
+fn sequence_label(sequence: &[KeyChord]) -> String {
+    sequence
+        .iter()
+        .map(|chord| chord.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(keys: &str) -> KeyChord {
+        KeyChord::parse(keys).unwrap()
+    }
+
+    #[test]
+    fn parses_both_notations() {
+        assert_eq!(chord("C-x"), KeyChord::new(Code::KeyX, Modifiers::CONTROL));
+        assert_eq!(
+            chord("Cmd+Shift+Z"),
+            KeyChord::new(Code::KeyZ, Modifiers::META | Modifiers::SHIFT)
+        );
+        assert_eq!(
+            chord("M-Left"),
+            KeyChord::new(Code::ArrowLeft, Modifiers::ALT)
+        );
+        assert_eq!(chord("PageDown").code, Code::PageDown);
+        assert!(KeyChord::parse("Hyper+x").is_err());
+        assert!(KeyChord::parse("C-nope").is_err());
+        assert_eq!(chord("C-M-f").to_string(), "Ctrl+Alt+F");
+    }
+
+    #[test]
+    fn shift_extends_motions() {
+        let mut keymap = Keymap::default();
+        assert_eq!(
+            keymap.feed(chord("S-ArrowLeft")),
+            KeyInput::Command {
+                command: EditorCommand::MoveLeft,
+                extend: true
+            }
+        );
+        assert_eq!(
+            keymap.feed(chord("Cmd+Shift+Z")),
+            KeyInput::Command {
+                command: EditorCommand::Redo,
+                extend: false
+            }
+        );
+        assert_eq!(keymap.feed(chord("q")), KeyInput::Unbound);
+    }
+
+    #[test]
+    fn emacs_profile_and_sequences() {
+        let mut keymap = Keymap::from_profile(KeymapProfile::Emacs);
+        let command = |command| KeyInput::Command {
+            command,
+            extend: false,
+        };
+        assert_eq!(keymap.feed(chord("C-a")), command(EditorCommand::LineStart));
+        assert_eq!(keymap.feed(chord("M-f")), command(EditorCommand::WordRight));
+        assert_eq!(keymap.feed(chord("C-x")), KeyInput::Pending);
+        assert_eq!(keymap.pending(), "Ctrl+X");
+        assert_eq!(keymap.feed(chord("C-s")), command(EditorCommand::Save));
+        assert_eq!(keymap.pending(), "");
+        assert_eq!(
+            keymap.keys_for(EditorCommand::Save).as_deref(),
+            Some("Cmd+S")
+        );
+
+        // 続きが割り当てられていなければ入力途中のキー列は捨てる
+        assert_eq!(keymap.feed(chord("C-x")), KeyInput::Pending);
+        assert_eq!(keymap.feed(chord("q")), KeyInput::Unbound);
+        assert_eq!(keymap.feed(chord("C-e")), command(EditorCommand::LineEnd));
+    }
+
+    #[test]
+    fn overrides_from_json_and_toml() {
+        let json = r#"{ "bindings": { "Cmd+S": "save", "Cmd+K": "unbind" } }"#;
+        let mut keymap = Keymap::from_config(&KeymapConfig::parse(json).unwrap()).unwrap();
+        assert_eq!(
+            keymap.feed(chord("Cmd+S")),
+            KeyInput::Command {
+                command: EditorCommand::Save,
+                extend: false
+            }
+        );
+        assert_eq!(keymap.feed(chord("Cmd+K")), KeyInput::Unbound);
+
+        let toml = "profile = \"emacs\"\n[bindings]\n\"C-k\" = \"delete_to_line_end\"\n";
+        let config = KeymapConfig::parse(toml).unwrap();
+        assert_eq!(config.profile, KeymapProfile::Emacs);
+        let mut keymap = Keymap::from_config(&config).unwrap();
+        assert_eq!(
+            keymap.feed(chord("C-k")),
+            KeyInput::Command {
+                command: EditorCommand::DeleteToLineEnd,
+                extend: false
+            }
+        );
+
+        assert!(KeymapConfig::parse(r#"{ "bindings": { "C-a": "fly" } }"#).is_err());
+        let config = KeymapConfig::parse(r#"{ "bindings": { "C-?x": "save" } }"#).unwrap();
+        assert!(Keymap::from_config(&config).is_err());
+    }
+}
@@ -1,8 +1,12 @@
+use crate::r#fn::grapheme;
 use crate::types::enums::Glyph;
 
 /// Represents sningle line in editor state.
 /// Line is a simple vector of `Renderable` structs,
 /// But also supports reporting length, inserting and removing elements at positio
+///
+/// 本文は `TextBuffer` が保持しており、Lineは描画用に切り出した1行分のコピー
+#[derive(Clone, Default)]
 pub struct Line(Vec<Glyph>);
 
 impl Line {
@@ -26,18 +30,20 @@ impl Line {
     }
 }
 
+impl From<Vec<Glyph>> for Line {
+    fn from(value: Vec<Glyph>) -> Self {
+        Line(value)
+    }
+}
 impl From<String> for Line {
     fn from(value: String) -> Self {
-        let vec: Vec<Glyph> = value.chars().map(Glyph::Char).collect();
-
-        Line(vec)
+        Line::from(value.as_str())
     }
 }
+/// 書記素クラスタごとに1つのGlyphにする
 impl From<&str> for Line {
     fn from(value: &str) -> Self {
-        let vec: Vec<Glyph> = value.chars().map(Glyph::Char).collect();
-
-        Line(vec)
+        Line(grapheme::glyphs(value))
     }
 }
 impl std::fmt::Debug for Line {
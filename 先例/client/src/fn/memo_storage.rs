@@ -0,0 +1,268 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use memo_tree::{MemoTree, NodeId};
+use serde::{Deserialize, Serialize};
+
+use crate::r#fn::editor_state::EditorState;
+
+/// 最後に編集してから保存するまでの時間 (ミリ秒)
+pub const AUTOSAVE_DELAY_MS: u32 = 500;
+
+/// `MemoStorage` が返すFuture
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// 保存するメモ1件 (本文とカーソルの位置)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoSnapshot {
+    pub text: String,
+    pub current_line: usize,
+    pub cursor_position: usize,
+}
+
+impl MemoSnapshot {
+    pub fn from_state(state: &EditorState) -> MemoSnapshot {
+        MemoSnapshot {
+            text: state.contents.buffer.text(),
+            current_line: state.current_line,
+            cursor_position: state.cursor_position,
+        }
+    }
+
+    /// 保存した本文とカーソルの位置でエディタを作る
+    pub fn to_state(&self) -> EditorState {
+        let mut state = EditorState::from(self.text.as_str());
+        state.set_cursor(self.current_line, self.cursor_position);
+        state
+    }
+
+    /// 階層に表示するサイズ (バイト数)
+    pub fn size(&self) -> u64 {
+        self.text.len() as u64
+    }
+}
+
+/// メモの本文と階層の保存先
+/// 未ログインのユーザーはブラウザ (localStorage) に保存する
+pub trait MemoStorage {
+    /// 保存していない・読めない場合は `None`
+    fn load(&self, memo_id: NodeId) -> StorageFuture<'_, Option<MemoSnapshot>>;
+
+    fn save(&self, memo_id: NodeId, snapshot: &MemoSnapshot) -> StorageFuture<'_, ()>;
+
+    fn remove(&self, memo_id: NodeId) -> StorageFuture<'_, ()>;
+
+    /// 保存していない・読めない場合は `None`
+    fn load_tree(&self) -> StorageFuture<'_, Option<MemoTree>>;
+
+    fn save_tree(&self, tree: &MemoTree) -> StorageFuture<'_, ()>;
+}
+
+/// 本文を保存し、階層に表示するサイズと更新日時も更新する
+/// 階層にあるメモの名前 (サーバーに送るタイトル) を返す
+pub async fn save_memo(
+    storage: &dyn MemoStorage,
+    memo_id: NodeId,
+    snapshot: &MemoSnapshot,
+) -> Option<String> {
+    storage.save(memo_id, snapshot).await;
+    let mut tree = storage.load_tree().await?;
+    tree.update_memo(memo_id, snapshot.size()).ok()?;
+    storage.save_tree(&tree).await;
+    tree.get(memo_id).map(|node| node.name.clone())
+}
+
+/// `cp` でコピーしたメモ `memo_id` に、コピー元 `source_id` の本文を保存する
+/// 保存したメモの名前 (サーバーに送るタイトル) と本文を返す
+pub async fn copy_memo(
+    storage: &dyn MemoStorage,
+    memo_id: NodeId,
+    source_id: NodeId,
+) -> Option<(String, MemoSnapshot)> {
+    let snapshot = storage.load(source_id).await?;
+    let title = save_memo(storage, memo_id, &snapshot).await?;
+    Some((title, snapshot))
+}
+
+/// `before` にあって `after` にないメモ (`rm` で消したメモ)
+pub fn removed_memos(before: &MemoTree, after: &MemoTree) -> Vec<NodeId> {
+    before
+        .nodes()
+        .filter(|node| !node.is_folder() && after.get(node.id).is_none())
+        .map(|node| node.id)
+        .collect()
+}
+
+/// 編集のたびに保存しないよう、最後の編集から少し待って保存する
+/// 待っている間に次の編集があれば、前の保存はやめて最新の内容だけを保存する
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Autosave {
+    generation: u64,
+    pending: Option<(NodeId, MemoSnapshot)>,
+}
+
+impl Autosave {
+    /// 保存を予約する。待った後に `take_if_current` に渡す番号を返す
+    pub fn schedule(&mut self, memo_id: NodeId, snapshot: MemoSnapshot) -> u64 {
+        self.generation += 1;
+        self.pending = Some((memo_id, snapshot));
+        self.generation
+    }
+
+    /// 予約の後に編集がなければ、保存する内容を取り出す
+    pub fn take_if_current(&mut self, generation: u64) -> Option<(NodeId, MemoSnapshot)> {
+        if generation != self.generation {
+            return None;
+        }
+        self.pending.take()
+    }
+
+    /// 待たずに保存する内容 (別のメモを開く前など)
+    pub fn take(&mut self) -> Option<(NodeId, MemoSnapshot)> {
+        self.pending.take()
+    }
+}
+
+/// テスト用: メモリ上の保存先
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct InMemoryStorage {
+    memos: std::cell::RefCell<std::collections::HashMap<NodeId, MemoSnapshot>>,
+    tree: std::cell::RefCell<Option<MemoTree>>,
+}
+
+#[cfg(test)]
+impl InMemoryStorage {
+    /// 本文だけのメモを保存する
+    pub fn insert(&self, memo_id: NodeId, text: &str) {
+        let snapshot = MemoSnapshot {
+            text: text.to_string(),
+            current_line: 1,
+            cursor_position: 1,
+        };
+        self.memos.borrow_mut().insert(memo_id, snapshot);
+    }
+}
+
+#[cfg(test)]
+impl MemoStorage for InMemoryStorage {
+    fn load(&self, memo_id: NodeId) -> StorageFuture<'_, Option<MemoSnapshot>> {
+        let snapshot = self.memos.borrow().get(&memo_id).cloned();
+        Box::pin(async move { snapshot })
+    }
+
+    fn save(&self, memo_id: NodeId, snapshot: &MemoSnapshot) -> StorageFuture<'_, ()> {
+        self.memos.borrow_mut().insert(memo_id, snapshot.clone());
+        Box::pin(async {})
+    }
+
+    fn remove(&self, memo_id: NodeId) -> StorageFuture<'_, ()> {
+        self.memos.borrow_mut().remove(&memo_id);
+        Box::pin(async {})
+    }
+
+    fn load_tree(&self) -> StorageFuture<'_, Option<MemoTree>> {
+        let tree = self.tree.borrow().clone();
+        Box::pin(async move { tree })
+    }
+
+    fn save_tree(&self, tree: &MemoTree) -> StorageFuture<'_, ()> {
+        *self.tree.borrow_mut() = Some(tree.clone());
+        Box::pin(async {})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::block_on;
+    use memo_tree::NodeKind;
+
+    #[test]
+    fn saves_and_restores_editor_state() {
+        let storage = InMemoryStorage::default();
+        let mut state = EditorState::from("hello\nworld");
+        state.set_cursor(2, 3);
+        block_on(storage.save(1, &MemoSnapshot::from_state(&state)));
+
+        let restored = block_on(storage.load(1)).unwrap().to_state();
+        assert_eq!(restored.contents.buffer.text(), "hello\nworld");
+        assert_eq!((restored.current_line, restored.cursor_position), (2, 3));
+
+        block_on(storage.remove(1));
+        assert_eq!(block_on(storage.load(1)), None);
+    }
+
+    #[test]
+    fn saves_tree_and_finds_removed_memos() {
+        let storage = InMemoryStorage::default();
+        let mut tree = MemoTree::new();
+        tree.create("/notes", NodeKind::Folder).unwrap();
+        let memo = tree.create("/notes/memo1", NodeKind::Memo).unwrap();
+        block_on(storage.save_tree(&tree));
+        assert_eq!(block_on(storage.load_tree()), Some(tree.clone()));
+
+        let snapshot = MemoSnapshot::from_state(&EditorState::from("hello"));
+        let title = block_on(save_memo(&storage, memo, &snapshot));
+        assert_eq!(title.as_deref(), Some("memo1"));
+        let saved = block_on(storage.load_tree()).unwrap();
+        assert_eq!(saved.get(memo).unwrap().size, 5);
+
+        let mut after = tree.clone();
+        after
+            .remove(tree.lookup("/notes").unwrap().unwrap())
+            .unwrap();
+        assert_eq!(removed_memos(&tree, &after), vec![memo]);
+    }
+
+    #[test]
+    fn copies_memo_bodies() {
+        let storage = InMemoryStorage::default();
+        let mut tree = MemoTree::new();
+        tree.create("/notes", NodeKind::Folder).unwrap();
+        let memo = tree.create("/notes/memo1", NodeKind::Memo).unwrap();
+        let copied = tree.copy_path("/notes", "/backup").unwrap();
+        block_on(storage.save_tree(&tree));
+        storage.insert(memo, "hello");
+
+        let copy = tree.memo_id("/backup/memo1").unwrap();
+        assert!(copied.contains(&(copy, memo)));
+        let (title, snapshot) = block_on(copy_memo(&storage, copy, memo)).unwrap();
+        assert_eq!((title.as_str(), snapshot.text.as_str()), ("memo1", "hello"));
+        assert_eq!(block_on(storage.load(copy)), Some(snapshot));
+        assert_eq!(
+            block_on(storage.load_tree())
+                .unwrap()
+                .get(copy)
+                .unwrap()
+                .size,
+            5
+        );
+
+        // 本文を保存していないメモ (空のメモ) はそのまま
+        let folder = tree.lookup("/backup").unwrap().unwrap();
+        assert_eq!(
+            block_on(copy_memo(
+                &storage,
+                folder,
+                tree.lookup("/notes").unwrap().unwrap()
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn autosave_keeps_only_latest_edit() {
+        let snapshot = |text: &str| MemoSnapshot {
+            text: text.to_string(),
+            current_line: 1,
+            cursor_position: 1,
+        };
+        let mut autosave = Autosave::default();
+        let first = autosave.schedule(1, snapshot("a"));
+        let second = autosave.schedule(1, snapshot("ab"));
+        assert_eq!(autosave.take_if_current(first), None);
+        assert_eq!(autosave.take_if_current(second), Some((1, snapshot("ab"))));
+        assert_eq!(autosave.take(), None);
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+use std::ops::DerefMut;
+
+use memo_tree::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::r#fn::memo_storage::StorageFuture;
+
+/// サーバーのメモ (`/v1/memos` が返すJSONの一部)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteMemo {
+    pub id: String,
+    pub title: String,
+    pub body: String,
+    /// 更新のたびにサーバーが1つ増やす
+    pub version: i64,
+}
+
+/// サーバーに送っていない編集
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingEdit {
+    pub memo_id: NodeId,
+    pub title: String,
+    pub body: String,
+}
+
+/// サーバーに送るリクエスト。`remote_id` がなければ作成、あれば `version` を元にした更新
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncRequest {
+    pub edit: PendingEdit,
+    pub remote_id: Option<String>,
+    pub version: Option<i64>,
+}
+
+/// サーバーの応答
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncOutcome {
+    Saved(RemoteMemo),
+    /// 他の端末で更新されていた。サーバーの最新のメモ
+    Conflict(RemoteMemo),
+    /// サーバーで削除されていた
+    Missing,
+    /// つながらない。残りの編集はオンラインに戻ってから送る
+    Offline,
+    /// それ以外のエラー。この編集は送らない (本文はローカルに残っている)
+    Failed,
+}
+
+/// サーバーのメモとのつながり
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncedMemo {
+    pub remote_id: String,
+    /// 最後に同期したバージョン。次の更新はこのバージョンを元にする
+    pub version: i64,
+    /// 最後に同期した本文
+    pub body: String,
+}
+
+/// 送った編集とサーバーのメモが食い違っている
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conflict {
+    pub memo_id: NodeId,
+    /// この端末の本文
+    pub local: String,
+    pub remote: RemoteMemo,
+}
+
+/// 食い違いの解消のしかた
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// この端末の本文でサーバーを上書きする
+    KeepLocal,
+    /// サーバーの本文を使う
+    KeepRemote,
+    /// 違う行を両方残して、手で直す
+    Merge,
+}
+
+/// オフラインの間の編集を溜めておき、オンラインに戻ったら順に送る
+/// 他の端末の更新と食い違った場合は上書きせず `conflicts` に残す
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    synced: HashMap<NodeId, SyncedMemo>,
+    queue: Vec<PendingEdit>,
+    conflicts: Vec<Conflict>,
+}
+
+impl SyncState {
+    /// 保存した編集を送る順番に加える。同じメモの古い編集は送らない
+    /// 食い違いを解消するまではサーバーに送らず、この端末の本文だけ更新する
+    pub fn enqueue(&mut self, memo_id: NodeId, title: &str, body: &str) {
+        if let Some(conflict) = self.conflicts.iter_mut().find(|c| c.memo_id == memo_id) {
+            conflict.local = body.to_string();
+            return;
+        }
+        self.queue.retain(|edit| edit.memo_id != memo_id);
+        if self
+            .synced
+            .get(&memo_id)
+            .is_some_and(|synced| synced.body == body)
+        {
+            return;
+        }
+        self.queue.push(PendingEdit {
+            memo_id,
+            title: title.to_string(),
+            body: body.to_string(),
+        });
+    }
+
+    /// 保存しておいた状態を読み込む。読み込むまでの間に溜めた編集は上に重ねる
+    pub fn restore(&mut self, loaded: SyncState) {
+        let current = std::mem::replace(self, loaded);
+        self.synced.extend(current.synced);
+        for conflict in current.conflicts {
+            self.conflicts.retain(|c| c.memo_id != conflict.memo_id);
+            self.conflicts.push(conflict);
+        }
+        for edit in current.queue {
+            self.enqueue(edit.memo_id, &edit.title, &edit.body);
+        }
+    }
+
+    /// 送っていない編集の数
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn conflict(&self, memo_id: NodeId) -> Option<&Conflict> {
+        self.conflicts.iter().find(|c| c.memo_id == memo_id)
+    }
+
+    /// `rm` で消したメモは送らない
+    pub fn forget(&mut self, memo_id: NodeId) {
+        self.synced.remove(&memo_id);
+        self.queue.retain(|edit| edit.memo_id != memo_id);
+        self.conflicts.retain(|c| c.memo_id != memo_id);
+    }
+
+    /// 次に送るリクエスト
+    pub fn next_request(&self) -> Option<SyncRequest> {
+        let edit = self.queue.first()?.clone();
+        let synced = self.synced.get(&edit.memo_id);
+        Some(SyncRequest {
+            remote_id: synced.map(|synced| synced.remote_id.clone()),
+            version: synced.map(|synced| synced.version),
+            edit,
+        })
+    }
+
+    /// 応答を反映する。続けて送れる場合は `true`
+    pub fn apply(&mut self, request: &SyncRequest, outcome: SyncOutcome) -> bool {
+        let memo_id = request.edit.memo_id;
+        match outcome {
+            SyncOutcome::Saved(remote) => {
+                self.finish(&request.edit);
+                self.mark_synced(memo_id, remote);
+            }
+            SyncOutcome::Conflict(remote) => {
+                // 送っている間に編集していれば、新しい方を比べる
+                let local = self
+                    .queue
+                    .iter()
+                    .find(|edit| edit.memo_id == memo_id)
+                    .map_or_else(|| request.edit.body.clone(), |edit| edit.body.clone());
+                self.queue.retain(|edit| edit.memo_id != memo_id);
+                if remote.body == local {
+                    self.mark_synced(memo_id, remote);
+                } else {
+                    self.conflicts.push(Conflict {
+                        memo_id,
+                        local,
+                        remote,
+                    });
+                }
+            }
+            // 次は新しいメモとして作る
+            SyncOutcome::Missing if request.remote_id.is_some() => {
+                self.synced.remove(&memo_id);
+            }
+            SyncOutcome::Missing | SyncOutcome::Failed => self.finish(&request.edit),
+            SyncOutcome::Offline => return false,
+        }
+        true
+    }
+
+    /// 食い違いを解消する。エディタの本文を置き換える場合はその本文を返す
+    pub fn resolve(&mut self, memo_id: NodeId, resolution: Resolution) -> Option<String> {
+        let idx = self.conflicts.iter().position(|c| c.memo_id == memo_id)?;
+        let conflict = self.conflicts.remove(idx);
+        let title = conflict.remote.title.clone();
+        // 以降の更新はサーバーの最新のバージョンを元にする
+        self.mark_synced(memo_id, conflict.remote.clone());
+        match resolution {
+            Resolution::KeepLocal => {
+                self.enqueue(memo_id, &title, &conflict.local);
+                None
+            }
+            Resolution::KeepRemote => Some(conflict.remote.body),
+            Resolution::Merge => Some(merge_lines(&conflict.local, &conflict.remote.body)),
+        }
+    }
+
+    /// 送った編集を取り除く。送っている間にまた編集していれば残す
+    fn finish(&mut self, sent: &PendingEdit) {
+        self.queue.retain(|edit| edit != sent);
+    }
+
+    fn mark_synced(&mut self, memo_id: NodeId, remote: RemoteMemo) {
+        self.synced.insert(
+            memo_id,
+            SyncedMemo {
+                remote_id: remote.id,
+                version: remote.version,
+                body: remote.body,
+            },
+        );
+    }
+}
+
+/// 編集をサーバーに送る
+pub trait MemoRemote {
+    fn send(&self, request: &SyncRequest) -> StorageFuture<'_, SyncOutcome>;
+}
+
+/// 溜まった編集を順に送る。オフラインになったら残りは次に回す
+/// `state` は送っている間の編集を受け付けられるよう、その都度借りる
+pub async fn replay<R>(remote: &dyn MemoRemote, mut state: impl FnMut() -> R)
+where
+    R: DerefMut<Target = SyncState>,
+{
+    loop {
+        let next = state().next_request();
+        let Some(request) = next else {
+            return;
+        };
+        let outcome = remote.send(&request).await;
+        if !state().apply(&request, outcome) {
+            return;
+        }
+    }
+}
+
+/// 違う行を両方残した本文。前後の同じ行はそのままで、違う部分を印で囲む
+pub fn merge_lines(local: &str, remote: &str) -> String {
+    let local: Vec<&str> = local.lines().collect();
+    let remote: Vec<&str> = remote.lines().collect();
+    let prefix = local
+        .iter()
+        .zip(&remote)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = local[prefix..]
+        .iter()
+        .rev()
+        .zip(remote[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut merged: Vec<&str> = local[..prefix].to_vec();
+    if prefix + suffix < local.len().max(remote.len()) {
+        merged.push("<<<<<<<\u{00A0}local");
+        merged.extend(&local[prefix..local.len() - suffix]);
+        merged.push("=======");
+        merged.extend(&remote[prefix..remote.len() - suffix]);
+        merged.push(">>>>>>>\u{00A0}server");
+    }
+    merged.extend(&local[local.len() - suffix..]);
+    merged.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::block_on;
+    use std::cell::RefCell;
+
+    fn remote(id: &str, body: &str, version: i64) -> RemoteMemo {
+        RemoteMemo {
+            id: id.to_string(),
+            title: "memo1".to_string(),
+            body: body.to_string(),
+            version,
+        }
+    }
+
+    /// テスト用: 決まった応答を順に返すサーバー
+    struct FakeRemote {
+        outcomes: RefCell<Vec<SyncOutcome>>,
+        sent: RefCell<Vec<SyncRequest>>,
+    }
+
+    impl FakeRemote {
+        fn new(outcomes: Vec<SyncOutcome>) -> FakeRemote {
+            FakeRemote {
+                outcomes: RefCell::new(outcomes),
+                sent: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl MemoRemote for FakeRemote {
+        fn send(&self, request: &SyncRequest) -> StorageFuture<'_, SyncOutcome> {
+            self.sent.borrow_mut().push(request.clone());
+            let outcome = self.outcomes.borrow_mut().remove(0);
+            Box::pin(async move { outcome })
+        }
+    }
+
+    #[test]
+    fn replays_queue_until_offline() {
+        let state = RefCell::new(SyncState::default());
+        state.borrow_mut().enqueue(1, "memo1", "a");
+        state.borrow_mut().enqueue(2, "memo2", "b");
+        state.borrow_mut().enqueue(1, "memo1", "ab");
+        assert_eq!(state.borrow().pending(), 2);
+
+        // memo2 を送る前にオフラインになる
+        let fake = FakeRemote::new(vec![
+            SyncOutcome::Saved(remote("r2", "b", 1)),
+            SyncOutcome::Offline,
+        ]);
+        block_on(replay(&fake, || state.borrow_mut()));
+        assert_eq!(fake.sent.borrow()[0].edit.body, "b");
+        assert_eq!(fake.sent.borrow()[1].edit.body, "ab");
+        assert_eq!(state.borrow().pending(), 1);
+
+        // オンラインに戻ったら、作ったメモはそのバージョンを元に更新する
+        let fake = FakeRemote::new(vec![SyncOutcome::Saved(remote("r1", "ab", 1))]);
+        block_on(replay(&fake, || state.borrow_mut()));
+        assert_eq!(state.borrow().pending(), 0);
+        state.borrow_mut().enqueue(2, "memo2", "bc");
+        let request = state.borrow().next_request().unwrap();
+        assert_eq!(
+            (request.remote_id.as_deref(), request.version),
+            (Some("r2"), Some(1))
+        );
+        // 同期した本文と同じなら送らない
+        state.borrow_mut().enqueue(1, "memo1", "ab");
+        assert_eq!(state.borrow().pending(), 1);
+    }
+
+    #[test]
+    fn restores_without_dropping_new_edits() {
+        let mut loaded = SyncState::default();
+        loaded.enqueue(1, "memo1", "old");
+        loaded.enqueue(2, "memo2", "b");
+
+        // 読み込む前に編集したメモは新しい本文で送る
+        let mut state = SyncState::default();
+        state.enqueue(1, "memo1", "new");
+        state.enqueue(3, "memo3", "c");
+        state.restore(loaded);
+        assert_eq!(state.pending(), 3);
+        let bodies: Vec<&str> = state.queue.iter().map(|edit| edit.body.as_str()).collect();
+        assert_eq!(bodies, vec!["b", "new", "c"]);
+    }
+
+    #[test]
+    fn keeps_conflicts_until_resolved() {
+        let state = RefCell::new(SyncState::default());
+        state.borrow_mut().enqueue(1, "memo1", "a\nb");
+        let fake = FakeRemote::new(vec![
+            SyncOutcome::Saved(remote("r1", "a\nb", 1)),
+            SyncOutcome::Conflict(remote("r1", "a\nserver\nb", 3)),
+        ]);
+        block_on(replay(&fake, || state.borrow_mut()));
+        state.borrow_mut().enqueue(1, "memo1", "a\nlocal\nb");
+        block_on(replay(&fake, || state.borrow_mut()));
+
+        // 食い違っている間の編集は送らない
+        state.borrow_mut().enqueue(1, "memo1", "a\nlocal!\nb");
+        assert_eq!(state.borrow().pending(), 0);
+        assert_eq!(state.borrow().conflict(1).unwrap().local, "a\nlocal!\nb");
+
+        let mut resolved = state.borrow().clone();
+        assert_eq!(
+            resolved.resolve(1, Resolution::KeepRemote),
+            Some("a\nserver\nb".to_string())
+        );
+        assert_eq!(resolved.pending(), 0);
+
+        let mut resolved = state.borrow().clone();
+        assert_eq!(resolved.resolve(1, Resolution::KeepLocal), None);
+        let request = resolved.next_request().unwrap();
+        assert_eq!(request.edit.body, "a\nlocal!\nb");
+        assert_eq!(request.version, Some(3));
+        assert_eq!(resolved.conflict(1), None);
+    }
+
+    #[test]
+    fn merges_differing_lines() {
+        assert_eq!(
+            merge_lines("a\nlocal\nb", "a\nserver\nb"),
+            "a\n<<<<<<<\u{00A0}local\nlocal\n=======\nserver\n>>>>>>>\u{00A0}server\nb"
+        );
+        assert_eq!(merge_lines("a\nb", "a\nb"), "a\nb");
+        assert_eq!(
+            merge_lines("a", "a\nb"),
+            "a\n<<<<<<<\u{00A0}local\n=======\nb\n>>>>>>>\u{00A0}server"
+        );
+    }
+}
@@ -1,4 +1,16 @@
+pub mod clipboard_text;
+pub mod editor_command;
 pub mod editor_data;
 pub mod editor_state;
 pub mod get_context_default_value_factory;
+pub mod grapheme;
+pub mod history;
+pub mod keymap;
 pub mod line;
+pub mod memo_storage;
+pub mod memo_sync;
+pub mod search;
+pub mod shell;
+pub mod text_buffer;
+pub mod vim;
+pub mod word;
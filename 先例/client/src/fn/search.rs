@@ -0,0 +1,106 @@
+use std::ops::Range;
+
+use regex::{Regex, RegexBuilder};
+
+/// 検索条件
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    /// 大文字・小文字を区別する
+    pub case_sensitive: bool,
+    /// 単語単位で一致させる
+    pub whole_word: bool,
+    /// `pattern` を正規表現として扱う
+    pub regex: bool,
+}
+
+impl SearchQuery {
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_empty()
+    }
+
+    /// 検索に使う正規表現。正規表現モードでない場合は `pattern` をそのまま探す
+    pub fn build(&self) -> Result<Regex, regex::Error> {
+        // 本文の空白はノーブレークスペースで保持しているので合わせる
+        let pattern = self.pattern.replace(' ', "\u{00A0}");
+        let mut pattern = if self.regex {
+            pattern
+        } else {
+            regex::escape(&pattern)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{pattern})\b");
+        }
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive)
+            .multi_line(true)
+            .build()
+    }
+
+    /// `range` (byte) の一致を置き換える文字列
+    /// 正規表現モードでは `$1` などをキャプチャで展開する
+    pub fn expand(
+        &self,
+        re: &Regex,
+        text: &str,
+        range: &Range<usize>,
+        replacement: &str,
+    ) -> String {
+        if !self.regex {
+            return replacement.to_string();
+        }
+        let mut expanded = String::new();
+        if let Some(caps) = re.captures_at(text, range.start) {
+            caps.expand(replacement, &mut expanded);
+        }
+        expanded
+    }
+}
+
+/// `text` 中の一致 (byte範囲)。空文字列への一致は除く
+pub fn find_all(re: &Regex, text: &str) -> Vec<Range<usize>> {
+    re.find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| m.range())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pattern: &str) -> SearchQuery {
+        SearchQuery {
+            pattern: pattern.to_string(),
+            ..SearchQuery::default()
+        }
+    }
+
+    #[test]
+    fn plain_search_escapes_and_ignores_case() {
+        let re = query("a.b").build().unwrap();
+        assert_eq!(find_all(&re, "A.B axb a.b"), vec![0..3, 8..11]);
+    }
+
+    #[test]
+    fn whole_word_and_case_sensitive() {
+        let mut q = query("memo");
+        q.whole_word = true;
+        q.case_sensitive = true;
+        let re = q.build().unwrap();
+        assert_eq!(find_all(&re, "memo memos Memo"), vec![0..4]);
+    }
+
+    #[test]
+    fn regex_replacement_expands_captures() {
+        let mut q = query(r"(\d+)-(\d+)");
+        q.regex = true;
+        let re = q.build().unwrap();
+        let text = "x 10-20";
+        let found = find_all(&re, text);
+        assert_eq!(q.expand(&re, text, &found[0], "$2-$1"), "20-10");
+        assert!(query("(").build().is_ok());
+        q.pattern = "(".to_string();
+        assert!(q.build().is_err());
+    }
+}
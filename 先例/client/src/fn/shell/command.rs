@@ -0,0 +1,287 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+use memo_tree::path::resolve;
+use memo_tree::{MemoTree, MemoTreeError, NodeId};
+
+use crate::r#fn::shell::history::ShellHistory;
+
+/// コマンドを実行するセッションの状態
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellContext {
+    /// カレントディレクトリ
+    pub pwd: String,
+    pub tree: MemoTree,
+    /// 実行したコマンド
+    pub history: ShellHistory,
+}
+
+impl Default for ShellContext {
+    fn default() -> ShellContext {
+        ShellContext {
+            pwd: "/".to_string(),
+            tree: MemoTree::default(),
+            history: ShellHistory::default(),
+        }
+    }
+}
+
+impl ShellContext {
+    /// カレントディレクトリからの相対パスを絶対パスにする
+    pub fn resolve(&self, path: &str) -> String {
+        resolve(&self.pwd, path)
+    }
+
+    /// 消したり移動したりしてカレントディレクトリがなくなった場合はルートに戻る
+    pub fn ensure_pwd(&mut self) {
+        if !self.tree.is_folder(&self.pwd) {
+            self.pwd = "/".to_string();
+        }
+    }
+
+    /// パスの指すメモのmemo_id
+    pub fn find_memo(&self, path: &str) -> Result<NodeId, MemoTreeError> {
+        self.tree.memo_id(&self.resolve(path))
+    }
+}
+
+/// コマンドの結果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellOutput {
+    /// 何も表示しない
+    Empty,
+    /// 出力するブロック (上から順に表示する)
+    Blocks(Vec<OutputBlock>),
+    /// メモを開く
+    Open(NodeId),
+    /// コピーしたメモ (新しいid, コピー元のid)。本文は画面側でコピーする
+    Copied(Vec<(NodeId, NodeId)>),
+    /// それまでの出力を消す
+    Clear,
+}
+
+impl ShellOutput {
+    /// テキストの行だけの出力
+    pub fn lines(lines: Vec<String>) -> ShellOutput {
+        ShellOutput::Blocks(vec![OutputBlock::Text(lines)])
+    }
+}
+
+/// 出力の1ブロック。HTMLではなく構造のまま渡し、表示は画面側で決める
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputBlock {
+    /// そのまま表示する行
+    Text(Vec<String>),
+    /// 列をそろえて表示する表 (行ごとのセル)
+    Table(Vec<Vec<String>>),
+    /// メモへのリンク。選ぶとメモを開く
+    Links(Vec<MemoLink>),
+    /// エラーのメッセージ
+    Error(String),
+}
+
+/// 出力に表示するメモへのリンク
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoLink {
+    pub memo_id: NodeId,
+    /// 表示する文字列
+    pub label: String,
+    /// 開いた時にカーソルを置く行 (1から)
+    pub line: Option<usize>,
+}
+
+/// コマンドラインのエラー。1行のメッセージとして表示する
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShellError {
+    /// 引用符が閉じていない
+    UnterminatedQuote(char),
+    /// 行末が `\` で終わっている
+    TrailingEscape,
+    UnknownCommand(String),
+    /// `!n` の履歴がない
+    EventNotFound(String),
+    /// 受け付けないフラグ
+    UnknownOption {
+        command: &'static str,
+        option: char,
+    },
+    /// 引数の数が合わない
+    Usage {
+        command: &'static str,
+        usage: &'static str,
+    },
+    /// 正規表現として読めないパターン
+    InvalidPattern {
+        command: &'static str,
+        pattern: String,
+    },
+    /// メモの階層の操作に失敗した
+    Memo {
+        command: &'static str,
+        error: MemoTreeError,
+    },
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::UnterminatedQuote(quote) => write!(f, "unterminated quote: {quote}"),
+            ShellError::TrailingEscape => write!(f, "unexpected end of line after \\"),
+            ShellError::UnknownCommand(name) => write!(f, "{name}: command not found"),
+            ShellError::EventNotFound(event) => write!(f, "{event}: event not found"),
+            ShellError::UnknownOption { command, option } => {
+                write!(f, "{command}: invalid option -- '{option}'")
+            }
+            ShellError::Usage { command, usage } => write!(f, "usage: {command} {usage}"),
+            ShellError::InvalidPattern { command, pattern } => {
+                write!(f, "{command}: invalid regular expression: {pattern}")
+            }
+            ShellError::Memo { command, error } => write!(f, "{command}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// 受け付ける引数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgsSpec {
+    /// `-r` `-p` のようなフラグの文字
+    pub flags: &'static str,
+    /// フラグを除いた引数の数
+    pub min: usize,
+    /// `None` は上限なし
+    pub max: Option<usize>,
+    /// `usage:` に続けて表示する引数の説明 (例: `[-p] <dir>...`)
+    pub usage: &'static str,
+}
+
+impl ArgsSpec {
+    pub const fn none() -> ArgsSpec {
+        ArgsSpec {
+            flags: "",
+            min: 0,
+            max: Some(0),
+            usage: "",
+        }
+    }
+
+    /// フラグと引数に分けて数を確かめる
+    /// `--` より後ろと `-` だけの引数はフラグとして扱わない
+    pub fn parse(&self, command: &'static str, tokens: &[String]) -> Result<Args, ShellError> {
+        let mut args = Args::default();
+        let mut tokens = tokens.iter();
+        while let Some(token) = tokens.next() {
+            if token == "--" {
+                args.operands.extend(tokens.cloned());
+                break;
+            }
+            match token.strip_prefix('-') {
+                Some(flags) if !flags.is_empty() => {
+                    for option in flags.chars() {
+                        if !self.flags.contains(option) {
+                            return Err(ShellError::UnknownOption { command, option });
+                        }
+                        args.flags.push(option);
+                    }
+                }
+                _ => args.operands.push(token.clone()),
+            }
+        }
+
+        let count = args.operands.len();
+        if count < self.min || self.max.is_some_and(|max| count > max) {
+            return Err(ShellError::Usage {
+                command,
+                usage: self.usage,
+            });
+        }
+        Ok(args)
+    }
+}
+
+/// フラグを分けた引数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Args {
+    pub flags: Vec<char>,
+    pub operands: Vec<String>,
+}
+
+impl Args {
+    pub fn has(&self, flag: char) -> bool {
+        self.flags.contains(&flag)
+    }
+}
+
+pub type CommandResult = Result<ShellOutput, ShellError>;
+
+/// `Command::execute` が返すFuture
+pub type CommandFuture<'a> = Pin<Box<dyn Future<Output = CommandResult> + 'a>>;
+
+/// 引数に取るパスの種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperandKind {
+    /// メモとフォルダ
+    Path,
+    /// フォルダだけ
+    Folder,
+}
+
+/// コマンドラインから実行できるコマンド
+pub trait Command {
+    fn name(&self) -> &'static str;
+
+    fn args(&self) -> ArgsSpec;
+
+    /// 1行の説明
+    fn help(&self) -> &'static str;
+
+    /// Tabで補完する引数の種類
+    fn operand_kind(&self) -> OperandKind {
+        OperandKind::Path
+    }
+
+    /// `args` は `args()` で確認済みの引数
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a>;
+}
+
+/// `execute` で使う: 階層の操作のエラーをコマンドのエラーにする
+pub fn memo_error(command: &'static str) -> impl Fn(MemoTreeError) -> ShellError {
+    move |error| ShellError::Memo { command, error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_flags_and_operands() {
+        let spec = ArgsSpec {
+            flags: "rp",
+            min: 1,
+            max: None,
+            usage: "[-rp] <path>...",
+        };
+        let args = spec
+            .parse("rm", &tokens(&["-rp", "a", "--", "-b", "-"]))
+            .unwrap();
+        assert_eq!(args.flags, vec!['r', 'p']);
+        assert_eq!(args.operands, tokens(&["a", "-b", "-"]));
+        assert!(args.has('r'));
+
+        assert_eq!(
+            spec.parse("rm", &tokens(&["-f", "a"])),
+            Err(ShellError::UnknownOption {
+                command: "rm",
+                option: 'f'
+            })
+        );
+        let usage = spec.parse("rm", &tokens(&["-r"])).unwrap_err();
+        assert_eq!(usage.to_string(), "usage: rm [-rp] <path>...");
+    }
+}
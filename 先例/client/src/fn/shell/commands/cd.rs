@@ -0,0 +1,73 @@
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, OperandKind, ShellContext, ShellOutput,
+};
+
+/// カレントディレクトリを移動する。引数がなければルートに戻る
+pub struct Cd;
+
+impl Command for Cd {
+    fn name(&self) -> &'static str {
+        "cd"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "",
+            min: 0,
+            max: Some(1),
+            usage: "[dir]",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "ディレクトリを移動"
+    }
+
+    fn operand_kind(&self) -> OperandKind {
+        OperandKind::Folder
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let target = args.operands.first().map(String::as_str).unwrap_or("/");
+            let path = context.resolve(target);
+            // ディレクトリがあるか確かめる
+            context
+                .tree
+                .folder_id(&path)
+                .map_err(memo_error(self.name()))?;
+            context.pwd = path;
+            Ok(ShellOutput::Empty)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn changes_working_directory() {
+        let mut context = sample_context();
+        assert_eq!(
+            run_command(&Cd, &["notes"], &mut context),
+            Ok(ShellOutput::Empty)
+        );
+        assert_eq!(context.pwd, "/notes");
+
+        run_command(&Cd, &[], &mut context).unwrap();
+        assert_eq!(context.pwd, "/");
+    }
+
+    #[test]
+    fn rejects_missing_directories() {
+        let mut context = sample_context();
+        let err = run_command(&Cd, &["memo1"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "cd: /memo1: not a directory");
+        let err = run_command(&Cd, &["missing/dir"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "cd: /missing: no such file or directory");
+        assert_eq!(context.pwd, "/");
+    }
+}
@@ -0,0 +1,24 @@
+use crate::r#fn::shell::command::{
+    Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// それまでのコマンドと出力を消す
+pub struct Clear;
+
+impl Command for Clear {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec::none()
+    }
+
+    fn help(&self) -> &'static str {
+        "画面の出力を消す"
+    }
+
+    fn execute<'a>(&'a self, _args: &'a Args, _context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move { Ok(ShellOutput::Clear) })
+    }
+}
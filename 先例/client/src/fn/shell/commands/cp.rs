@@ -0,0 +1,68 @@
+use memo_tree::MemoTreeError;
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// メモをコピーする。`-r` でディレクトリもコピーする
+pub struct Cp;
+
+impl Command for Cp {
+    fn name(&self) -> &'static str {
+        "cp"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "r",
+            min: 2,
+            max: Some(2),
+            usage: "[-r] <source> <dest>",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモをコピー"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let from = context.resolve(&args.operands[0]);
+            let to = context.resolve(&args.operands[1]);
+            let is_folder = context
+                .tree
+                .node_at(&from)
+                .map_err(memo_error(self.name()))?
+                .is_none_or(|node| node.is_folder());
+            if is_folder && !args.has('r') {
+                return Err(memo_error(self.name())(MemoTreeError::IsADirectory(from)));
+            }
+            context
+                .tree
+                .copy_path(&from, &to)
+                .map(ShellOutput::Copied)
+                .map_err(memo_error(self.name()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn copies_memos_and_directories() {
+        let mut context = sample_context();
+        let output = run_command(&Cp, &["memo1", "copy"], &mut context).unwrap();
+        assert_eq!(context.tree.memo_id("/copy"), Ok(5));
+        assert_eq!(output, ShellOutput::Copied(vec![(5, 1)]));
+
+        let err = run_command(&Cp, &["notes", "backup"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "cp: /notes: is a directory");
+        run_command(&Cp, &["-r", "notes", "backup"], &mut context).unwrap();
+        assert_eq!(context.tree.memo_id("/backup/memo3"), Ok(7));
+        assert_eq!(context.tree.memo_id("/notes/memo3"), Ok(4));
+    }
+}
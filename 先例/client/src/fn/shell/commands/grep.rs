@@ -0,0 +1,236 @@
+use std::rc::Rc;
+
+use memo_tree::NodeId;
+use regex::{Regex, RegexBuilder};
+
+use crate::r#fn::memo_storage::MemoStorage;
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, MemoLink, OutputBlock, ShellContext,
+    ShellError, ShellOutput,
+};
+
+/// メモの本文をパターン (正規表現) で検索し、一致した行をリンクで表示する
+/// パスを省略するとカレントディレクトリのメモを検索する
+/// `-i` で大文字小文字を区別せず、`-r` でフォルダの下も検索し、`-n` で行番号を表示する
+/// 本文は `storage` に保存したものを読む
+pub struct Grep {
+    storage: Rc<dyn MemoStorage>,
+}
+
+impl Grep {
+    pub fn new(storage: Rc<dyn MemoStorage>) -> Grep {
+        Grep { storage }
+    }
+}
+
+impl Command for Grep {
+    fn name(&self) -> &'static str {
+        "grep"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "inr",
+            min: 1,
+            max: None,
+            usage: "[-inr] <pattern> [path]...",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモの本文を検索"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let error = memo_error(self.name());
+            let pattern = &args.operands[0];
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(args.has('i'))
+                .build()
+                .map_err(|_| ShellError::InvalidPattern {
+                    command: self.name(),
+                    pattern: pattern.clone(),
+                })?;
+
+            let mut paths: Vec<&str> = args.operands[1..].iter().map(String::as_str).collect();
+            if paths.is_empty() {
+                paths.push(".");
+            }
+
+            // 検索するメモ (表示するパス, memo_id)
+            let mut memos = Vec::new();
+            for path in paths {
+                match context
+                    .tree
+                    .node_at(&context.resolve(path))
+                    .map_err(&error)?
+                {
+                    Some(node) if !node.is_folder() => memos.push((path.to_string(), node.id)),
+                    node => {
+                        let prefix = if path == "." {
+                            String::new()
+                        } else {
+                            format!("{}/", path.trim_end_matches('/'))
+                        };
+                        collect(
+                            context,
+                            node.map(|node| node.id),
+                            &prefix,
+                            args.has('r'),
+                            &mut memos,
+                        );
+                    }
+                }
+            }
+
+            let mut links = Vec::new();
+            for (path, memo_id) in memos {
+                let Some(snapshot) = self.storage.load(memo_id).await else {
+                    continue;
+                };
+                links.extend(matched_lines(
+                    &regex,
+                    &path,
+                    memo_id,
+                    &snapshot.text,
+                    args.has('n'),
+                ));
+            }
+            if links.is_empty() {
+                return Ok(ShellOutput::Empty);
+            }
+            Ok(ShellOutput::Blocks(vec![OutputBlock::Links(links)]))
+        })
+    }
+}
+
+/// フォルダの中のメモを名前順に集める。`recursive` ならフォルダの下もたどる
+fn collect(
+    context: &ShellContext,
+    folder_id: Option<NodeId>,
+    prefix: &str,
+    recursive: bool,
+    memos: &mut Vec<(String, NodeId)>,
+) {
+    let mut children = context.tree.children(folder_id);
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    for node in children {
+        let path = format!("{prefix}{}", node.name);
+        if !node.is_folder() {
+            memos.push((path, node.id));
+        } else if recursive {
+            collect(
+                context,
+                Some(node.id),
+                &format!("{path}/"),
+                recursive,
+                memos,
+            );
+        }
+    }
+}
+
+/// 一致した行のリンク (`path:line:text`、`-n` なしは `path:text`)
+/// 本文のスペースはNBSPで保存しているので、スペースに戻してから比べる
+fn matched_lines(
+    regex: &Regex,
+    path: &str,
+    memo_id: NodeId,
+    text: &str,
+    number: bool,
+) -> Vec<MemoLink> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = line.replace('\u{00A0}', " ");
+            if !regex.is_match(&line) {
+                return None;
+            }
+            let label = if number {
+                format!("{path}:{}:{line}", idx + 1)
+            } else {
+                format!("{path}:{line}")
+            };
+            Some(MemoLink {
+                memo_id,
+                label,
+                line: Some(idx + 1),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::memo_storage::InMemoryStorage;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    fn grep() -> Grep {
+        let storage = InMemoryStorage::default();
+        storage.insert(1, "Hello\u{00A0}world\nTODO: write");
+        storage.insert(2, "nothing here");
+        storage.insert(4, "todo in notes\nhello again");
+        Grep::new(Rc::new(storage))
+    }
+
+    fn labels(result: ShellOutput) -> Vec<String> {
+        match result {
+            ShellOutput::Blocks(blocks) => match &blocks[..] {
+                [OutputBlock::Links(links)] => {
+                    links.iter().map(|link| link.label.clone()).collect()
+                }
+                blocks => panic!("unexpected blocks: {blocks:?}"),
+            },
+            ShellOutput::Empty => Vec::new(),
+            output => panic!("unexpected output: {output:?}"),
+        }
+    }
+
+    #[test]
+    fn searches_current_folder() {
+        let mut context = sample_context();
+        let result = run_command(&grep(), &["-n", "hello world"], &mut context).unwrap();
+        assert!(labels(result).is_empty());
+
+        let result = run_command(&grep(), &["-in", "hello world"], &mut context).unwrap();
+        assert_eq!(labels(result), vec!["memo1:1:Hello world"]);
+    }
+
+    #[test]
+    fn searches_recursively_with_regex() {
+        let mut context = sample_context();
+        let result = run_command(&grep(), &["-ri", "^todo"], &mut context).unwrap();
+        assert_eq!(
+            labels(result),
+            vec!["memo1:TODO: write", "notes/memo3:todo in notes"]
+        );
+
+        let result = run_command(&grep(), &["-n", "again", "notes"], &mut context).unwrap();
+        assert_eq!(labels(result), vec!["notes/memo3:2:hello again"]);
+
+        context.pwd = "/notes".to_string();
+        let Ok(ShellOutput::Blocks(blocks)) = run_command(&grep(), &["again"], &mut context) else {
+            panic!("no match");
+        };
+        assert_eq!(
+            blocks,
+            vec![OutputBlock::Links(vec![MemoLink {
+                memo_id: 4,
+                label: "memo3:hello again".to_string(),
+                line: Some(2),
+            }])]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_pattern() {
+        let mut context = sample_context();
+        let err = run_command(&grep(), &["(todo"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "grep: invalid regular expression: (todo");
+        let err = run_command(&grep(), &["todo", "missing"], &mut context).unwrap_err();
+        assert!(matches!(err, ShellError::Memo { .. }));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::r#fn::shell::command::{
+    Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellError, ShellOutput,
+};
+
+/// 実行したコマンドを番号付きで表示する (`!n` で実行し直せる)
+/// `n` を渡すと新しい方から `n` 件だけ、`-c` で履歴を消す
+pub struct History;
+
+impl Command for History {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "c",
+            min: 0,
+            max: Some(1),
+            usage: "[-c] [n]",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "コマンドの履歴を表示"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            if args.has('c') {
+                context.history.clear();
+                return Ok(ShellOutput::Empty);
+            }
+            let entries = context.history.entries();
+            let count = match args.operands.first() {
+                Some(count) => count.parse::<usize>().map_err(|_| ShellError::Usage {
+                    command: self.name(),
+                    usage: self.args().usage,
+                })?,
+                None => entries.len(),
+            };
+            let skip = entries.len().saturating_sub(count);
+            let lines = entries
+                .iter()
+                .enumerate()
+                .skip(skip)
+                .map(|(idx, entry)| format!("{:>5}  {entry}", idx + 1))
+                .collect();
+            Ok(ShellOutput::lines(lines))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn prints_numbered_entries() {
+        let mut context = sample_context();
+        for line in ["ls", "cd notes", "history"] {
+            context.history.push(line);
+        }
+        assert_eq!(
+            run_command(&History, &["2"], &mut context),
+            Ok(ShellOutput::lines(vec![
+                "    2  cd notes".to_string(),
+                "    3  history".to_string(),
+            ]))
+        );
+        assert!(run_command(&History, &["x"], &mut context).is_err());
+
+        run_command(&History, &["-c"], &mut context).unwrap();
+        assert!(context.history.is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::Local;
+use memo_tree::{MemoTreeError, Node};
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, OutputBlock, ShellContext, ShellOutput,
+};
+use crate::r#fn::shell::glob::{expand, is_glob};
+
+/// メモを一覧表示する。ディレクトリは末尾に `/` を付ける
+/// `-l` でサイズと更新日時、`-a` で `.` から始まる名前も表示する
+pub struct Ls;
+
+impl Command for Ls {
+    fn name(&self) -> &'static str {
+        "ls"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "la",
+            min: 0,
+            max: None,
+            usage: "[-la] [path]...",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモの一覧を表示"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let error = memo_error(self.name());
+            let long = args.has('l');
+
+            // globを展開したパス (入力した書き方のまま)
+            let mut paths = Vec::new();
+            for operand in &args.operands {
+                if !is_glob(operand) {
+                    paths.push(operand.clone());
+                    continue;
+                }
+                let matched = expand(&context.tree, &context.pwd, operand);
+                if matched.is_empty() {
+                    return Err(error(MemoTreeError::NotFound(operand.clone())));
+                }
+                paths.extend(matched);
+            }
+            if paths.is_empty() {
+                paths.push(".".to_string());
+            }
+
+            // メモを先に、ディレクトリの中身を後に表示する
+            let mut files = Vec::new();
+            let mut dirs = Vec::new();
+            for path in &paths {
+                let node = context
+                    .tree
+                    .node_at(&context.resolve(path))
+                    .map_err(&error)?;
+                match node {
+                    Some(node) if !node.is_folder() => files.push((path.clone(), node)),
+                    _ => dirs.push(path),
+                }
+            }
+
+            let mut blocks = Vec::new();
+            if !files.is_empty() {
+                blocks.push(entries(&files, long));
+            }
+            for path in &dirs {
+                let mut children: Vec<(String, &Node)> = context
+                    .tree
+                    .list(&context.resolve(path))
+                    .map_err(&error)?
+                    .into_iter()
+                    .filter(|node| args.has('a') || !node.name.starts_with('.'))
+                    .map(|node| (node.name.clone(), node))
+                    .collect();
+                children.sort_by(|a, b| a.0.cmp(&b.0));
+
+                if paths.len() > 1 {
+                    // 2つ目以降は空行で区切る
+                    let mut header = Vec::new();
+                    if !blocks.is_empty() {
+                        header.push(String::new());
+                    }
+                    header.push(format!("{path}:"));
+                    blocks.push(OutputBlock::Text(header));
+                }
+                blocks.push(entries(&children, long));
+            }
+            Ok(ShellOutput::Blocks(blocks))
+        })
+    }
+}
+
+/// 1件1行。`long` は `-l` の形式の表 (種類, サイズ, 更新日時, 名前)
+fn entries(nodes: &[(String, &Node)], long: bool) -> OutputBlock {
+    let name = |name: &String, node: &Node| {
+        if node.is_folder() {
+            format!("{name}/")
+        } else {
+            name.clone()
+        }
+    };
+    if !long {
+        return OutputBlock::Text(nodes.iter().map(|(n, node)| name(n, node)).collect());
+    }
+    let rows = nodes
+        .iter()
+        .map(|(n, node)| {
+            let kind = if node.is_folder() { "d" } else { "-" };
+            let modified = node
+                .updated_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M");
+            vec![
+                kind.to_string(),
+                node.size.to_string(),
+                modified.to_string(),
+                name(n, node),
+            ]
+        })
+        .collect();
+    OutputBlock::Table(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+    use memo_tree::NodeKind;
+
+    /// 出力を行にする。表は列を空白でつなぐ
+    fn lines(output: Result<ShellOutput, crate::r#fn::shell::command::ShellError>) -> Vec<String> {
+        let Ok(ShellOutput::Blocks(blocks)) = output else {
+            panic!("unexpected output: {output:?}");
+        };
+        blocks
+            .into_iter()
+            .flat_map(|block| match block {
+                OutputBlock::Text(lines) => lines,
+                OutputBlock::Table(rows) => rows.into_iter().map(|row| row.join(" ")).collect(),
+                other => panic!("unexpected block: {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lists_working_directory() {
+        let mut context = sample_context();
+        context.tree.create("/.hidden", NodeKind::Memo).unwrap();
+        assert_eq!(
+            lines(run_command(&Ls, &[], &mut context)),
+            vec!["memo1", "memo2", "notes/"]
+        );
+        assert_eq!(
+            lines(run_command(&Ls, &["-a"], &mut context)),
+            vec![".hidden", "memo1", "memo2", "notes/"]
+        );
+        context.pwd = "/notes".to_string();
+        assert_eq!(lines(run_command(&Ls, &[], &mut context)), vec!["memo3"]);
+    }
+
+    #[test]
+    fn lists_paths_and_globs() {
+        let mut context = sample_context();
+        assert_eq!(
+            lines(run_command(&Ls, &["memo*", "notes"], &mut context)),
+            vec!["memo1", "memo2", "", "notes:", "memo3"]
+        );
+        assert_eq!(
+            lines(run_command(&Ls, &["notes/*3"], &mut context)),
+            vec!["notes/memo3"]
+        );
+        let err = run_command(&Ls, &["*.md"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "ls: *.md: no such file or directory");
+        let err = run_command(&Ls, &["missing"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "ls: /missing: no such file or directory");
+    }
+
+    #[test]
+    fn long_format_shows_size_and_date() {
+        let mut context = sample_context();
+        context.tree.update_memo(1, 1200).unwrap();
+        let output = lines(run_command(&Ls, &["-l"], &mut context));
+        assert_eq!(output.len(), 3);
+        assert!(output[0].starts_with("- 1200 "));
+        assert!(output[0].ends_with(" memo1"));
+        assert!(output[1].starts_with("- 0 "));
+        assert!(output[2].starts_with("d 0 "));
+        assert!(output[2].ends_with(" notes/"));
+    }
+}
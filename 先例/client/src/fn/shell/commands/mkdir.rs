@@ -0,0 +1,65 @@
+use memo_tree::NodeKind;
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// ディレクトリを作る。`-p` で途中のディレクトリも作る
+pub struct Mkdir;
+
+impl Command for Mkdir {
+    fn name(&self) -> &'static str {
+        "mkdir"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "p",
+            min: 1,
+            max: None,
+            usage: "[-p] <dir>...",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "ディレクトリを作成"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            for dir in &args.operands {
+                let path = context.resolve(dir);
+                let result = if args.has('p') {
+                    context.tree.create_folder_all(&path).map(|_| ())
+                } else {
+                    context.tree.create(&path, NodeKind::Folder).map(|_| ())
+                };
+                result.map_err(memo_error(self.name()))?;
+            }
+            Ok(ShellOutput::Empty)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn creates_directories() {
+        let mut context = sample_context();
+        run_command(&Mkdir, &["a", "notes/b"], &mut context).unwrap();
+        assert!(context.tree.is_folder("/a"));
+        assert!(context.tree.is_folder("/notes/b"));
+
+        let err = run_command(&Mkdir, &["x/y"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "mkdir: /x: no such file or directory");
+        run_command(&Mkdir, &["-p", "x/y"], &mut context).unwrap();
+        assert!(context.tree.is_folder("/x/y"));
+
+        let err = run_command(&Mkdir, &["memo1"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "mkdir: /memo1: file exists");
+    }
+}
@@ -0,0 +1,31 @@
+pub mod cd;
+pub mod clear;
+pub mod cp;
+pub mod grep;
+pub mod history;
+pub mod ls;
+pub mod mkdir;
+pub mod mv;
+pub mod pwd;
+pub mod rm;
+pub mod touch;
+pub mod tree;
+pub mod vim;
+
+/// テスト用のセッション
+/// `/memo1` `/memo2` `/notes/memo3` (idは順に1〜4)
+#[cfg(test)]
+pub(crate) fn sample_context() -> crate::r#fn::shell::command::ShellContext {
+    use memo_tree::NodeKind;
+
+    let mut context = crate::r#fn::shell::command::ShellContext::default();
+    for (path, kind) in [
+        ("/memo1", NodeKind::Memo),
+        ("/memo2", NodeKind::Memo),
+        ("/notes", NodeKind::Folder),
+        ("/notes/memo3", NodeKind::Memo),
+    ] {
+        context.tree.create(path, kind).unwrap();
+    }
+    context
+}
@@ -0,0 +1,61 @@
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// メモやディレクトリの名前を変える・移動する
+pub struct Mv;
+
+impl Command for Mv {
+    fn name(&self) -> &'static str {
+        "mv"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "",
+            min: 2,
+            max: Some(2),
+            usage: "<source> <dest>",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモの移動・名前の変更"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let from = context.resolve(&args.operands[0]);
+            let to = context.resolve(&args.operands[1]);
+            context
+                .tree
+                .move_path(&from, &to)
+                .map_err(memo_error(self.name()))?;
+            // カレントディレクトリを移動した場合に備える
+            context.ensure_pwd();
+            Ok(ShellOutput::Empty)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn renames_and_moves() {
+        let mut context = sample_context();
+        run_command(&Mv, &["memo1", "renamed"], &mut context).unwrap();
+        assert_eq!(context.tree.memo_id("/renamed"), Ok(1));
+        run_command(&Mv, &["renamed", "notes"], &mut context).unwrap();
+        assert_eq!(context.tree.memo_id("/notes/renamed"), Ok(1));
+
+        let err = run_command(&Mv, &["memo2", "notes/memo3"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "mv: /notes/memo3: file exists");
+        let err = run_command(&Mv, &["missing", "x"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "mv: /missing: no such file or directory");
+        assert!(run_command(&Mv, &["memo2"], &mut context).is_err());
+    }
+}
@@ -0,0 +1,42 @@
+use crate::r#fn::shell::command::{
+    Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// カレントディレクトリを表示する
+pub struct Pwd;
+
+impl Command for Pwd {
+    fn name(&self) -> &'static str {
+        "pwd"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec::none()
+    }
+
+    fn help(&self) -> &'static str {
+        "現在のディレクトリを表示"
+    }
+
+    fn execute<'a>(&'a self, _args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move { Ok(ShellOutput::lines(vec![context.pwd.clone()])) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn prints_working_directory() {
+        let mut context = sample_context();
+        context.pwd = "/notes".to_string();
+        assert_eq!(
+            run_command(&Pwd, &[], &mut context),
+            Ok(ShellOutput::lines(vec!["/notes".to_string()]))
+        );
+        assert!(run_command(&Pwd, &["x"], &mut context).is_err());
+    }
+}
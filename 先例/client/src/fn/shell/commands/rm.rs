@@ -0,0 +1,85 @@
+use memo_tree::{MemoTree, MemoTreeError};
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// メモを消す。`-r` でディレクトリも消し、`-f` でないパスを無視する
+pub struct Rm;
+
+impl Command for Rm {
+    fn name(&self) -> &'static str {
+        "rm"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "rf",
+            min: 1,
+            max: None,
+            usage: "[-rf] <path>...",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモを削除"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let result = args.operands.iter().try_for_each(|path| {
+                let path = context.resolve(path);
+                match remove(&mut context.tree, &path, args.has('r')) {
+                    Err(MemoTreeError::NotFound(_)) if args.has('f') => Ok(()),
+                    result => result,
+                }
+            });
+            // カレントディレクトリを消した場合に備える
+            context.ensure_pwd();
+            result.map_err(memo_error(self.name()))?;
+            Ok(ShellOutput::Empty)
+        })
+    }
+}
+
+/// フォルダは `recursive` の場合だけ消す
+fn remove(tree: &mut MemoTree, path: &str, recursive: bool) -> Result<(), MemoTreeError> {
+    let node = tree
+        .node_at(path)?
+        .ok_or_else(|| MemoTreeError::InvalidPath(path.to_string()))?;
+    if node.is_folder() && !recursive {
+        return Err(MemoTreeError::IsADirectory(path.to_string()));
+    }
+    tree.remove(node.id).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn removes_memos_and_directories() {
+        let mut context = sample_context();
+        run_command(&Rm, &["memo1"], &mut context).unwrap();
+        assert!(!context.tree.exists("/memo1"));
+
+        let err = run_command(&Rm, &["notes"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "rm: /notes: is a directory");
+
+        context.pwd = "/notes".to_string();
+        run_command(&Rm, &["-r", "/notes"], &mut context).unwrap();
+        assert!(!context.tree.exists("/notes"));
+        assert_eq!(context.pwd, "/");
+    }
+
+    #[test]
+    fn force_ignores_missing_paths() {
+        let mut context = sample_context();
+        let err = run_command(&Rm, &["missing"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "rm: /missing: no such file or directory");
+        run_command(&Rm, &["-f", "missing", "memo2"], &mut context).unwrap();
+        assert!(!context.tree.exists("/memo2"));
+    }
+}
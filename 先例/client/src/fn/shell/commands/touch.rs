@@ -0,0 +1,64 @@
+use memo_tree::NodeKind;
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// 空のメモを作る。既にある場合は何もしない
+pub struct Touch;
+
+impl Command for Touch {
+    fn name(&self) -> &'static str {
+        "touch"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "",
+            min: 1,
+            max: None,
+            usage: "<memo>...",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモを作成"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            for memo in &args.operands {
+                let path = context.resolve(memo);
+                if !context.tree.exists(&path) {
+                    context
+                        .tree
+                        .create(&path, NodeKind::Memo)
+                        .map_err(memo_error(self.name()))?;
+                }
+            }
+            Ok(ShellOutput::Empty)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn creates_memos() {
+        let mut context = sample_context();
+        context.pwd = "/notes".to_string();
+        run_command(&Touch, &["memo4", "/memo1"], &mut context).unwrap();
+        assert_eq!(context.tree.memo_id("/notes/memo4"), Ok(5));
+        assert_eq!(context.tree.memo_id("/memo1"), Ok(1));
+
+        let err = run_command(&Touch, &["missing/memo"], &mut context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "touch: /notes/missing: no such file or directory"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use memo_tree::{MemoTree, NodeId};
+
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, OperandKind, ShellContext, ShellOutput,
+};
+
+/// メモの階層を罫線で表示する。`-a` で `.` から始まる名前も表示する
+pub struct Tree;
+
+impl Command for Tree {
+    fn name(&self) -> &'static str {
+        "tree"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "a",
+            min: 0,
+            max: Some(1),
+            usage: "[-a] [dir]",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモの階層を表示"
+    }
+
+    fn operand_kind(&self) -> OperandKind {
+        OperandKind::Folder
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            let path = args.operands.first().map(String::as_str).unwrap_or(".");
+            let folder_id = context
+                .tree
+                .folder_id(&context.resolve(path))
+                .map_err(memo_error(self.name()))?;
+
+            let mut lines = vec![path.to_string()];
+            let mut counts = (0, 0);
+            draw(
+                &context.tree,
+                folder_id,
+                "",
+                args.has('a'),
+                &mut lines,
+                &mut counts,
+            );
+            let (dirs, files) = counts;
+            lines.push(String::new());
+            lines.push(format!(
+                "{dirs} {}, {files} {}",
+                if dirs == 1 {
+                    "directory"
+                } else {
+                    "directories"
+                },
+                if files == 1 { "file" } else { "files" },
+            ));
+            Ok(ShellOutput::lines(lines))
+        })
+    }
+}
+
+/// フォルダの中身を1件1行で追加する。`counts` は (ディレクトリ数, メモ数)
+fn draw(
+    tree: &MemoTree,
+    folder_id: Option<NodeId>,
+    prefix: &str,
+    all: bool,
+    lines: &mut Vec<String>,
+    counts: &mut (usize, usize),
+) {
+    let mut children = tree.children(folder_id);
+    children.retain(|node| all || !node.name.starts_with('.'));
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let count = children.len();
+    for (idx, node) in children.into_iter().enumerate() {
+        let last = idx + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        if node.is_folder() {
+            counts.0 += 1;
+            lines.push(format!("{prefix}{branch}{}/", node.name));
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            draw(tree, Some(node.id), &prefix, all, lines, counts);
+        } else {
+            counts.1 += 1;
+            lines.push(format!("{prefix}{branch}{}", node.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+    use memo_tree::NodeKind;
+
+    #[test]
+    fn draws_hierarchy() {
+        let mut context = sample_context();
+        context
+            .tree
+            .create("/notes/daily", NodeKind::Folder)
+            .unwrap();
+        context
+            .tree
+            .create("/notes/daily/today", NodeKind::Memo)
+            .unwrap();
+        assert_eq!(
+            run_command(&Tree, &[], &mut context),
+            Ok(ShellOutput::lines(
+                [
+                    ".",
+                    "├── memo1",
+                    "├── memo2",
+                    "└── notes/",
+                    "    ├── daily/",
+                    "    │   └── today",
+                    "    └── memo3",
+                    "",
+                    "2 directories, 4 files",
+                ]
+                .map(String::from)
+                .to_vec()
+            ))
+        );
+
+        let err = run_command(&Tree, &["memo1"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "tree: /memo1: not a directory");
+    }
+}
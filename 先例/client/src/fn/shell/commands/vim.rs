@@ -0,0 +1,63 @@
+use crate::r#fn::shell::command::{
+    memo_error, Args, ArgsSpec, Command, CommandFuture, ShellContext, ShellOutput,
+};
+
+/// メモを開く
+pub struct Vim;
+
+impl Command for Vim {
+    fn name(&self) -> &'static str {
+        "vim"
+    }
+
+    fn args(&self) -> ArgsSpec {
+        ArgsSpec {
+            flags: "",
+            min: 1,
+            max: Some(1),
+            usage: "<memo>",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        "メモを開く"
+    }
+
+    fn execute<'a>(&'a self, args: &'a Args, context: &'a mut ShellContext) -> CommandFuture<'a> {
+        Box::pin(async move {
+            context
+                .find_memo(&args.operands[0])
+                .map(ShellOutput::Open)
+                .map_err(memo_error(self.name()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use crate::r#fn::shell::run_command;
+
+    #[test]
+    fn opens_memo_by_path() {
+        let mut context = sample_context();
+        assert_eq!(
+            run_command(&Vim, &["memo2"], &mut context),
+            Ok(ShellOutput::Open(2))
+        );
+        context.pwd = "/notes".to_string();
+        assert_eq!(
+            run_command(&Vim, &["../notes/memo3"], &mut context),
+            Ok(ShellOutput::Open(4))
+        );
+
+        let err = run_command(&Vim, &["memo9"], &mut context).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "vim: /notes/memo9: no such file or directory"
+        );
+        let err = run_command(&Vim, &["/notes"], &mut context).unwrap_err();
+        assert_eq!(err.to_string(), "vim: /notes: is a directory");
+    }
+}
@@ -0,0 +1,283 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::r#fn::shell::command::{OperandKind, ShellContext};
+use crate::r#fn::shell::registry::CommandRegistry;
+use crate::r#fn::shell::tokenizer::{is_separator, tokenize};
+
+/// カーソルの前の単語と、置き換える候補
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// 入力済みの単語 (引用符やエスケープもそのまま)
+    pub word: String,
+    /// 単語を置き換える文字列。確定した候補には区切りの空白を付ける
+    pub candidates: Vec<String>,
+}
+
+impl Completion {
+    /// 置き換える単語の書記素数
+    pub fn word_len(&self) -> usize {
+        self.word.graphemes(true).count()
+    }
+
+    /// すべての候補に共通する先頭部分
+    pub fn common_prefix(&self) -> String {
+        common_prefix(&self.candidates)
+    }
+}
+
+/// 候補が複数ある場合の一覧。続けてTabを押すと順に切り替える
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompletionMenu {
+    pub candidates: Vec<String>,
+    /// 選んでいる候補。最初のTabでは共通部分だけを入力する
+    pub active: Option<usize>,
+    /// カーソルの前に補完で入力した書記素数
+    pub inserted: usize,
+    /// 補完した後のカーソル位置 (行, 列)。動いたら一覧を閉じる
+    pub cursor: (usize, usize),
+}
+
+impl CompletionMenu {
+    /// 次の候補を選ぶ
+    pub fn select_next(&mut self) -> &str {
+        let next = self
+            .active
+            .map_or(0, |active| (active + 1) % self.candidates.len());
+        self.active = Some(next);
+        &self.candidates[next]
+    }
+}
+
+/// 一覧の表示名 (区切りの空白と引用符を除く)
+pub fn candidate_label(candidate: &str) -> &str {
+    candidate.trim_end().trim_matches(['\'', '"'])
+}
+
+/// すべての候補に共通する先頭部分
+pub fn common_prefix(candidates: &[String]) -> String {
+    let Some((first, rest)) = candidates.split_first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for candidate in rest {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((idx, a), b)| *idx < len && a == b)
+            .map(|((idx, a), _)| idx + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+    first[..len].to_string()
+}
+
+/// カーソルより前のテキストから補完候補を探す
+///
+/// 先頭の単語がコマンド (の一部) でない行は `None` (普通の文章として扱う)
+/// 1語目はコマンド名、2語目以降は `pwd` からのメモとフォルダのパスを補完する
+pub fn complete(
+    registry: &CommandRegistry,
+    context: &ShellContext,
+    before_cursor: &str,
+) -> Option<Completion> {
+    let line = before_cursor.replace('\u{00A0}', " ");
+    let (head, word) = split_word(&line);
+    let completion = |candidates| {
+        Some(Completion {
+            word: word.to_string(),
+            candidates,
+        })
+    };
+
+    let Some(name) = head.split(is_separator).find(|word| !word.is_empty()) else {
+        if word.is_empty() {
+            return None;
+        }
+        let names: Vec<String> = registry
+            .commands()
+            .map(|command| command.name())
+            .filter(|name| name.starts_with(word))
+            .map(|name| format!("{name} "))
+            .collect();
+        return if names.is_empty() {
+            None
+        } else {
+            completion(names)
+        };
+    };
+
+    let command = registry.get(name)?;
+    if command.args().max == Some(0) || word.starts_with('-') {
+        return completion(Vec::new());
+    }
+    completion(complete_path(context, word, command.operand_kind()))
+}
+
+/// 引用符の外の最後の区切りで分ける (前, 入力中の単語)
+fn split_word(line: &str) -> (&str, &str) {
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (None | Some('"'), '\\') => escaped = true,
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if is_separator(c) => start = idx + c.len_utf8(),
+            _ => (),
+        }
+    }
+    line.split_at(start)
+}
+
+fn complete_path(context: &ShellContext, word: &str, kind: OperandKind) -> Vec<String> {
+    // 引用符を開いたままの単語は、閉じずに続きを補完する
+    let quote = word
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '\'' | '"'))
+        .filter(|q| !word[1..].contains(*q));
+    let typed = match quote {
+        Some(_) => word[1..].to_string(),
+        None => match tokenize(word) {
+            Ok(tokens) => tokens.into_iter().next().unwrap_or_default(),
+            Err(_) => return Vec::new(),
+        },
+    };
+    let (dir, base) = typed.split_at(typed.rfind('/').map_or(0, |idx| idx + 1));
+    let folder = context.resolve(if dir.is_empty() { "." } else { dir });
+    let Ok(children) = context.tree.list(&folder) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<(&str, bool)> = children
+        .iter()
+        .filter(|node| kind == OperandKind::Path || node.is_folder())
+        .map(|node| (node.name.as_str(), node.is_folder()))
+        .filter(|(name, _)| name.starts_with(base))
+        .filter(|(name, _)| !name.starts_with('.') || base.starts_with('.'))
+        .collect();
+    names.sort_unstable();
+    names
+        .into_iter()
+        .map(|(name, is_folder)| {
+            let path = format!("{dir}{name}");
+            match (quote, is_folder) {
+                (Some(q), true) => format!("{q}{path}/"),
+                (Some(q), false) => format!("{q}{path}{q} "),
+                (None, true) => format!("{}/", escape(&path)),
+                (None, false) => format!("{} ", escape(&path)),
+            }
+        })
+        .collect()
+}
+
+/// 引用符なしで1つの引数になるようにエスケープする
+fn escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if is_separator(c) || matches!(c, '\\' | '\'' | '"') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::commands::sample_context;
+    use memo_tree::NodeKind;
+
+    fn candidates(context: &ShellContext, line: &str) -> Option<Vec<String>> {
+        complete(&CommandRegistry::default(), context, line).map(|c| c.candidates)
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let context = sample_context();
+        assert_eq!(candidates(&context, "tr"), Some(vec!["tree ".to_string()]));
+        assert_eq!(
+            candidates(&context, "\u{00A0}m"),
+            Some(vec!["mkdir ".to_string(), "mv ".to_string()])
+        );
+        // 普通の文章はTabでインデントする
+        assert_eq!(candidates(&context, "hello"), None);
+        assert_eq!(candidates(&context, ""), None);
+        assert_eq!(candidates(&context, "hello wor"), None);
+    }
+
+    #[test]
+    fn completes_paths_from_pwd() {
+        let mut context = sample_context();
+        assert_eq!(
+            candidates(&context, "vim me"),
+            Some(vec!["memo1 ".to_string(), "memo2 ".to_string()])
+        );
+        assert_eq!(
+            candidates(&context, "vim\u{00A0}notes/m"),
+            Some(vec!["notes/memo3 ".to_string()])
+        );
+        assert_eq!(
+            candidates(&context, "cd "),
+            Some(vec!["notes/".to_string()])
+        );
+        context.pwd = "/notes".to_string();
+        assert_eq!(
+            candidates(&context, "cat ../n"),
+            None,
+            "登録していないコマンドは補完しない"
+        );
+        assert_eq!(
+            candidates(&context, "ls ../n"),
+            Some(vec!["../notes/".to_string()])
+        );
+        assert_eq!(candidates(&context, "pwd "), Some(Vec::new()));
+    }
+
+    #[test]
+    fn keeps_quotes_and_escapes() {
+        let mut context = sample_context();
+        context.tree.create("/my memo", NodeKind::Memo).unwrap();
+        context.tree.create("/.hidden", NodeKind::Memo).unwrap();
+        assert_eq!(
+            candidates(&context, "vim my"),
+            Some(vec![r"my\ memo ".to_string()])
+        );
+        assert_eq!(
+            candidates(&context, "vim my\\\u{00A0}m"),
+            Some(vec![r"my\ memo ".to_string()])
+        );
+        assert_eq!(
+            candidates(&context, "vim 'my m"),
+            Some(vec!["'my memo' ".to_string()])
+        );
+        assert_eq!(
+            candidates(&context, "vim ."),
+            Some(vec![".hidden ".to_string()])
+        );
+    }
+
+    #[test]
+    fn cycles_through_menu() {
+        let completion = complete(&CommandRegistry::default(), &sample_context(), "vim m").unwrap();
+        assert_eq!(completion.word_len(), 1);
+        assert_eq!(completion.common_prefix(), "memo");
+
+        let mut menu = CompletionMenu {
+            candidates: completion.candidates,
+            active: None,
+            inserted: 4,
+            cursor: (1, 9),
+        };
+        assert_eq!(menu.select_next(), "memo1 ");
+        assert_eq!(menu.select_next(), "memo2 ");
+        assert_eq!(menu.select_next(), "memo1 ");
+        assert_eq!(candidate_label("'my memo' "), "my memo");
+    }
+}
@@ -0,0 +1,146 @@
+use memo_tree::path::resolve;
+use memo_tree::MemoTree;
+
+/// `*` `?` `[...]` を含むか
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// 名前1つとの一致
+/// `*` は任意の文字列、`?` は任意の1文字、`[abc]` `[a-z]` `[!a]` は文字クラス
+pub fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches_from(&pattern, &name)
+}
+
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| matches_from(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && matches_from(&pattern[1..], &name[1..]),
+        Some('[') => match name.first() {
+            None => false,
+            Some(c) => match class_matches(&pattern[1..], *c) {
+                Some((matched, rest)) => matched && matches_from(rest, &name[1..]),
+                // 閉じていない `[` は文字として扱う
+                None => *c == '[' && matches_from(&pattern[1..], &name[1..]),
+            },
+        },
+        Some(c) => name.first() == Some(c) && matches_from(&pattern[1..], &name[1..]),
+    }
+}
+
+/// `[` の後ろの文字クラスに `c` が含まれるか。`]` より後ろのパターンも返す
+fn class_matches(pattern: &[char], c: char) -> Option<(bool, &[char])> {
+    let negate = matches!(pattern.first(), Some('!' | '^'));
+    let body = if negate { &pattern[1..] } else { pattern };
+    // 先頭の `]` は文字として扱う
+    let end = body.iter().skip(1).position(|c| *c == ']')? + 1;
+    let items = &body[..end];
+    let mut found = false;
+    let mut idx = 0;
+    while idx < items.len() {
+        if idx + 2 < items.len() && items[idx + 1] == '-' {
+            found |= items[idx] <= c && c <= items[idx + 2];
+            idx += 3;
+        } else {
+            found |= items[idx] == c;
+            idx += 1;
+        }
+    }
+    Some((found != negate, &body[end + 1..]))
+}
+
+/// パターンに一致するパス (入力と同じ書き方で、名前順)
+/// `.` から始まる名前はパターンも `.` から始まる場合だけ一致させる
+pub fn expand(tree: &MemoTree, pwd: &str, pattern: &str) -> Vec<String> {
+    // (表示するパス, 絶対パス)
+    let root = if pattern.starts_with('/') { "/" } else { "" };
+    let mut candidates = vec![(root.to_string(), resolve(pwd, root))];
+
+    for segment in pattern.split('/').filter(|s| !s.is_empty()) {
+        let mut next = Vec::new();
+        for (display, path) in candidates {
+            let join = |name: &str| {
+                if display.is_empty() || display.ends_with('/') {
+                    format!("{display}{name}")
+                } else {
+                    format!("{display}/{name}")
+                }
+            };
+            if !is_glob(segment) {
+                let path = resolve(&path, segment);
+                if tree.exists(&path) {
+                    next.push((join(segment), path));
+                }
+                continue;
+            }
+            let Ok(children) = tree.list(&path) else {
+                continue;
+            };
+            let mut names: Vec<&str> = children
+                .iter()
+                .map(|node| node.name.as_str())
+                .filter(|name| !name.starts_with('.') || segment.starts_with('.'))
+                .filter(|name| matches(segment, name))
+                .collect();
+            names.sort_unstable();
+            next.extend(
+                names
+                    .into_iter()
+                    .map(|name| (join(name), resolve(&path, name))),
+            );
+        }
+        candidates = next;
+    }
+
+    candidates
+        .into_iter()
+        .map(|(display, _)| display)
+        .filter(|display| !display.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use memo_tree::NodeKind;
+
+    #[test]
+    fn matches_wildcards_and_classes() {
+        assert!(matches("*.md", "memo.md"));
+        assert!(!matches("*.md", "memo.txt"));
+        assert!(matches("memo?", "memo1"));
+        assert!(!matches("memo?", "memo"));
+        assert!(matches("memo[1-3]", "memo2"));
+        assert!(!matches("memo[!1-3]", "memo2"));
+        assert!(matches("a*b*c", "aXXbYc"));
+        assert!(matches("[", "["));
+    }
+
+    #[test]
+    fn expands_against_tree() {
+        let mut tree = MemoTree::new();
+        for (path, kind) in [
+            ("/notes", NodeKind::Folder),
+            ("/notes/b.md", NodeKind::Memo),
+            ("/notes/a.md", NodeKind::Memo),
+            ("/notes/.draft.md", NodeKind::Memo),
+            ("/notes/c.txt", NodeKind::Memo),
+            ("/memo1", NodeKind::Memo),
+        ] {
+            tree.create(path, kind).unwrap();
+        }
+        assert_eq!(
+            expand(&tree, "/", "notes/*.md"),
+            vec!["notes/a.md", "notes/b.md"]
+        );
+        assert_eq!(
+            expand(&tree, "/notes", "../*"),
+            vec!["../memo1", "../notes"]
+        );
+        assert_eq!(expand(&tree, "/", "/*/.*"), vec!["/notes/.draft.md"]);
+        assert!(expand(&tree, "/", "*.md").is_empty());
+    }
+}
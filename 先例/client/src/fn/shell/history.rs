@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+
+use crate::r#fn::shell::command::ShellError;
+use crate::r#fn::shell::tokenizer::is_separator;
+
+/// 残す履歴の上限。古いものから消す
+pub const MAX_ENTRIES: usize = 1000;
+
+/// 実行したコマンドの履歴 (古い順)
+/// `history` と `!n` の番号は1から数える
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ShellHistory {
+    entries: Vec<String>,
+}
+
+impl ShellHistory {
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 番号 (1から) の履歴
+    pub fn get(&self, number: usize) -> Option<&str> {
+        let idx = number.checked_sub(1)?;
+        self.entries.get(idx).map(String::as_str)
+    }
+
+    /// 実行した行を追加する。空の行と直前と同じ行は追加しない
+    pub fn push(&mut self, line: &str) {
+        let line = line.replace('\u{00A0}', " ");
+        let line = line.trim();
+        if line.is_empty() || self.entries.last().is_some_and(|last| last == line) {
+            return;
+        }
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            let overflow = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..overflow);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 先頭の単語が `!n` `!-n` `!!` なら履歴の行に置き換える
+    pub fn expand(&self, line: &str) -> Result<String, ShellError> {
+        let start = line.len() - line.trim_start_matches(is_separator).len();
+        let end = line[start..]
+            .find(is_separator)
+            .map_or(line.len(), |idx| start + idx);
+        let word = &line[start..end];
+        if !is_reference(word) {
+            return Ok(line.to_string());
+        }
+
+        let number = match &word[1..] {
+            "!" => Some(self.len()),
+            number => match number.strip_prefix('-') {
+                Some(back) => back
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|back| (self.len() + 1).checked_sub(back)),
+                None => number.parse().ok(),
+            },
+        };
+        let entry = number
+            .and_then(|number| self.get(number))
+            .ok_or_else(|| ShellError::EventNotFound(word.to_string()))?;
+        Ok(format!("{entry}{}", &line[end..]))
+    }
+
+    /// `before` より前で `query` を含む最も新しい履歴の位置 (0から)
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.len())]
+            .iter()
+            .rposition(|entry| entry.contains(query))
+    }
+}
+
+/// `!n` `!-n` `!!` の形か
+pub fn is_reference(word: &str) -> bool {
+    match word.strip_prefix('!') {
+        Some("!") => true,
+        Some(rest) => {
+            let digits = rest.strip_prefix('-').unwrap_or(rest);
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// 上下キーで履歴をたどっている状態
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recall {
+    /// 表示している履歴の位置。`None` は入力中の行
+    index: Option<usize>,
+    /// たどり始める前に入力していた行
+    draft: String,
+}
+
+impl Recall {
+    pub fn is_active(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// 1つ古い履歴。たどり始めた時の行は `draft` として残す
+    pub fn older(&mut self, history: &ShellHistory, current: &str) -> Option<String> {
+        let index = match self.index {
+            Some(0) => return None,
+            Some(index) => index - 1,
+            None => {
+                let index = history.len().checked_sub(1)?;
+                self.draft = current.to_string();
+                index
+            }
+        };
+        self.index = Some(index);
+        Some(history.entries[index].clone())
+    }
+
+    /// 1つ新しい履歴。最新より後は入力していた行に戻る
+    pub fn newer(&mut self, history: &ShellHistory) -> Option<String> {
+        let index = self.index?;
+        if index + 1 < history.len() {
+            self.index = Some(index + 1);
+            Some(history.entries[index + 1].clone())
+        } else {
+            self.index = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Recall::default();
+    }
+}
+
+/// Ctrl+R の逆方向インクリメンタル検索
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReverseSearch {
+    pub query: String,
+    /// 一致した履歴の位置
+    pub matched: Option<usize>,
+}
+
+impl ReverseSearch {
+    /// 検索語に文字を足す。今の一致も含めて古い方へ探し直す
+    pub fn input(&mut self, history: &ShellHistory, text: &str) {
+        self.query.push_str(text);
+        let before = self.matched.map_or(history.len(), |matched| matched + 1);
+        self.matched = history.search(&self.query, before);
+    }
+
+    /// 検索語を1文字消し、最新から探し直す
+    pub fn backspace(&mut self, history: &ShellHistory) {
+        self.query.pop();
+        self.matched = history.search(&self.query, history.len());
+    }
+
+    /// 続けて Ctrl+R を押した場合: さらに古い一致へ進む。なければそのまま
+    pub fn older(&mut self, history: &ShellHistory) {
+        let before = self.matched.unwrap_or(history.len());
+        if let Some(matched) = history.search(&self.query, before) {
+            self.matched = Some(matched);
+        }
+    }
+
+    pub fn matched_line<'a>(&self, history: &'a ShellHistory) -> Option<&'a str> {
+        self.matched
+            .and_then(|matched| history.entries.get(matched))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(lines: &[&str]) -> ShellHistory {
+        let mut history = ShellHistory::default();
+        for line in lines {
+            history.push(line);
+        }
+        history
+    }
+
+    #[test]
+    fn push_skips_blank_and_repeated_lines() {
+        let history = history(&["ls", "ls", "\u{00A0}", "cd\u{00A0}notes ", "ls"]);
+        assert_eq!(history.entries(), ["ls", "cd notes", "ls"]);
+        assert_eq!(history.get(2), Some("cd notes"));
+        assert_eq!(history.get(0), None);
+    }
+
+    #[test]
+    fn expands_references() {
+        let history = history(&["ls", "cd notes", "vim memo3"]);
+        assert_eq!(history.expand("!2").unwrap(), "cd notes");
+        assert_eq!(history.expand("!!").unwrap(), "vim memo3");
+        assert_eq!(history.expand("!-3 -l").unwrap(), "ls -l");
+        assert_eq!(history.expand("ls !2").unwrap(), "ls !2");
+        assert_eq!(
+            history.expand("!9"),
+            Err(ShellError::EventNotFound("!9".to_string()))
+        );
+        assert!(is_reference("!12"));
+        assert!(!is_reference("!important"));
+    }
+
+    #[test]
+    fn recalls_older_and_newer() {
+        let history = history(&["ls", "pwd"]);
+        let mut recall = Recall::default();
+        assert_eq!(recall.older(&history, "cd"), Some("pwd".to_string()));
+        assert_eq!(recall.older(&history, "pwd"), Some("ls".to_string()));
+        assert_eq!(recall.older(&history, "ls"), None);
+        assert_eq!(recall.newer(&history), Some("pwd".to_string()));
+        assert_eq!(recall.newer(&history), Some("cd".to_string()));
+        assert!(!recall.is_active());
+        assert_eq!(recall.newer(&history), None);
+    }
+
+    #[test]
+    fn reverse_search_is_incremental() {
+        let history = history(&["vim memo1", "ls notes", "vim memo2"]);
+        let mut search = ReverseSearch::default();
+        search.input(&history, "v");
+        assert_eq!(search.matched_line(&history), Some("vim memo2"));
+        search.input(&history, "im memo1");
+        assert_eq!(search.matched_line(&history), Some("vim memo1"));
+        search.backspace(&history);
+        assert_eq!(search.matched_line(&history), Some("vim memo2"));
+        search.older(&history);
+        assert_eq!(search.matched_line(&history), Some("vim memo1"));
+        search.older(&history);
+        assert_eq!(search.matched_line(&history), Some("vim memo1"));
+    }
+}
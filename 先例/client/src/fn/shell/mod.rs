@@ -0,0 +1,38 @@
+//! HomeEditorのコマンドライン
+//!
+//! 行のテキストを `tokenizer` で引数に分け、`registry` に登録したコマンドを実行する
+
+pub mod command;
+pub mod commands;
+pub mod completion;
+pub mod glob;
+pub mod history;
+pub mod registry;
+pub mod terminal;
+pub mod tokenizer;
+
+/// テスト用: 引数を確認してコマンドを実行する
+#[cfg(test)]
+pub(crate) fn run_command(
+    command: &dyn command::Command,
+    tokens: &[&str],
+    context: &mut command::ShellContext,
+) -> command::CommandResult {
+    let tokens: Vec<String> = tokens.iter().map(|token| token.to_string()).collect();
+    let args = command.args().parse(command.name(), &tokens)?;
+    block_on(command.execute(&args, context))
+}
+
+/// テスト用: すぐに完了するFutureを待つ
+#[cfg(test)]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, Waker};
+
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
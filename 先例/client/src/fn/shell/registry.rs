@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::r#fn::shell::command::{Command, CommandResult, ShellContext, ShellError, ShellOutput};
+use crate::r#fn::shell::commands::{
+    cd::Cd, clear::Clear, cp::Cp, history::History, ls::Ls, mkdir::Mkdir, mv::Mv, pwd::Pwd, rm::Rm,
+    touch::Touch, tree::Tree, vim::Vim,
+};
+use crate::r#fn::shell::history::is_reference;
+use crate::r#fn::shell::tokenizer::{is_separator, tokenize};
+
+/// コマンド名からコマンドを引く
+pub struct CommandRegistry {
+    commands: BTreeMap<&'static str, Box<dyn Command>>,
+}
+
+impl Default for CommandRegistry {
+    /// 組み込みのコマンドを登録したもの
+    fn default() -> CommandRegistry {
+        let mut registry = CommandRegistry::new();
+        registry.register(Ls);
+        registry.register(Cd);
+        registry.register(Pwd);
+        registry.register(Vim);
+        registry.register(Mkdir);
+        registry.register(Touch);
+        registry.register(Rm);
+        registry.register(Mv);
+        registry.register(Cp);
+        registry.register(Tree);
+        registry.register(History);
+        registry.register(Clear);
+        registry
+    }
+}
+
+impl CommandRegistry {
+    /// 何も登録していないもの
+    pub fn new() -> CommandRegistry {
+        CommandRegistry {
+            commands: BTreeMap::new(),
+        }
+    }
+
+    /// 同じ名前のコマンドは置き換える
+    pub fn register(&mut self, command: impl Command + 'static) {
+        self.commands.insert(command.name(), Box::new(command));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Command> {
+        self.commands.get(name).map(|command| command.as_ref())
+    }
+
+    /// 名前順のコマンド
+    pub fn commands(&self) -> impl Iterator<Item = &dyn Command> {
+        self.commands.values().map(|command| command.as_ref())
+    }
+
+    /// 行の先頭の単語が登録したコマンド (または `!n` の履歴) か
+    /// 普通の文章の行はコマンドとして扱わない
+    pub fn is_command(&self, line: &str) -> bool {
+        line.split(is_separator)
+            .find(|word| !word.is_empty())
+            .is_some_and(|word| self.commands.contains_key(word) || is_reference(word))
+    }
+
+    /// 行を引数に分けてコマンドを実行する
+    /// `!n` を履歴の行に置き換えてから、実行する行を履歴に追加する
+    pub async fn run(&self, line: &str, context: &mut ShellContext) -> CommandResult {
+        let line = context.history.expand(line)?;
+        context.history.push(&line);
+        let tokens = tokenize(&line)?;
+        let Some((name, args)) = tokens.split_first() else {
+            return Ok(ShellOutput::Empty);
+        };
+        let Some(command) = self.get(name) else {
+            return Err(ShellError::UnknownCommand(name.clone()));
+        };
+        let args = command.args().parse(command.name(), args)?;
+        command.execute(&args, context).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::block_on;
+    use memo_tree::NodeKind;
+
+    fn context() -> ShellContext {
+        let mut context = ShellContext::default();
+        context.tree.create("/memo1", NodeKind::Memo).unwrap();
+        context.tree.create("/my memo", NodeKind::Memo).unwrap();
+        context
+    }
+
+    #[test]
+    fn recognizes_command_lines() {
+        let registry = CommandRegistry::default();
+        assert!(registry.is_command("ls"));
+        assert!(registry.is_command("\u{00A0}cd\u{00A0}.."));
+        assert!(!registry.is_command("lsd"));
+        assert!(!registry.is_command("hello world"));
+        assert!(!registry.is_command(""));
+        assert!(registry.is_command("!2"));
+        let names: Vec<_> = registry.commands().map(|c| c.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "cd", "clear", "cp", "history", "ls", "mkdir", "mv", "pwd", "rm", "touch", "tree",
+                "vim"
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_with_quoted_arguments() {
+        let registry = CommandRegistry::default();
+        let mut context = context();
+        assert_eq!(
+            block_on(registry.run("vim\u{00A0}'my memo'", &mut context)),
+            Ok(ShellOutput::Open(2))
+        );
+        assert_eq!(
+            block_on(registry.run("  ", &mut context)),
+            Ok(ShellOutput::Empty)
+        );
+    }
+
+    #[test]
+    fn records_and_expands_history() {
+        let registry = CommandRegistry::default();
+        let mut context = context();
+        block_on(registry.run("cd /", &mut context)).unwrap();
+        assert_eq!(
+            block_on(registry.run("!1", &mut context)),
+            Ok(ShellOutput::Empty)
+        );
+        assert_eq!(
+            block_on(registry.run("!5", &mut context)),
+            Err(ShellError::EventNotFound("!5".to_string()))
+        );
+        // 置き換えた行を記録する
+        block_on(registry.run("vim memo1", &mut context)).unwrap();
+        assert_eq!(context.history.entries(), ["cd /", "vim memo1"]);
+    }
+
+    #[test]
+    fn reports_errors() {
+        let registry = CommandRegistry::default();
+        let mut context = context();
+        assert_eq!(
+            block_on(registry.run("sl x", &mut context)),
+            Err(ShellError::UnknownCommand("sl".to_string()))
+        );
+        let usage = block_on(registry.run("vim", &mut context)).unwrap_err();
+        assert_eq!(usage.to_string(), "usage: vim <memo>");
+        assert_eq!(
+            block_on(registry.run("vim 'memo", &mut context)),
+            Err(ShellError::UnterminatedQuote('\''))
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use crate::r#fn::shell::command::{CommandResult, OutputBlock, ShellOutput};
+
+/// 残すコマンドの上限。古いものから消す
+pub const MAX_SCROLLBACK: usize = 500;
+
+/// 実行したコマンド1件と、その出力
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalEntry {
+    /// 実行した時のプロンプト (`~/notes $`)
+    pub prompt: String,
+    pub command: String,
+    pub output: Vec<OutputBlock>,
+}
+
+/// HomeEditorの端末の画面 (編集できない出力の履歴)
+/// 入力はプロンプトの1行だけで、実行したコマンドと出力をここに積む
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Terminal {
+    entries: Vec<TerminalEntry>,
+}
+
+impl Terminal {
+    pub fn entries(&self) -> &[TerminalEntry] {
+        &self.entries
+    }
+
+    /// コマンドの結果を追加する。`clear` の場合はそれまでの出力を消す
+    pub fn push(&mut self, prompt: String, command: String, result: CommandResult) {
+        let output = match result {
+            Ok(ShellOutput::Clear) => {
+                self.clear();
+                return;
+            }
+            Ok(ShellOutput::Blocks(blocks)) => blocks,
+            Ok(ShellOutput::Empty | ShellOutput::Open(_) | ShellOutput::Copied(_)) => Vec::new(),
+            Err(err) => vec![OutputBlock::Error(err.to_string())],
+        };
+        self.entries.push(TerminalEntry {
+            prompt,
+            command,
+            output,
+        });
+        if self.entries.len() > MAX_SCROLLBACK {
+            let overflow = self.entries.len() - MAX_SCROLLBACK;
+            self.entries.drain(..overflow);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// カレントディレクトリを表示するプロンプト。ルートは `~` で表す
+pub fn prompt(pwd: &str) -> String {
+    let path = pwd.trim_end_matches('/');
+    if path.is_empty() {
+        "~ $".to_string()
+    } else {
+        format!("~{path} $")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::shell::command::ShellError;
+
+    #[test]
+    fn prompt_shows_working_directory() {
+        assert_eq!(prompt("/"), "~ $");
+        assert_eq!(prompt("/notes/daily"), "~/notes/daily $");
+    }
+
+    #[test]
+    fn pushes_output_and_clears() {
+        let mut terminal = Terminal::default();
+        terminal.push(
+            prompt("/"),
+            "pwd".to_string(),
+            Ok(ShellOutput::lines(vec!["/".to_string()])),
+        );
+        terminal.push(
+            prompt("/"),
+            "sl".to_string(),
+            Err(ShellError::UnknownCommand("sl".to_string())),
+        );
+        assert_eq!(terminal.entries().len(), 2);
+        assert_eq!(
+            terminal.entries()[1].output,
+            vec![OutputBlock::Error("sl: command not found".to_string())]
+        );
+
+        terminal.push(prompt("/"), "clear".to_string(), Ok(ShellOutput::Clear));
+        assert!(terminal.entries().is_empty());
+    }
+}
@@ -0,0 +1,117 @@
+use crate::r#fn::shell::command::ShellError;
+
+/// 区切りになる空白。本文の空白はノーブレークスペースで保持している
+pub fn is_separator(c: char) -> bool {
+    c.is_whitespace() || c == '\u{00A0}'
+}
+
+/// コマンドラインを引数に分ける
+///
+/// - 空白 (ノーブレークスペースを含む) で区切る。連続した空白は1つとして扱う
+/// - 引用符やエスケープで残したノーブレークスペースは普通の空白にする
+/// - `'...'` の中はそのまま
+/// - `"..."` の中では `\"` と `\\` だけをエスケープとして扱う
+/// - 引用符の外の `\` は次の1文字をそのまま使う
+pub fn tokenize(line: &str) -> Result<Vec<String>, ShellError> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    // 引用符だけの引数 (`""`) も1つの引数にする
+    let mut in_token = false;
+    let line = line.replace('\u{00A0}', " ");
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if is_separator(c) => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(ShellError::UnterminatedQuote('\'')),
+                    }
+                }
+            }
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(ShellError::UnterminatedQuote('"')),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(ShellError::UnterminatedQuote('"')),
+                    }
+                }
+            }
+            '\\' => {
+                in_token = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => return Err(ShellError::TrailingEscape),
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(line: &str) -> Vec<String> {
+        tokenize(line).unwrap()
+    }
+
+    #[test]
+    fn splits_on_spaces_and_nbsp() {
+        assert_eq!(
+            tokens("cd\u{00A0}\u{00A0}dir  sub "),
+            vec!["cd", "dir", "sub"]
+        );
+        assert!(tokens("\u{00A0} ").is_empty());
+    }
+
+    #[test]
+    fn quotes_and_escapes() {
+        assert_eq!(tokens(r#"vim 'my memo'"#), vec!["vim", "my memo"]);
+        assert_eq!(tokens(r#"vim "a \"b\" \n""#), vec!["vim", r#"a "b" \n"#]);
+        assert_eq!(tokens(r"vim my\ memo"), vec!["vim", "my memo"]);
+        assert_eq!(tokens("vim my\\\u{00A0}memo"), vec!["vim", "my memo"]);
+        assert_eq!(tokens(r#"cd "" x"#), vec!["cd", "", "x"]);
+        assert_eq!(tokens(r#"a'b'"c""#), vec!["abc"]);
+    }
+
+    #[test]
+    fn reports_unterminated_input() {
+        assert_eq!(
+            tokenize("vim 'memo"),
+            Err(ShellError::UnterminatedQuote('\''))
+        );
+        assert_eq!(
+            tokenize("vim \"memo"),
+            Err(ShellError::UnterminatedQuote('"'))
+        );
+        assert_eq!(tokenize("vim memo\\"), Err(ShellError::TrailingEscape));
+    }
+}
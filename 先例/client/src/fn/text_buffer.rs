@@ -0,0 +1,592 @@
+use std::ops::Range;
+
+use crate::r#fn::grapheme;
+use crate::types::enums::Glyph;
+
+/// 1ノードが保持するGlyphの最大数
+const MAX_CHUNK: usize = 64;
+
+/// 部分木の集計値
+/// 各種オフセット(glyph / char / byte)と改行数をまとめて持つことで、
+/// 位置の変換をO(log n)で行う
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub glyphs: usize,
+    pub chars: usize,
+    pub bytes: usize,
+    pub newlines: usize,
+}
+
+impl Summary {
+    /// HTMLNode, Component は1文字(U+FFFC)として数える
+    /// 改行として扱うのは `Glyph::Char('\n')` のみ
+    pub fn of(glyph: &Glyph) -> Summary {
+        let (chars, bytes, newlines) = match glyph {
+            Glyph::Text(t) => (t.chars().count(), t.len(), 0),
+            Glyph::Char(c) => (1, c.len_utf8(), (*c == '\n') as usize),
+            Glyph::Cursor => (0, 0, 0),
+            Glyph::HTMLNode(_) | Glyph::Component(_) => (1, '\u{FFFC}'.len_utf8(), 0),
+        };
+        Summary {
+            glyphs: 1,
+            chars,
+            bytes,
+            newlines,
+        }
+    }
+
+    fn of_slice(glyphs: &[Glyph]) -> Summary {
+        glyphs
+            .iter()
+            .fold(Summary::default(), |acc, g| acc.add(&Summary::of(g)))
+    }
+
+    fn add(&self, other: &Summary) -> Summary {
+        Summary {
+            glyphs: self.glyphs + other.glyphs,
+            chars: self.chars + other.chars,
+            bytes: self.bytes + other.bytes,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+}
+
+type Link = Option<Box<Node>>;
+
+/// implicit treap のノード
+/// キーは持たず、左部分木のglyph数で位置を決める
+#[derive(Debug, Clone)]
+struct Node {
+    chunk: Vec<Glyph>,
+    priority: u32,
+    summary: Summary,
+    left: Link,
+    right: Link,
+}
+
+impl Node {
+    fn new(chunk: Vec<Glyph>, priority: u32) -> Box<Node> {
+        let summary = Summary::of_slice(&chunk);
+        Box::new(Node {
+            chunk,
+            priority,
+            summary,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.summary = summary(&self.left)
+            .add(&Summary::of_slice(&self.chunk))
+            .add(&summary(&self.right));
+    }
+}
+
+fn summary(link: &Link) -> Summary {
+    link.as_ref().map(|n| n.summary).unwrap_or_default()
+}
+
+/// テキスト本体を保持するロープ
+///
+/// 改行は `Glyph::Char('\n')` として保持する。
+/// カーソルは保持しない(`EditorState` 側で位置のみ管理する)。
+#[derive(Debug, Clone)]
+pub struct TextBuffer {
+    root: Link,
+    seed: u32,
+}
+
+impl Default for TextBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextBuffer {
+    pub fn new() -> TextBuffer {
+        TextBuffer {
+            root: None,
+            seed: 0x2545_F491,
+        }
+    }
+
+    pub fn summary(&self) -> Summary {
+        summary(&self.root)
+    }
+
+    /// Glyph数
+    pub fn len(&self) -> usize {
+        self.summary().glyphs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 行数(改行数 + 1)
+    pub fn len_lines(&self) -> usize {
+        self.summary().newlines + 1
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.summary().chars
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.summary().bytes
+    }
+
+    fn next_priority(&mut self) -> u32 {
+        // xorshift32
+        let mut x = self.seed;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.seed = x;
+        x
+    }
+
+    /// `at` (glyph offset) の位置に挿入する
+    pub fn insert(&mut self, at: usize, glyphs: Vec<Glyph>) {
+        assert!(at <= self.len(), "insert position out of bounds");
+        if glyphs.is_empty() {
+            return;
+        }
+        if glyphs.len() < MAX_CHUNK && insert_in_place(&mut self.root, at, &glyphs) {
+            return;
+        }
+
+        let root = self.root.take();
+        let (left, right) = self.split(root, at);
+        let mut middle: Link = None;
+        let mut glyphs = glyphs;
+        while !glyphs.is_empty() {
+            let rest = glyphs.split_off(glyphs.len().min(MAX_CHUNK));
+            let node = Node::new(glyphs, self.next_priority());
+            middle = merge(middle, Some(node));
+            glyphs = rest;
+        }
+        self.root = merge(merge(left, middle), right);
+    }
+
+    /// `range` (glyph offset) を削除し、削除したGlyphを返す
+    pub fn remove(&mut self, range: Range<usize>) -> Vec<Glyph> {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "remove range out of bounds"
+        );
+        if range.is_empty() {
+            return Vec::new();
+        }
+        if let Some(removed) = remove_in_place(&mut self.root, range.clone()) {
+            return removed;
+        }
+
+        let root = self.root.take();
+        let (rest, right) = self.split(root, range.end);
+        let (left, middle) = self.split(rest, range.start);
+        let mut removed = Vec::with_capacity(range.len());
+        collect(&middle, &mut removed);
+        self.root = merge(left, right);
+        removed
+    }
+
+    fn split(&mut self, link: Link, at: usize) -> (Link, Link) {
+        let mut node = match link {
+            Some(node) => node,
+            None => return (None, None),
+        };
+        let left_len = summary(&node.left).glyphs;
+        let chunk_len = node.chunk.len();
+
+        if at <= left_len {
+            let (l, r) = self.split(node.left.take(), at);
+            node.left = r;
+            node.update();
+            (l, Some(node))
+        } else if at >= left_len + chunk_len {
+            let (l, r) = self.split(node.right.take(), at - left_len - chunk_len);
+            node.right = l;
+            node.update();
+            (Some(node), r)
+        } else {
+            let tail = node.chunk.split_off(at - left_len);
+            let tail = Node::new(tail, self.next_priority());
+            let right = merge(Some(tail), node.right.take());
+            node.update();
+            (Some(node), right)
+        }
+    }
+
+    /// `[range]` のGlyphを複製して返す
+    pub fn slice(&self, range: Range<usize>) -> Vec<Glyph> {
+        let mut out = Vec::with_capacity(range.len());
+        collect_range(&self.root, 0, &range, &mut out);
+        out
+    }
+
+    pub fn get(&self, at: usize) -> Option<&Glyph> {
+        let mut link = &self.root;
+        let mut at = at;
+        while let Some(node) = link {
+            let left_len = summary(&node.left).glyphs;
+            if at < left_len {
+                link = &node.left;
+            } else if at < left_len + node.chunk.len() {
+                return node.chunk.get(at - left_len);
+            } else {
+                at -= left_len + node.chunk.len();
+                link = &node.right;
+            }
+        }
+        None
+    }
+
+    /// 全Glyphを先頭から順に返す
+    pub fn glyphs(&self) -> Vec<Glyph> {
+        let mut out = Vec::with_capacity(self.len());
+        collect(&self.root, &mut out);
+        out
+    }
+
+    /// 本文の文字列。byte位置は `Summary` のbyte offsetと対応する
+    /// (HTMLNode, Component はU+FFFCになる)
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.summary().bytes);
+        for glyph in self.glyphs() {
+            match glyph {
+                Glyph::Text(t) => text.push_str(&t),
+                Glyph::Char(c) => text.push(c),
+                Glyph::Cursor => (),
+                Glyph::HTMLNode(_) | Glyph::Component(_) => text.push('\u{FFFC}'),
+            }
+        }
+        text
+    }
+
+    /// 先頭から `at` (glyph offset) までの集計値
+    pub fn prefix(&self, at: usize) -> Summary {
+        let mut link = &self.root;
+        let mut at = at;
+        let mut acc = Summary::default();
+        while let Some(node) = link {
+            let left = summary(&node.left);
+            if at <= left.glyphs {
+                link = &node.left;
+            } else if at <= left.glyphs + node.chunk.len() {
+                let inner = Summary::of_slice(&node.chunk[..at - left.glyphs]);
+                return acc.add(&left).add(&inner);
+            } else {
+                at -= left.glyphs + node.chunk.len();
+                acc = acc.add(&left).add(&Summary::of_slice(&node.chunk));
+                link = &node.right;
+            }
+        }
+        acc
+    }
+
+    /// `line` (0始まり) の先頭のglyph offset
+    pub fn line_to_offset(&self, line: usize) -> usize {
+        if line == 0 {
+            return 0;
+        }
+        // line 個目の改行の直後
+        match self.seek(line - 1, |s| s.newlines) {
+            Some(at) => at + 1,
+            None => self.len(),
+        }
+    }
+
+    /// `at` (glyph offset) が含まれる行(0始まり)
+    pub fn offset_to_line(&self, at: usize) -> usize {
+        self.prefix(at).newlines
+    }
+
+    /// char offset を glyph offset に変換する
+    /// 複数文字の `Glyph::Text` の途中を指す場合はそのGlyphの位置を返す
+    pub fn char_to_offset(&self, char_idx: usize) -> usize {
+        self.seek(char_idx, |s| s.chars).unwrap_or(self.len())
+    }
+
+    /// byte offset を glyph offset に変換する
+    pub fn byte_to_offset(&self, byte_idx: usize) -> usize {
+        self.seek(byte_idx, |s| s.bytes).unwrap_or(self.len())
+    }
+
+    pub fn offset_to_char(&self, at: usize) -> usize {
+        self.prefix(at).chars
+    }
+
+    pub fn offset_to_byte(&self, at: usize) -> usize {
+        self.prefix(at).bytes
+    }
+
+    /// `measure` の累積値が `target` を超える最初のGlyphの位置
+    fn seek(&self, target: usize, measure: fn(&Summary) -> usize) -> Option<usize> {
+        let mut link = &self.root;
+        let mut target = target;
+        let mut offset = 0;
+        while let Some(node) = link {
+            let left = summary(&node.left);
+            if target < measure(&left) {
+                link = &node.left;
+                continue;
+            }
+            target -= measure(&left);
+            offset += left.glyphs;
+            for glyph in &node.chunk {
+                let m = measure(&Summary::of(glyph));
+                if target < m {
+                    return Some(offset);
+                }
+                target -= m;
+                offset += 1;
+            }
+            link = &node.right;
+        }
+        None
+    }
+}
+
+fn merge(a: Link, b: Link) -> Link {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority >= b.priority {
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+/// 挿入先のチャンクに空きがあればその場で挿入する
+fn insert_in_place(link: &mut Link, at: usize, glyphs: &[Glyph]) -> bool {
+    let node = match link {
+        Some(node) => node,
+        None => return false,
+    };
+    let left_len = summary(&node.left).glyphs;
+    let chunk_len = node.chunk.len();
+
+    let inserted = if at < left_len {
+        insert_in_place(&mut node.left, at, glyphs)
+    } else if at > left_len + chunk_len {
+        insert_in_place(&mut node.right, at - left_len - chunk_len, glyphs)
+    } else if chunk_len + glyphs.len() <= MAX_CHUNK {
+        let at = at - left_len;
+        node.chunk.splice(at..at, glyphs.iter().cloned());
+        true
+    } else {
+        false
+    };
+
+    if inserted {
+        node.update();
+    }
+    inserted
+}
+
+/// 削除範囲が1つのチャンクに収まる場合はその場で削除する
+fn remove_in_place(link: &mut Link, range: Range<usize>) -> Option<Vec<Glyph>> {
+    let node = link.as_mut()?;
+    let left_len = summary(&node.left).glyphs;
+    let chunk_len = node.chunk.len();
+
+    let removed = if range.end <= left_len {
+        remove_in_place(&mut node.left, range)
+    } else if range.start >= left_len + chunk_len {
+        let offset = left_len + chunk_len;
+        remove_in_place(&mut node.right, range.start - offset..range.end - offset)
+    } else if range.start >= left_len && range.end <= left_len + chunk_len {
+        let removed = node
+            .chunk
+            .drain(range.start - left_len..range.end - left_len)
+            .collect();
+        Some(removed)
+    } else {
+        None
+    };
+
+    if removed.is_some() {
+        if node.chunk.is_empty() {
+            let (left, right) = (node.left.take(), node.right.take());
+            *link = merge(left, right);
+        } else {
+            node.update();
+        }
+    }
+    removed
+}
+
+fn collect(link: &Link, out: &mut Vec<Glyph>) {
+    if let Some(node) = link {
+        collect(&node.left, out);
+        out.extend(node.chunk.iter().cloned());
+        collect(&node.right, out);
+    }
+}
+
+fn collect_range(link: &Link, offset: usize, range: &Range<usize>, out: &mut Vec<Glyph>) {
+    let node = match link {
+        Some(node) => node,
+        None => return,
+    };
+    if offset >= range.end || offset + node.summary.glyphs <= range.start {
+        return;
+    }
+    let left_len = summary(&node.left).glyphs;
+    collect_range(&node.left, offset, range, out);
+
+    let chunk_start = offset + left_len;
+    let from = range.start.max(chunk_start) - chunk_start;
+    let to = range.end.min(chunk_start + node.chunk.len());
+    if chunk_start + from < to {
+        out.extend(node.chunk[from..to - chunk_start].iter().cloned());
+    }
+
+    collect_range(&node.right, chunk_start + node.chunk.len(), range, out);
+}
+
+impl From<&str> for TextBuffer {
+    fn from(value: &str) -> TextBuffer {
+        let mut buffer = TextBuffer::new();
+        buffer.insert(0, grapheme::glyphs(value));
+        buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(glyphs: &[Glyph]) -> String {
+        glyphs.iter().map(|glyph| glyph.to_string()).collect()
+    }
+
+    /// `MAX_CHUNK` を何度かまたぐ長さの本文 (1行10文字)
+    fn long_text() -> String {
+        (0..40).map(|line| format!("line{line:05}\n")).collect()
+    }
+
+    #[test]
+    fn empty_buffer() {
+        let buffer = TextBuffer::new();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.len_lines(), 1);
+        assert_eq!(buffer.text(), "");
+        assert_eq!(buffer.line_to_offset(0), 0);
+        assert_eq!(buffer.line_to_offset(3), 0);
+        assert_eq!(buffer.offset_to_line(0), 0);
+        assert_eq!(buffer.char_to_offset(5), 0);
+        assert!(buffer.slice(0..0).is_empty());
+        assert!(buffer.get(0).is_none());
+    }
+
+    #[test]
+    fn inserts_and_removes_across_chunks() {
+        let text = long_text();
+        let mut buffer = TextBuffer::from(text.as_str());
+        assert_eq!(buffer.len(), 400);
+        assert_eq!(buffer.text(), text);
+
+        // チャンクの途中に、チャンクより長いGlyph列を挿入する
+        let inserted = "x".repeat(MAX_CHUNK * 2 + 3);
+        buffer.insert(MAX_CHUNK - 1, grapheme::glyphs(&inserted));
+        let mut expected = text.clone();
+        expected.insert_str(MAX_CHUNK - 1, &inserted);
+        assert_eq!(buffer.text(), expected);
+
+        // 短い挿入を繰り返してチャンクを溢れさせる
+        for at in (0..MAX_CHUNK * 3).step_by(7) {
+            buffer.insert(at, vec![Glyph::Char('y')]);
+            expected.insert(at, 'y');
+        }
+        assert_eq!(buffer.text(), expected);
+        assert_eq!(buffer.len(), expected.len());
+
+        // 複数のチャンクにまたがる削除
+        let range = MAX_CHUNK / 2..MAX_CHUNK * 4;
+        let removed = buffer.remove(range.clone());
+        assert_eq!(text_of(&removed), &expected[range.clone()]);
+        expected.replace_range(range, "");
+        assert_eq!(buffer.text(), expected);
+
+        // 1つのチャンクの中の削除
+        let removed = buffer.remove(1..3);
+        assert_eq!(text_of(&removed), &expected[1..3]);
+        expected.replace_range(1..3, "");
+        assert_eq!(buffer.text(), expected);
+
+        // すべて削除
+        buffer.remove(0..buffer.len());
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.len_lines(), 1);
+    }
+
+    #[test]
+    fn slices_across_chunks() {
+        let text = long_text();
+        let buffer = TextBuffer::from(text.as_str());
+        for range in [
+            0..0,
+            5..MAX_CHUNK + 5,
+            MAX_CHUNK - 1..MAX_CHUNK * 3 + 1,
+            0..400,
+        ] {
+            assert_eq!(text_of(&buffer.slice(range.clone())), &text[range]);
+        }
+        assert_eq!(
+            buffer.get(MAX_CHUNK).unwrap().to_string(),
+            &text[MAX_CHUNK..MAX_CHUNK + 1]
+        );
+    }
+
+    #[test]
+    fn converts_lines_and_offsets() {
+        let buffer = TextBuffer::from(long_text().as_str());
+        // 末尾の改行の後に空の行がある
+        assert_eq!(buffer.len_lines(), 41);
+        assert_eq!(buffer.line_to_offset(0), 0);
+        assert_eq!(buffer.line_to_offset(7), 70);
+        assert_eq!(buffer.line_to_offset(40), 400);
+        assert_eq!(buffer.line_to_offset(41), 400);
+        assert_eq!(buffer.offset_to_line(0), 0);
+        assert_eq!(buffer.offset_to_line(69), 6);
+        assert_eq!(buffer.offset_to_line(70), 7);
+        assert_eq!(buffer.offset_to_line(400), 40);
+
+        let trailing = TextBuffer::from("a\n");
+        assert_eq!(trailing.len_lines(), 2);
+        assert_eq!(trailing.line_to_offset(1), 2);
+        assert_eq!(trailing.offset_to_line(2), 1);
+    }
+
+    #[test]
+    fn converts_char_and_byte_offsets() {
+        // "が" は3byte、"👍🏽" は2文字で1つのGlyph
+        let buffer = TextBuffer::from("aが👍🏽b\nc");
+        assert_eq!(buffer.len(), 6);
+        assert_eq!(buffer.len_chars(), 7);
+        assert_eq!(buffer.len_bytes(), "aが👍🏽b\nc".len());
+
+        assert_eq!(buffer.offset_to_char(3), 4);
+        assert_eq!(buffer.offset_to_byte(2), 4);
+        assert_eq!(buffer.offset_to_byte(3), 12);
+        assert_eq!(buffer.char_to_offset(2), 2);
+        // Glyphの途中はそのGlyphの位置
+        assert_eq!(buffer.char_to_offset(3), 2);
+        assert_eq!(buffer.char_to_offset(4), 3);
+        assert_eq!(buffer.byte_to_offset(1), 1);
+        assert_eq!(buffer.byte_to_offset(2), 1);
+        assert_eq!(buffer.byte_to_offset(12), 3);
+        assert_eq!(buffer.char_to_offset(100), buffer.len());
+    }
+}
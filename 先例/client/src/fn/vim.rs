@@ -0,0 +1,592 @@
+use keyboard_types::Key;
+
+use crate::r#fn::editor_state::EditorState;
+use crate::r#fn::word;
+use crate::types::enums::{Direction, Glyph};
+
+/// Vimのモード
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    CommandLine,
+}
+
+/// コマンドラインモードで入力したコマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExCommand {
+    Write,
+    Quit,
+    WriteQuit,
+    /// `:e <memo>`
+    Edit(String),
+    Unknown(String),
+}
+
+impl ExCommand {
+    pub fn parse(line: &str) -> ExCommand {
+        let line = line.trim();
+        match line {
+            "w" => ExCommand::Write,
+            "q" | "q!" => ExCommand::Quit,
+            "wq" | "x" => ExCommand::WriteQuit,
+            _ => match line.strip_prefix("e ") {
+                Some(memo) if !memo.trim().is_empty() => ExCommand::Edit(memo.trim().to_string()),
+                _ => ExCommand::Unknown(line.to_string()),
+            },
+        }
+    }
+}
+
+/// キー入力を処理した結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VimAction {
+    /// Vimとして処理した
+    Handled,
+    /// 通常のキー処理に任せる (Insertモードの入力や矢印キーなど)
+    PassThrough,
+    /// コマンドラインで確定したコマンド。実行は呼び出し側で行う
+    Command(ExCommand),
+}
+
+/// ヤンク・削除した内容
+#[derive(Debug, Clone, Default)]
+struct Register {
+    glyphs: Vec<Glyph>,
+    /// `dd` や `yy` のような行単位の内容
+    linewise: bool,
+}
+
+/// `EditorState` の上に重ねるVimのモーダル編集
+/// 無効の場合はすべてのキーを通常の処理に任せる
+#[derive(Debug, Clone, Default)]
+pub struct Vim {
+    pub enabled: bool,
+    pub mode: VimMode,
+    /// コマンドラインモードで入力中の文字列 (`:` を除く)
+    pub command_line: String,
+    /// ステータスラインに表示するメッセージ
+    pub message: String,
+    count: Option<usize>,
+    /// 2文字目を待っているオペレータ (`d`, `y`, `g`)
+    operator: Option<char>,
+    register: Register,
+    /// Visualモードの起点 (行, 位置)
+    visual_anchor: (usize, usize),
+    /// 入力中の変更のキー列
+    recording: Vec<Key>,
+    /// `.` で繰り返す直前の変更のキー列
+    last_change: Vec<Key>,
+    replaying: bool,
+}
+
+impl Vim {
+    pub fn toggle(&mut self) {
+        *self = Vim {
+            enabled: !self.enabled,
+            ..Vim::default()
+        };
+    }
+
+    /// ステータスラインに表示するモード
+    pub fn status(&self) -> String {
+        if !self.enabled {
+            return String::new();
+        }
+        let pending = format!(
+            "{}{}",
+            self.count.map(|c| c.to_string()).unwrap_or_default(),
+            self.operator.map(String::from).unwrap_or_default()
+        );
+        match self.mode {
+            VimMode::CommandLine => format!(":{}", self.command_line),
+            VimMode::Insert => "-- INSERT --".to_string(),
+            VimMode::Visual => format!("-- VISUAL -- {pending}"),
+            VimMode::Normal if !self.message.is_empty() => self.message.clone(),
+            VimMode::Normal => format!("-- NORMAL -- {pending}"),
+        }
+    }
+
+    /// キー入力を処理する。`ctrl` はCtrlキーを押しているかどうか
+    pub fn handle_key(&mut self, key: &Key, ctrl: bool, e: &mut EditorState) -> VimAction {
+        if !self.enabled {
+            return VimAction::PassThrough;
+        }
+        match self.mode {
+            VimMode::Insert => self.insert_key(key, e),
+            VimMode::CommandLine => self.command_line_key(key),
+            VimMode::Normal | VimMode::Visual => self.normal_key(key, ctrl, e),
+        }
+    }
+
+    fn insert_key(&mut self, key: &Key, e: &mut EditorState) -> VimAction {
+        if !self.replaying {
+            self.recording.push(key.clone());
+        }
+        if *key == Key::Escape {
+            self.mode = VimMode::Normal;
+            if !self.replaying {
+                self.last_change = std::mem::take(&mut self.recording);
+            }
+            // Insertモードを抜けるとカーソルは1つ左に戻る
+            if e.cursor_position > 1 {
+                e.move_cursor(Direction::Backward);
+            }
+            e.break_undo_group();
+            return VimAction::Handled;
+        }
+        if !self.replaying {
+            return VimAction::PassThrough;
+        }
+
+        // `.` で繰り返す場合は入力を自前で反映する
+        match key {
+            Key::Character(text) if text == " " => e.insert_char('\u{00A0}'),
+            Key::Character(text) => e.insert_text(text),
+            Key::Enter => e.next_line_or_new(),
+            Key::Backspace => e.delete(Direction::Backward),
+            _ => (),
+        }
+        VimAction::Handled
+    }
+
+    fn command_line_key(&mut self, key: &Key) -> VimAction {
+        match key {
+            Key::Escape => self.mode = VimMode::Normal,
+            // 空の状態でBackspaceを押すとNormalモードに戻る
+            Key::Backspace if self.command_line.pop().is_none() => self.mode = VimMode::Normal,
+            Key::Enter => {
+                self.mode = VimMode::Normal;
+                let command = ExCommand::parse(&std::mem::take(&mut self.command_line));
+                if let ExCommand::Unknown(name) = &command {
+                    self.message = format!("E492: Not an editor command: {name}");
+                }
+                return VimAction::Command(command);
+            }
+            Key::Character(text) => self.command_line.push_str(text),
+            _ => (),
+        }
+        VimAction::Handled
+    }
+
+    fn normal_key(&mut self, key: &Key, ctrl: bool, e: &mut EditorState) -> VimAction {
+        let ch = match key {
+            Key::Character(text) if text.chars().count() == 1 => text.chars().next().unwrap(),
+            Key::Escape => {
+                self.reset_pending();
+                if self.mode == VimMode::Visual {
+                    self.mode = VimMode::Normal;
+                    let (line, pos) = (e.current_line, e.cursor_position);
+                    e.set_cursor(line, pos);
+                }
+                return VimAction::Handled;
+            }
+            // 矢印キーなどのカーソル移動は通常の処理に任せる
+            Key::ArrowUp
+            | Key::ArrowDown
+            | Key::ArrowLeft
+            | Key::ArrowRight
+            | Key::Home
+            | Key::End
+            | Key::PageUp
+            | Key::PageDown => return VimAction::PassThrough,
+            _ => return VimAction::Handled,
+        };
+
+        // 新しいコマンドの入力を始める
+        if self.count.is_none() && self.operator.is_none() && !self.replaying {
+            self.recording.clear();
+            self.message.clear();
+        }
+        if !self.replaying {
+            self.recording.push(key.clone());
+        }
+
+        if ctrl {
+            self.reset_pending();
+            if ch == 'r' {
+                e.redo();
+                return VimAction::Handled;
+            }
+            // Ctrl + C などエディタ側のショートカットは通常の処理に任せる
+            return VimAction::PassThrough;
+        }
+
+        if ch.is_ascii_digit() && (ch != '0' || self.count.is_some()) {
+            let digit = ch.to_digit(10).unwrap() as usize;
+            self.count = Some(self.count.unwrap_or(0) * 10 + digit);
+            return VimAction::Handled;
+        }
+
+        let explicit = self.count.take();
+        let count = explicit.unwrap_or(1);
+
+        match (self.operator.take(), ch) {
+            (Some('d'), 'd') => {
+                self.register = Register {
+                    glyphs: e.delete_lines(count),
+                    linewise: true,
+                };
+                self.finish_change();
+            }
+            (Some('y'), 'y') => {
+                self.register = Register {
+                    glyphs: e.yank_lines(count),
+                    linewise: true,
+                };
+            }
+            (Some('g'), 'g') => {
+                let line = explicit.unwrap_or(1);
+                self.visual_motion(e, |e| e.set_cursor(line, 1));
+            }
+            (Some(_), _) => (),
+            (None, 'd' | 'x') if self.mode == VimMode::Visual => {
+                let range = self.visual_range(e);
+                self.register = Register {
+                    glyphs: e.delete_range(range),
+                    linewise: false,
+                };
+                self.mode = VimMode::Normal;
+            }
+            (None, 'y') if self.mode == VimMode::Visual => {
+                let range = self.visual_range(e);
+                self.register = Register {
+                    glyphs: e.contents.buffer.slice(range.clone()),
+                    linewise: false,
+                };
+                let (line, pos) = e.contents.position(range.start);
+                e.set_cursor(line, pos);
+                self.mode = VimMode::Normal;
+            }
+            (None, 'v') if self.mode == VimMode::Visual => {
+                self.mode = VimMode::Normal;
+                let (line, pos) = (e.current_line, e.cursor_position);
+                e.set_cursor(line, pos);
+            }
+            (None, op @ ('d' | 'y' | 'g')) => {
+                self.operator = Some(op);
+                self.count = explicit;
+            }
+            (None, 'x') => {
+                let mut removed = Vec::new();
+                for _ in 0..count {
+                    let line = e.current_line().as_vec().clone();
+                    let Some(glyph) = line.get(e.cursor_position - 1) else {
+                        break;
+                    };
+                    removed.push(glyph.clone());
+                    e.delete(Direction::Forward);
+                }
+                if !removed.is_empty() {
+                    self.register = Register {
+                        glyphs: removed,
+                        linewise: false,
+                    };
+                }
+                self.clamp_cursor(e);
+                self.finish_change();
+            }
+            (None, 'p' | 'P') => {
+                self.put(ch == 'p', count, e);
+                self.finish_change();
+            }
+            (None, 'u') => {
+                for _ in 0..count {
+                    e.undo();
+                }
+            }
+            (None, 'i') => self.enter_insert(e),
+            (None, 'a') => {
+                if e.current_line_length() > 0 {
+                    let (line, pos) = (e.current_line, e.cursor_position + 1);
+                    e.set_cursor(line, pos);
+                }
+                self.enter_insert(e);
+            }
+            (None, 'I') => {
+                e.set_cursor_start_of_line();
+                self.enter_insert(e);
+            }
+            (None, 'A') => {
+                e.set_cursor_end_of_line();
+                self.enter_insert(e);
+            }
+            (None, 'o') => {
+                e.set_cursor_end_of_line();
+                e.next_line_or_new();
+                self.enter_insert(e);
+            }
+            (None, 'O') => {
+                e.open_line_above();
+                self.enter_insert(e);
+            }
+            (None, 'v') => {
+                self.mode = VimMode::Visual;
+                self.visual_anchor = (e.current_line, e.cursor_position);
+            }
+            (None, ':') => {
+                self.mode = VimMode::CommandLine;
+                self.command_line.clear();
+            }
+            (None, '.') => self.repeat(count, e),
+            (None, 'G') => {
+                let line = explicit.unwrap_or(e.contents.len());
+                self.visual_motion(e, |e| e.set_cursor(line, 1));
+            }
+            (None, motion) => {
+                self.visual_motion(e, |e| {
+                    for _ in 0..count {
+                        apply_motion(motion, e);
+                    }
+                });
+            }
+        }
+        VimAction::Handled
+    }
+
+    fn reset_pending(&mut self) {
+        self.count = None;
+        self.operator = None;
+    }
+
+    fn enter_insert(&mut self, e: &mut EditorState) {
+        self.mode = VimMode::Insert;
+        e.break_undo_group();
+    }
+
+    /// 変更を `.` で繰り返せるように記録する
+    fn finish_change(&mut self) {
+        if !self.replaying {
+            self.last_change = std::mem::take(&mut self.recording);
+        }
+    }
+
+    fn repeat(&mut self, count: usize, e: &mut EditorState) {
+        let keys = self.last_change.clone();
+        self.replaying = true;
+        for _ in 0..count {
+            for key in &keys {
+                self.handle_key(key, false, e);
+            }
+        }
+        self.replaying = false;
+    }
+
+    /// カーソルを動かす。Visualモードでは選択範囲を広げる
+    fn visual_motion(&mut self, e: &mut EditorState, motion: impl FnOnce(&mut EditorState)) {
+        motion(e);
+        if self.mode == VimMode::Visual {
+            e.selection_anchor = Some(self.visual_anchor);
+        } else {
+            self.clamp_cursor(e);
+        }
+    }
+
+    /// Visualモードの選択範囲 (offset)。Vimと同じくカーソル位置の文字も含める
+    fn visual_range(&self, e: &EditorState) -> std::ops::Range<usize> {
+        let (line, pos) = self.visual_anchor;
+        let anchor = e.contents.offset(line, pos);
+        let head = e.contents.offset(e.current_line, e.cursor_position);
+        let end = (anchor.max(head) + 1).min(e.contents.buffer.len());
+        anchor.min(head)..end
+    }
+
+    /// Normalモードではカーソルを行末の文字の上に置く
+    fn clamp_cursor(&self, e: &mut EditorState) {
+        let length = e.current_line_length();
+        if length > 0 && e.cursor_position > length {
+            let line = e.current_line;
+            e.set_cursor(line, length);
+        }
+    }
+
+    fn put(&mut self, after: bool, count: usize, e: &mut EditorState) {
+        if self.register.glyphs.is_empty() && !self.register.linewise {
+            return;
+        }
+        let mut glyphs = Vec::new();
+        for idx in 0..count {
+            if idx > 0 && self.register.linewise {
+                glyphs.push(Glyph::Char('\n'));
+            }
+            glyphs.extend(self.register.glyphs.iter().cloned());
+        }
+
+        if self.register.linewise {
+            e.put_lines(glyphs, after);
+            return;
+        }
+        if after && e.current_line_length() > 0 {
+            let (line, pos) = (e.current_line, e.cursor_position + 1);
+            e.set_cursor(line, pos);
+        }
+        e.insert_glyphs(glyphs);
+        // 貼り付けた最後の文字の上に置く
+        e.move_cursor(Direction::Backward);
+    }
+}
+
+/// 1回分のカーソル移動。対応するモーションでなければ何もしない
+fn apply_motion(motion: char, e: &mut EditorState) {
+    match motion {
+        'h' if e.cursor_position > 1 => e.move_cursor(Direction::Backward),
+        'l' if e.cursor_position < e.current_line_length() => e.move_cursor(Direction::Forward),
+        'j' => e.go_to_line(Direction::Forward),
+        'k' => e.go_to_line(Direction::Backward),
+        'w' => next_word_start(e),
+        'b' => e.move_word(Direction::Backward),
+        'e' => next_word_end(e),
+        '0' => e.set_cursor_start_of_line(),
+        '$' => {
+            let (line, length) = (e.current_line, e.current_line_length());
+            e.set_cursor(line, length.max(1));
+        }
+        _ => (),
+    }
+}
+
+/// 次の単語の先頭に移動する (`w`)。行末では次の行の最初の単語に移る
+fn next_word_start(e: &mut EditorState) {
+    let glyphs = e.current_line().as_vec().clone();
+    let column = e.cursor_position - 1;
+    let next = word::word_segments(&glyphs)
+        .into_iter()
+        .find(|(start, _, is_space)| *start > column && !is_space);
+    match next {
+        Some((start, _, _)) => {
+            let line = e.current_line;
+            e.set_cursor(line, start + 1);
+        }
+        None if e.current_line < e.contents.len() => {
+            let line = e.current_line + 1;
+            let glyphs = e.contents.line(line).as_vec().clone();
+            let start = word::word_segments(&glyphs)
+                .into_iter()
+                .find(|(_, _, is_space)| !is_space)
+                .map(|(start, _, _)| start)
+                .unwrap_or(0);
+            e.set_cursor(line, start + 1);
+        }
+        None => (),
+    }
+}
+
+/// 単語の末尾の文字に移動する (`e`)。行末では次の行の最初の単語に移る
+fn next_word_end(e: &mut EditorState) {
+    let glyphs = e.current_line().as_vec().clone();
+    let column = e.cursor_position - 1;
+    let next = word::word_segments(&glyphs)
+        .into_iter()
+        .find(|(_, end, is_space)| *end > column + 1 && !is_space);
+    match next {
+        Some((_, end, _)) => {
+            let line = e.current_line;
+            e.set_cursor(line, end);
+        }
+        None if e.current_line < e.contents.len() => {
+            let line = e.current_line + 1;
+            let glyphs = e.contents.line(line).as_vec().clone();
+            let end = word::word_segments(&glyphs)
+                .into_iter()
+                .find(|(_, _, is_space)| !is_space)
+                .map(|(_, end, _)| end)
+                .unwrap_or(1);
+            e.set_cursor(line, end);
+        }
+        None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(vim: &mut Vim, e: &mut EditorState, input: &str) {
+        for ch in input.chars() {
+            let key = match ch {
+                '⎋' => Key::Escape,
+                '⏎' => Key::Enter,
+                ch => Key::Character(ch.to_string()),
+            };
+            if vim.handle_key(&key, false, e) == VimAction::PassThrough {
+                // Insertモードの入力はエディタ側で処理される
+                e.insert_text(&ch.to_string());
+            }
+        }
+    }
+
+    fn vim() -> Vim {
+        Vim {
+            enabled: true,
+            ..Vim::default()
+        }
+    }
+
+    #[test]
+    fn motions_with_counts() {
+        let mut e = EditorState::from("one\u{00A0}two\u{00A0}three\nfour\nfive");
+        let mut v = vim();
+        keys(&mut v, &mut e, "2w");
+        assert_eq!(e.cursor_position, 9);
+        keys(&mut v, &mut e, "$");
+        assert_eq!(e.cursor_position, 13);
+        keys(&mut v, &mut e, "G");
+        assert_eq!(e.current_line, 3);
+        keys(&mut v, &mut e, "2gg");
+        assert_eq!((e.current_line, e.cursor_position), (2, 1));
+        keys(&mut v, &mut e, "e");
+        assert_eq!(e.cursor_position, 4);
+    }
+
+    #[test]
+    fn delete_yank_put_and_undo() {
+        let mut e = EditorState::from("a\nb\nc");
+        let mut v = vim();
+        keys(&mut v, &mut e, "dd");
+        assert_eq!(e.get_line_content(1), "❮b");
+        keys(&mut v, &mut e, "p");
+        assert_eq!(e.contents.lines().len(), 3);
+        assert_eq!(e.get_line_content(2), "❮a");
+        keys(&mut v, &mut e, "yyP");
+        assert_eq!(e.get_line_content(2), "❮a");
+        assert_eq!(e.get_line_content(3), "a");
+        keys(&mut v, &mut e, "uu");
+        assert_eq!(e.get_line_content(1), "❮b");
+        assert_eq!(e.contents.lines().len(), 2);
+    }
+
+    #[test]
+    fn insert_and_dot_repeat() {
+        let mut e = EditorState::from("x");
+        let mut v = vim();
+        keys(&mut v, &mut e, "Aab⎋");
+        assert_eq!(v.mode, VimMode::Normal);
+        assert_eq!(e.get_line_content(1), "xa❮b");
+        keys(&mut v, &mut e, "..");
+        assert_eq!(e.get_line_content(1), "xababa❮b");
+        keys(&mut v, &mut e, "0x.");
+        assert_eq!(e.get_line_content(1), "❮babab");
+    }
+
+    #[test]
+    fn visual_delete_and_command_line() {
+        let mut e = EditorState::from("hello\u{00A0}world");
+        let mut v = vim();
+        keys(&mut v, &mut e, "vlld");
+        assert_eq!(e.get_line_content(1), "❮lo\u{00A0}world");
+        assert_eq!(v.mode, VimMode::Normal);
+
+        keys(&mut v, &mut e, ":wq");
+        assert_eq!(v.status(), ":wq");
+        assert_eq!(
+            v.handle_key(&Key::Enter, false, &mut e),
+            VimAction::Command(ExCommand::WriteQuit)
+        );
+        assert_eq!(
+            ExCommand::parse("e memo"),
+            ExCommand::Edit("memo".to_string())
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::types::enums::Glyph;
+
+/// 同じ種類が続く場合に1単語として扱う文字種
+/// UAX #29 では漢字・ひらがなは1文字ずつ区切られるため、連続する同じ文字種をまとめる
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Han,
+    Hiragana,
+    Katakana,
+    Other,
+}
+
+fn char_class(ch: char) -> CharClass {
+    match ch {
+        c if c.is_whitespace() => CharClass::Space,
+        '\u{3040}'..='\u{309F}' => CharClass::Hiragana,
+        '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+            CharClass::Katakana
+        }
+        '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2FFFF}' => CharClass::Han,
+        _ => CharClass::Other,
+    }
+}
+
+fn glyph_text(glyph: &Glyph) -> String {
+    match glyph {
+        Glyph::Text(t) => t.clone(),
+        Glyph::Char(c) => c.to_string(),
+        Glyph::Cursor => "".to_string(),
+        Glyph::HTMLNode(_) | Glyph::Component(_) => "\u{FFFC}".to_string(),
+    }
+}
+
+/// 行内の単語 (glyph位置 [開始, 終了), 空白かどうか) の一覧
+pub fn word_segments(glyphs: &[Glyph]) -> Vec<(usize, usize, bool)> {
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(glyphs.len());
+    for glyph in glyphs {
+        starts.push(text.len());
+        text.push_str(&glyph_text(glyph));
+    }
+
+    let mut segments: Vec<(usize, usize, CharClass)> = Vec::new();
+    for (byte_idx, word) in text.split_word_bound_indices() {
+        // Glyphの途中の境界は無視する
+        let start = starts.partition_point(|s| *s < byte_idx);
+        let end = starts.partition_point(|s| *s < byte_idx + word.len());
+        if start >= end {
+            continue;
+        }
+        let class = word
+            .chars()
+            .next()
+            .map(char_class)
+            .unwrap_or(CharClass::Other);
+
+        match segments.last_mut() {
+            Some(last)
+                if last.1 == start
+                    && last.2 == class
+                    && matches!(
+                        class,
+                        CharClass::Space
+                            | CharClass::Han
+                            | CharClass::Hiragana
+                            | CharClass::Katakana
+                    ) =>
+            {
+                last.1 = end
+            }
+            _ => segments.push((start, end, class)),
+        }
+    }
+
+    segments
+        .into_iter()
+        .map(|(start, end, class)| (start, end, class == CharClass::Space))
+        .collect()
+}
+
+/// `column` (0始まり) より前にある単語の先頭
+/// 直前の空白は読み飛ばす
+pub fn prev_word_start(glyphs: &[Glyph], column: usize) -> usize {
+    let mut result = 0;
+    for (start, _end, is_space) in word_segments(glyphs)
+        .into_iter()
+        .filter(|(start, _, _)| *start < column)
+    {
+        if !is_space {
+            result = start;
+        }
+    }
+    result
+}
+
+/// `column` (0始まり) より後ろにある単語の末尾
+/// 直後の空白は読み飛ばす
+pub fn next_word_end(glyphs: &[Glyph], column: usize) -> usize {
+    word_segments(glyphs)
+        .into_iter()
+        .find(|(_, end, is_space)| *end > column && !is_space)
+        .map(|(_, end, _)| end)
+        .unwrap_or(glyphs.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r#fn::line::Line;
+
+    fn glyphs(text: &str) -> Vec<Glyph> {
+        Line::from(text).as_vec().clone()
+    }
+
+    #[test]
+    fn prev_word_start_skips_spaces() {
+        let line = glyphs("hello  world");
+        assert_eq!(prev_word_start(&line, 12), 7);
+        assert_eq!(prev_word_start(&line, 7), 0);
+        assert_eq!(prev_word_start(&line, 3), 0);
+    }
+
+    #[test]
+    fn next_word_end_skips_spaces() {
+        let line = glyphs("hello\u{00A0}world");
+        assert_eq!(next_word_end(&line, 0), 5);
+        assert_eq!(next_word_end(&line, 5), 11);
+    }
+
+    #[test]
+    fn cjk_runs_are_one_word() {
+        // 漢字 / ひらがな / カタカナ の切り替わりで区切る
+        let line = glyphs("日本語のテキスト");
+        assert_eq!(next_word_end(&line, 0), 3);
+        assert_eq!(next_word_end(&line, 3), 4);
+        assert_eq!(next_word_end(&line, 4), 8);
+        assert_eq!(prev_word_start(&line, 8), 4);
+    }
+
+    #[test]
+    fn punctuation_is_its_own_word() {
+        let line = glyphs("foo(bar");
+        assert_eq!(prev_word_start(&line, 7), 4);
+        assert_eq!(prev_word_start(&line, 4), 3);
+    }
+}
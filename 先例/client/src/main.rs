@@ -1,4 +1,7 @@
 use components::{editor::Editor, home_editor::HomeEditor};
+use context::jump_context::JumpProvider;
+use context::storage_context::StorageProvider;
+use context::sync_context::SyncProvider;
 use context::theme_context::ThemeProvider;
 use dioxus::prelude::*;
 
@@ -36,7 +39,13 @@ fn App() -> Element {
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
         document::Link { rel: "stylesheet", href: MAIN_CSS }
-        ThemeProvider { Router::<Route> {} }
+        ThemeProvider {
+            StorageProvider {
+                SyncProvider {
+                    JumpProvider { Router::<Route> {} }
+                }
+            }
+        }
     }
 }
 
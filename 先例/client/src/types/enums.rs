@@ -1,12 +1,13 @@
 use dioxus::prelude::*;
 use std::fmt::Display;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Forward = 1,
     Backward = -1,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Glyph {
     Text(String),
     Char(char),
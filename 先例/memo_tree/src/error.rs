@@ -0,0 +1,37 @@
+use std::fmt;
+
+use crate::node::NodeId;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoTreeError {
+    NotFound(String),
+    AlreadyExists(String),
+    NotADirectory(String),
+    IsADirectory(String),
+    /// ルートなど操作できないパス
+    InvalidPath(String),
+    /// フォルダを自分の中へ移動・コピーしようとした
+    Cycle {
+        from: String,
+        to: String,
+    },
+    UnknownId(NodeId),
+}
+
+impl fmt::Display for MemoTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoTreeError::NotFound(path) => write!(f, "{path}: no such file or directory"),
+            MemoTreeError::AlreadyExists(path) => write!(f, "{path}: file exists"),
+            MemoTreeError::NotADirectory(path) => write!(f, "{path}: not a directory"),
+            MemoTreeError::IsADirectory(path) => write!(f, "{path}: is a directory"),
+            MemoTreeError::InvalidPath(path) => write!(f, "{path}: invalid path"),
+            MemoTreeError::Cycle { from, to } => {
+                write!(f, "cannot move or copy {from} into itself, {to}")
+            }
+            MemoTreeError::UnknownId(id) => write!(f, "{id}: no such node"),
+        }
+    }
+}
+
+impl std::error::Error for MemoTreeError {}
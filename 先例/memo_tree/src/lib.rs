@@ -0,0 +1,13 @@
+//! メモとフォルダの階層。クライアント (Dioxus) とサーバー (axum) で共有する
+//!
+//! ノードは `parent_id` で親を指す平らなリストとして保存し、
+//! パス (`/notes/memo1`) での参照や移動は `MemoTree` で行う
+
+pub mod error;
+pub mod node;
+pub mod path;
+pub mod tree;
+
+pub use error::MemoTreeError;
+pub use node::{Node, NodeId, NodeKind};
+pub use tree::MemoTree;
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// メモ・フォルダのid。メモのidはそのままmemo_idとして使う
+pub type NodeId = i32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Folder,
+    Memo,
+}
+
+/// 階層の1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Node {
+    pub id: NodeId,
+    /// `None` はルート直下
+    pub parent_id: Option<NodeId>,
+    pub name: String,
+    pub kind: NodeKind,
+    /// メモの本文のバイト数。フォルダは0
+    #[serde(default)]
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Node {
+    pub fn new(id: NodeId, parent_id: Option<NodeId>, name: &str, kind: NodeKind) -> Node {
+        let now = Utc::now();
+        Node {
+            id,
+            parent_id,
+            name: name.to_string(),
+            kind,
+            size: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_folder(&self) -> bool {
+        self.kind == NodeKind::Folder
+    }
+}
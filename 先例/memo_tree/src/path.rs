@@ -0,0 +1,67 @@
+//! `/` 区切りのパス
+
+/// 空の要素を除いたパスの要素
+pub fn segments(path: &str) -> Vec<&str> {
+    path.split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// 要素から絶対パスを作る
+pub fn join(segments: &[&str]) -> String {
+    format!("/{}", segments.join("/"))
+}
+
+/// 親フォルダのパスと名前に分ける。ルートは `None`
+pub fn split_parent(path: &str) -> Option<(String, String)> {
+    let segments = segments(path);
+    segments
+        .split_last()
+        .map(|(name, parent)| (join(parent), name.to_string()))
+}
+
+/// `cd` の引数（例: ".", "..", "dir1/dir2", "../..", "/dir" など）を
+/// 現在のパス `current` に当てはめた絶対パス
+pub fn resolve(current: &str, target: &str) -> String {
+    let mut segments: Vec<&str> = if target.starts_with('/') {
+        Vec::new()
+    } else {
+        segments(current)
+    };
+
+    for token in target.split('/') {
+        match token {
+            // 連続したスラッシュ ("dir//subdir") やカレントディレクトリは何もしない
+            "" | "." => {}
+            // 一つ上の階層へ
+            ".." => {
+                segments.pop();
+            }
+            other => segments.push(other),
+        }
+    }
+
+    join(&segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_relative_and_absolute_paths() {
+        assert_eq!(resolve("/", "dir/sub"), "/dir/sub");
+        assert_eq!(resolve("/a/b", "../c"), "/a/c");
+        assert_eq!(resolve("/a/b", "./c//d"), "/a/b/c/d");
+        assert_eq!(resolve("/a", "../../.."), "/");
+        assert_eq!(resolve("/a/b", "/x"), "/x");
+        assert_eq!(resolve("", "."), "/");
+    }
+
+    #[test]
+    fn splits_parent() {
+        assert_eq!(
+            split_parent("/a/b/"),
+            Some(("/a".to_string(), "b".to_string()))
+        );
+        assert_eq!(split_parent("/"), None);
+    }
+}
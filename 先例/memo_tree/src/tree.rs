@@ -0,0 +1,483 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::MemoTreeError;
+use crate::node::{Node, NodeId, NodeKind};
+use crate::path::{join, segments, split_parent};
+
+type Result<T> = std::result::Result<T, MemoTreeError>;
+
+/// メモとフォルダの階層
+/// JSONなどにはノードのリストとして保存する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<Node>", into = "Vec<Node>")]
+pub struct MemoTree {
+    nodes: BTreeMap<NodeId, Node>,
+    /// 次に作るノードのid
+    next_id: NodeId,
+}
+
+impl Default for MemoTree {
+    fn default() -> MemoTree {
+        MemoTree {
+            nodes: BTreeMap::new(),
+            next_id: 1,
+        }
+    }
+}
+
+impl From<Vec<Node>> for MemoTree {
+    fn from(nodes: Vec<Node>) -> MemoTree {
+        let next_id = nodes.iter().map(|node| node.id + 1).max().unwrap_or(1);
+        MemoTree {
+            nodes: nodes.into_iter().map(|node| (node.id, node)).collect(),
+            next_id,
+        }
+    }
+}
+
+impl From<MemoTree> for Vec<Node> {
+    fn from(tree: MemoTree) -> Vec<Node> {
+        tree.nodes.into_values().collect()
+    }
+}
+
+impl MemoTree {
+    pub fn new() -> MemoTree {
+        MemoTree::default()
+    }
+
+    /// id順のノード
+    pub fn nodes(&self) -> impl Iterator<Item = &Node> {
+        self.nodes.values()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(&id)
+    }
+
+    /// フォルダの中身 (id順)。`None` はルート
+    pub fn children(&self, parent_id: Option<NodeId>) -> Vec<&Node> {
+        self.nodes
+            .values()
+            .filter(|node| node.parent_id == parent_id)
+            .collect()
+    }
+
+    /// ノードの絶対パス
+    pub fn path_of(&self, id: NodeId) -> Option<String> {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let node = self.nodes.get(&id)?;
+            names.push(node.name.as_str());
+            current = node.parent_id;
+            // 壊れたデータで親をたどり続けないようにする
+            if names.len() > self.nodes.len() {
+                return None;
+            }
+        }
+        names.reverse();
+        Some(join(&names))
+    }
+
+    fn display_path(&self, parent_id: Option<NodeId>, name: &str) -> String {
+        let parent = parent_id
+            .and_then(|id| self.path_of(id))
+            .unwrap_or_default();
+        format!("{}/{}", parent, name)
+    }
+
+    /// `ancestor` が `id` 自身またはその上のフォルダか
+    pub fn is_ancestor(&self, ancestor: NodeId, id: NodeId) -> bool {
+        let mut current = Some(id);
+        let mut steps = 0;
+        while let Some(id) = current {
+            if id == ancestor {
+                return true;
+            }
+            current = self.nodes.get(&id).and_then(|node| node.parent_id);
+            steps += 1;
+            if steps > self.nodes.len() {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn child(&self, parent_id: Option<NodeId>, name: &str) -> Option<&Node> {
+        self.nodes
+            .values()
+            .find(|node| node.parent_id == parent_id && node.name == name)
+    }
+
+    /// パスの指すノードのid。ルートは `None`
+    pub fn lookup(&self, path: &str) -> Result<Option<NodeId>> {
+        let segments = segments(path);
+        let mut current: Option<&Node> = None;
+        for (idx, name) in segments.iter().enumerate() {
+            let path = || join(&segments[..=idx]);
+            if current.is_some_and(|node| !node.is_folder()) {
+                return Err(MemoTreeError::NotADirectory(join(&segments[..idx])));
+            }
+            current = Some(
+                self.child(current.map(|node| node.id), name)
+                    .ok_or_else(|| MemoTreeError::NotFound(path()))?,
+            );
+        }
+        Ok(current.map(|node| node.id))
+    }
+
+    /// パスの指すノード。ルートは `None`
+    pub fn node_at(&self, path: &str) -> Result<Option<&Node>> {
+        Ok(self.lookup(path)?.and_then(|id| self.nodes.get(&id)))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.lookup(path).is_ok()
+    }
+
+    /// フォルダか (ルートを含む)
+    pub fn is_folder(&self, path: &str) -> bool {
+        self.node_at(path)
+            .is_ok_and(|node| node.is_none_or(Node::is_folder))
+    }
+
+    /// フォルダのパスからフォルダのid。ルートは `None`
+    pub fn folder_id(&self, path: &str) -> Result<Option<NodeId>> {
+        match self.node_at(path)? {
+            Some(node) if !node.is_folder() => {
+                Err(MemoTreeError::NotADirectory(join(&segments(path))))
+            }
+            node => Ok(node.map(|node| node.id)),
+        }
+    }
+
+    /// フォルダの中身
+    pub fn list(&self, path: &str) -> Result<Vec<&Node>> {
+        Ok(self.children(self.folder_id(path)?))
+    }
+
+    /// パスの指すメモのid
+    pub fn memo_id(&self, path: &str) -> Result<NodeId> {
+        match self.node_at(path)? {
+            Some(node) if !node.is_folder() => Ok(node.id),
+            _ => Err(MemoTreeError::IsADirectory(join(&segments(path)))),
+        }
+    }
+
+    /// フォルダ `parent_id` に新しいノードを置く
+    pub fn insert(
+        &mut self,
+        parent_id: Option<NodeId>,
+        name: &str,
+        kind: NodeKind,
+    ) -> Result<NodeId> {
+        self.check_destination(parent_id, name, None)?;
+        let id = self.next_id;
+        self.next_id += 1;
+        self.nodes.insert(id, Node::new(id, parent_id, name, kind));
+        Ok(id)
+    }
+
+    /// パスに新しいノードを置く。親フォルダが必要
+    pub fn create(&mut self, path: &str, kind: NodeKind) -> Result<NodeId> {
+        let (parent, name) =
+            split_parent(path).ok_or_else(|| MemoTreeError::InvalidPath(path.to_string()))?;
+        let parent_id = self.folder_id(&parent)?;
+        self.insert(parent_id, &name, kind)
+    }
+
+    /// 途中のフォルダも作る (`mkdir -p`)。既にあるフォルダはそのまま使う
+    pub fn create_folder_all(&mut self, path: &str) -> Result<Option<NodeId>> {
+        let mut parent_id = None;
+        for name in segments(path) {
+            parent_id = match self.child(parent_id, name) {
+                Some(node) if node.is_folder() => Some(node.id),
+                Some(_) => {
+                    return Err(MemoTreeError::AlreadyExists(
+                        self.display_path(parent_id, name),
+                    ))
+                }
+                None => Some(self.insert(parent_id, name, NodeKind::Folder)?),
+            };
+        }
+        Ok(parent_id)
+    }
+
+    /// メモを保存した時に本文のバイト数と更新日時を記録する
+    pub fn update_memo(&mut self, id: NodeId, size: u64) -> Result<()> {
+        let node = self
+            .nodes
+            .get_mut(&id)
+            .ok_or(MemoTreeError::UnknownId(id))?;
+        node.size = size;
+        node.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// ノードと、フォルダならその中身を全て消す
+    pub fn remove(&mut self, id: NodeId) -> Result<Vec<Node>> {
+        if !self.nodes.contains_key(&id) {
+            return Err(MemoTreeError::UnknownId(id));
+        }
+        let ids: Vec<NodeId> = self
+            .nodes
+            .keys()
+            .copied()
+            .filter(|node_id| self.is_ancestor(id, *node_id))
+            .collect();
+        Ok(ids
+            .into_iter()
+            .filter_map(|id| self.nodes.remove(&id))
+            .collect())
+    }
+
+    /// ノードをフォルダ `parent_id` へ移し、名前を `name` にする
+    /// フォルダを自分の中へは移せない
+    pub fn move_node(&mut self, id: NodeId, parent_id: Option<NodeId>, name: &str) -> Result<()> {
+        if !self.nodes.contains_key(&id) {
+            return Err(MemoTreeError::UnknownId(id));
+        }
+        self.check_cycle(id, parent_id, name)?;
+        self.check_destination(parent_id, name, Some(id))?;
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.parent_id = parent_id;
+            node.name = name.to_string();
+            node.updated_at = Utc::now();
+        }
+        Ok(())
+    }
+
+    /// ノードをフォルダ `parent_id` に `name` としてコピーする
+    /// コピーしたノードには新しいidを振り、(新しいid, コピー元のid) の一覧を返す
+    /// 先頭は `id` のコピー
+    pub fn copy_node(
+        &mut self,
+        id: NodeId,
+        parent_id: Option<NodeId>,
+        name: &str,
+    ) -> Result<Vec<(NodeId, NodeId)>> {
+        let node = self
+            .nodes
+            .get(&id)
+            .cloned()
+            .ok_or(MemoTreeError::UnknownId(id))?;
+        self.check_cycle(id, parent_id, name)?;
+        let new_id = self.insert(parent_id, name, node.kind)?;
+        if let Some(copy) = self.nodes.get_mut(&new_id) {
+            copy.size = node.size;
+        }
+        let mut copied = vec![(new_id, id)];
+        // 先に子の一覧を取ってからコピーする
+        let children: Vec<(NodeId, String)> = self
+            .children(Some(id))
+            .into_iter()
+            .map(|child| (child.id, child.name.clone()))
+            .collect();
+        for (child_id, child_name) in children {
+            copied.extend(self.copy_node(child_id, Some(new_id), &child_name)?);
+        }
+        Ok(copied)
+    }
+
+    /// `mv` `cp` の移動先 (親フォルダ, 名前)
+    /// `to` が既存のフォルダならその中へ同じ名前で置く
+    pub fn destination(&self, from: &str, to: &str) -> Result<(Option<NodeId>, String)> {
+        let node = self
+            .node_at(from)?
+            .ok_or_else(|| MemoTreeError::InvalidPath(from.to_string()))?;
+        if self.is_folder(to) {
+            return Ok((self.folder_id(to)?, node.name.clone()));
+        }
+        let (parent, name) =
+            split_parent(to).ok_or_else(|| MemoTreeError::InvalidPath(to.to_string()))?;
+        Ok((self.folder_id(&parent)?, name))
+    }
+
+    /// パスで指定した移動・名前の変更 (`mv`)
+    pub fn move_path(&mut self, from: &str, to: &str) -> Result<()> {
+        let (parent_id, name) = self.destination(from, to)?;
+        let id = self
+            .lookup(from)?
+            .ok_or_else(|| MemoTreeError::InvalidPath(from.to_string()))?;
+        self.move_node(id, parent_id, &name)
+    }
+
+    /// パスで指定したコピー (`cp`)
+    pub fn copy_path(&mut self, from: &str, to: &str) -> Result<Vec<(NodeId, NodeId)>> {
+        let (parent_id, name) = self.destination(from, to)?;
+        let id = self
+            .lookup(from)?
+            .ok_or_else(|| MemoTreeError::InvalidPath(from.to_string()))?;
+        self.copy_node(id, parent_id, &name)
+    }
+
+    fn check_cycle(&self, id: NodeId, parent_id: Option<NodeId>, name: &str) -> Result<()> {
+        if parent_id.is_some_and(|parent_id| self.is_ancestor(id, parent_id)) {
+            return Err(MemoTreeError::Cycle {
+                from: self.path_of(id).unwrap_or_default(),
+                to: self.display_path(parent_id, name),
+            });
+        }
+        Ok(())
+    }
+
+    /// 置き先のフォルダがあり、同じ名前がないか (`except` 自身は除く)
+    fn check_destination(
+        &self,
+        parent_id: Option<NodeId>,
+        name: &str,
+        except: Option<NodeId>,
+    ) -> Result<()> {
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(MemoTreeError::InvalidPath(name.to_string()));
+        }
+        if let Some(parent_id) = parent_id {
+            let parent = self
+                .nodes
+                .get(&parent_id)
+                .ok_or(MemoTreeError::UnknownId(parent_id))?;
+            if !parent.is_folder() {
+                return Err(MemoTreeError::NotADirectory(
+                    self.path_of(parent_id).unwrap_or_default(),
+                ));
+            }
+        }
+        match self.child(parent_id, name) {
+            Some(node) if Some(node.id) != except => Err(MemoTreeError::AlreadyExists(
+                self.display_path(parent_id, name),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `/memo1` `/notes/daily/` `/notes/memo2`
+    fn tree() -> MemoTree {
+        let mut tree = MemoTree::new();
+        tree.create("/memo1", NodeKind::Memo).unwrap();
+        tree.create_folder_all("/notes/daily").unwrap();
+        tree.create("/notes/memo2", NodeKind::Memo).unwrap();
+        tree
+    }
+
+    fn names(tree: &MemoTree, dir: &str) -> Vec<String> {
+        tree.list(dir)
+            .unwrap()
+            .iter()
+            .map(|node| node.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn looks_up_paths() {
+        let tree = tree();
+        assert_eq!(names(&tree, "/"), vec!["memo1", "notes"]);
+        assert_eq!(names(&tree, "/notes"), vec!["daily", "memo2"]);
+        assert_eq!(tree.memo_id("/notes/memo2"), Ok(4));
+        assert_eq!(tree.path_of(3).as_deref(), Some("/notes/daily"));
+        assert_eq!(tree.lookup("/"), Ok(None));
+
+        assert_eq!(
+            tree.lookup("/notes/missing/x"),
+            Err(MemoTreeError::NotFound("/notes/missing".to_string()))
+        );
+        assert_eq!(
+            tree.lookup("/memo1/x"),
+            Err(MemoTreeError::NotADirectory("/memo1".to_string()))
+        );
+        assert_eq!(
+            tree.memo_id("/notes"),
+            Err(MemoTreeError::IsADirectory("/notes".to_string()))
+        );
+    }
+
+    #[test]
+    fn rejects_name_collisions() {
+        let mut tree = tree();
+        assert_eq!(
+            tree.create("/notes", NodeKind::Folder),
+            Err(MemoTreeError::AlreadyExists("/notes".to_string()))
+        );
+        assert_eq!(
+            tree.create("/a/b", NodeKind::Folder),
+            Err(MemoTreeError::NotFound("/a".to_string()))
+        );
+        assert_eq!(
+            tree.create_folder_all("/memo1/x"),
+            Err(MemoTreeError::AlreadyExists("/memo1".to_string()))
+        );
+        assert_eq!(tree.create_folder_all("/notes/daily"), Ok(Some(3)));
+    }
+
+    #[test]
+    fn moves_with_cycle_detection() {
+        let mut tree = tree();
+        tree.move_path("/memo1", "/notes").unwrap();
+        assert_eq!(tree.path_of(1).as_deref(), Some("/notes/memo1"));
+        tree.move_path("/notes/memo1", "/renamed").unwrap();
+        assert_eq!(tree.memo_id("/renamed"), Ok(1));
+
+        assert_eq!(
+            tree.move_path("/renamed", "/notes/memo2"),
+            Err(MemoTreeError::AlreadyExists("/notes/memo2".to_string()))
+        );
+        assert_eq!(
+            tree.move_path("/notes", "/notes/daily"),
+            Err(MemoTreeError::Cycle {
+                from: "/notes".to_string(),
+                to: "/notes/daily/notes".to_string()
+            })
+        );
+        assert_eq!(
+            tree.move_node(2, Some(2), "notes"),
+            Err(MemoTreeError::Cycle {
+                from: "/notes".to_string(),
+                to: "/notes/notes".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn copies_and_removes_subtrees() {
+        let mut tree = tree();
+        let memo2 = tree.memo_id("/notes/memo2").unwrap();
+        tree.update_memo(memo2, 42).unwrap();
+        let copied = tree.copy_path("/notes", "/backup").unwrap();
+        assert_eq!(names(&tree, "/backup"), vec!["daily", "memo2"]);
+        assert_ne!(tree.memo_id("/backup/memo2"), tree.memo_id("/notes/memo2"));
+        let copy2 = tree.memo_id("/backup/memo2").unwrap();
+        assert!(copied.contains(&(copy2, memo2)));
+        assert_eq!(tree.get(copy2).map(|node| node.size), Some(42));
+        assert_eq!(copied.len(), 3);
+
+        let copy = copied[0].0;
+
+        let removed = tree.remove(copy).unwrap();
+        assert_eq!(removed.len(), 3);
+        assert!(!tree.exists("/backup"));
+        assert_eq!(tree.remove(copy), Err(MemoTreeError::UnknownId(copy)));
+    }
+
+    #[test]
+    fn round_trips_as_node_list() {
+        let tree = tree();
+        let json = serde_json::to_value(&tree).unwrap();
+        assert_eq!(json.as_array().map(Vec::len), Some(4));
+        assert_eq!(json[1]["kind"], "folder");
+        assert_eq!(json[2]["parent_id"], 2);
+
+        let mut restored: MemoTree = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, tree);
+        // idは保存したノードの続きから振る
+        assert_eq!(restored.create("/memo3", NodeKind::Memo), Ok(5));
+        restored.update_memo(5, 42).unwrap();
+        assert_eq!(restored.get(5).map(|node| node.size), Some(42));
+    }
+}
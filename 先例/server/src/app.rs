@@ -19,11 +19,18 @@ pub async fn create_app() -> Router {
     Router::new()
         .merge(routes::status::create_route())
         .merge(routes::user::create_route())
-        .merge(Router::new().nest(
-            "/v1",
-            // All public v1 routes will be nested here.
-            Router::new().merge(routes::cat::create_route()),
-        ))
+        .merge(
+            Router::new().nest(
+                "/v1",
+                // All public v1 routes will be nested here.
+                Router::new()
+                    .merge(routes::cat::create_route())
+                    .merge(routes::folder::create_route())
+                    .merge(routes::history::create_route())
+                    .merge(routes::memo::create_route())
+                    .merge(routes::search::create_route()),
+            ),
+        )
         // High level logging of requests and responses
         .layer(
             trace::TraceLayer::new_for_http()
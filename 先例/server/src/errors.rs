@@ -32,6 +32,9 @@ pub enum Error {
     #[error("{0}")]
     NotFound(#[from] NotFound),
 
+    #[error("{0}")]
+    Conflict(#[from] Conflict),
+
     #[error("{0}")]
     RunSyncTask(#[from] JoinError),
 
@@ -53,6 +56,7 @@ impl Error {
                 (StatusCode::UNAUTHORIZED, 40005)
             }
             Error::Authenticate(AuthenticateError::Locked) => (StatusCode::LOCKED, 40006),
+            Error::Conflict(_) => (StatusCode::CONFLICT, 40007),
 
             // 5XX Errors
             Error::Authenticate(AuthenticateError::TokenCreation) => {
@@ -73,6 +77,10 @@ impl Error {
     pub fn not_found() -> Self {
         Error::NotFound(NotFound {})
     }
+
+    pub fn conflict() -> Self {
+        Error::Conflict(Conflict {})
+    }
 }
 
 impl IntoResponse for Error {
@@ -105,3 +113,7 @@ pub struct BadRequest {}
 #[derive(thiserror::Error, Debug)]
 #[error("Not found")]
 pub struct NotFound {}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Conflict")]
+pub struct Conflict {}
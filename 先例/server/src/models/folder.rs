@@ -0,0 +1,116 @@
+use bson::serde_helpers::bson_datetime_as_rfc3339_string;
+use bson::serde_helpers::serialize_object_id_as_hex_string;
+use memo_tree::path::{join, segments};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use wither::bson::{doc, oid::ObjectId};
+use wither::Model as WitherModel;
+
+use crate::utils::date;
+use crate::utils::date::Date;
+use crate::utils::models::ModelExt;
+use crate::utils::search::escape_regex;
+
+impl ModelExt for Folder {}
+
+/// A folder of memos. `path` caches the absolute path (`/notes/daily`) so a
+/// whole subtree can be found with a single prefix query.
+#[derive(Debug, Clone, Serialize, Deserialize, WitherModel, Validate)]
+#[model(index(
+    keys = r#"doc!{ "user": 1, "parent": 1, "name": 1 }"#,
+    options = r#"doc!{ "unique": true }"#
+))]
+#[model(index(keys = r#"doc!{ "user": 1, "path": 1 }"#))]
+pub struct Folder {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user: ObjectId,
+    /// Parent folder, `None` for folders at the root.
+    pub parent: Option<ObjectId>,
+    #[validate(length(min = 1, max = 255), custom(function = "validate_name"))]
+    pub name: String,
+    pub path: String,
+    pub updated_at: Date,
+    pub created_at: Date,
+}
+
+impl Folder {
+    pub fn new(user: ObjectId, parent: Option<&Folder>, name: String) -> Self {
+        let now = date::now();
+        Self {
+            id: None,
+            user,
+            parent: parent.and_then(|parent| parent.id),
+            path: child_path(parent, &name),
+            name,
+            updated_at: now,
+            created_at: now,
+        }
+    }
+
+    /// Regex matching the paths of every folder below this one.
+    pub fn descendants_pattern(&self) -> String {
+        format!("^{}/", escape_regex(&self.path))
+    }
+}
+
+/// Path of a folder named `name` inside `parent` (the root when `None`).
+pub fn child_path(parent: Option<&Folder>, name: &str) -> String {
+    let mut path = parent.map_or_else(Vec::new, |parent| segments(&parent.path));
+    path.push(name);
+    join(&path)
+}
+
+/// Names are path segments, so they can't contain `/` or be `.` or `..`.
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    if name.contains('/') || name == "." || name == ".." {
+        return Err(ValidationError::new("folder_name"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicFolder {
+    #[serde(alias = "_id", serialize_with = "serialize_object_id_as_hex_string")]
+    pub id: ObjectId,
+    #[serde(serialize_with = "serialize_object_id_as_hex_string")]
+    pub user: ObjectId,
+    pub parent: Option<String>,
+    pub name: String,
+    pub path: String,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub updated_at: Date,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub created_at: Date,
+}
+
+impl From<Folder> for PublicFolder {
+    fn from(folder: Folder) -> Self {
+        Self {
+            id: folder.id.unwrap(),
+            user: folder.user,
+            parent: folder.parent.map(|parent| parent.to_hex()),
+            name: folder.name,
+            path: folder.path,
+            updated_at: folder.updated_at,
+            created_at: folder.created_at,
+        }
+    }
+}
+
+/// A folder with everything below it, as returned by the tree endpoint.
+/// The root of the user's tree has no `id`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FolderTree {
+    pub id: Option<String>,
+    pub name: String,
+    pub path: String,
+    pub folders: Vec<FolderTree>,
+    pub memos: Vec<MemoEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MemoEntry {
+    pub id: String,
+    pub title: String,
+}
@@ -0,0 +1,57 @@
+use bson::serde_helpers::bson_datetime_as_rfc3339_string;
+use bson::serde_helpers::serialize_object_id_as_hex_string;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use wither::bson::{doc, oid::ObjectId};
+use wither::Model as WitherModel;
+
+use crate::utils::date;
+use crate::utils::date::Date;
+use crate::utils::models::ModelExt;
+
+impl ModelExt for HistoryEntry {}
+
+/// A command line executed from the client's home shell.
+#[derive(Debug, Clone, Serialize, Deserialize, WitherModel, Validate)]
+#[model(index(keys = r#"doc!{ "user": 1, "created_at": 1 }"#))]
+pub struct HistoryEntry {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user: ObjectId,
+    #[validate(length(min = 1, max = 4096))]
+    pub line: String,
+    pub created_at: Date,
+}
+
+impl HistoryEntry {
+    pub fn new(user: ObjectId, line: String) -> Self {
+        Self {
+            id: None,
+            user,
+            line,
+            created_at: date::now(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicHistoryEntry {
+    #[serde(alias = "_id", serialize_with = "serialize_object_id_as_hex_string")]
+    pub id: ObjectId,
+    #[serde(serialize_with = "serialize_object_id_as_hex_string")]
+    pub user: ObjectId,
+    pub line: String,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub created_at: Date,
+}
+
+impl From<HistoryEntry> for PublicHistoryEntry {
+    fn from(entry: HistoryEntry) -> Self {
+        Self {
+            id: entry.id.unwrap(),
+            user: entry.user,
+            line: entry.line,
+            created_at: entry.created_at,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use bson::serde_helpers::bson_datetime_as_rfc3339_string;
+use bson::serde_helpers::serialize_object_id_as_hex_string;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use wither::bson::{doc, oid::ObjectId};
+use wither::Model as WitherModel;
+
+use crate::utils::date;
+use crate::utils::date::Date;
+use crate::utils::models::ModelExt;
+
+impl ModelExt for Memo {}
+
+/// A memo written in the client's editor. `lines` holds the body split by
+/// line, which is how the editor addresses its content.
+#[derive(Debug, Clone, Serialize, Deserialize, WitherModel, Validate)]
+#[model(index(keys = r#"doc!{ "user": 1, "folder": 1 }"#))]
+#[model(index(keys = r#"doc!{ "user": 1, "updated_at": -1 }"#))]
+pub struct Memo {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user: ObjectId,
+    /// Parent folder, `None` for memos at the root.
+    pub folder: Option<ObjectId>,
+    #[validate(length(min = 1, max = 255))]
+    pub title: String,
+    #[validate(length(max = 1_000_000))]
+    pub body: String,
+    pub lines: Vec<String>,
+    /// Removed memos are kept, flagged, so they can be restored.
+    pub deleted: bool,
+    /// Incremented on every update. Clients send back the version they
+    /// edited so an update based on a stale copy is rejected.
+    pub version: i64,
+    pub updated_at: Date,
+    pub created_at: Date,
+}
+
+impl Memo {
+    pub fn new(user: ObjectId, folder: Option<ObjectId>, title: String, body: String) -> Self {
+        let now = date::now();
+        Self {
+            id: None,
+            user,
+            folder,
+            title,
+            lines: split_lines(&body),
+            body,
+            deleted: false,
+            version: 1,
+            updated_at: now,
+            created_at: now,
+        }
+    }
+}
+
+pub fn split_lines(body: &str) -> Vec<String> {
+    body.lines().map(ToOwned::to_owned).collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicMemo {
+    #[serde(alias = "_id", serialize_with = "serialize_object_id_as_hex_string")]
+    pub id: ObjectId,
+    #[serde(serialize_with = "serialize_object_id_as_hex_string")]
+    pub user: ObjectId,
+    pub folder: Option<String>,
+    pub title: String,
+    pub body: String,
+    pub lines: Vec<String>,
+    pub version: i64,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub updated_at: Date,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub created_at: Date,
+}
+
+impl From<Memo> for PublicMemo {
+    fn from(memo: Memo) -> Self {
+        Self {
+            id: memo.id.unwrap(),
+            user: memo.user,
+            folder: memo.folder.map(|folder| folder.to_hex()),
+            title: memo.title,
+            body: memo.body,
+            lines: memo.lines,
+            version: memo.version,
+            updated_at: memo.updated_at,
+            created_at: memo.created_at,
+        }
+    }
+}
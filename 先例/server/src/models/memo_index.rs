@@ -0,0 +1,90 @@
+use bson::serde_helpers::bson_datetime_as_rfc3339_string;
+use bson::serde_helpers::serialize_object_id_as_hex_string;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use wither::bson::{doc, oid::ObjectId};
+use wither::mongodb::options::UpdateOptions;
+use wither::Model as WitherModel;
+
+use crate::errors::Error;
+use crate::utils::date;
+use crate::utils::date::Date;
+use crate::utils::models::ModelExt;
+use crate::utils::search::{index_terms, Snippet};
+
+impl ModelExt for MemoIndex {}
+
+/// Search index entry of a memo: its searchable text and the distinct terms
+/// built from it. The multikey index on `terms` is the inverted index.
+#[derive(Debug, Clone, Serialize, Deserialize, WitherModel, Validate)]
+#[model(index(keys = r#"doc!{ "user": 1, "terms": 1 }"#))]
+#[model(index(keys = r#"doc!{ "memo": 1 }"#, options = r#"doc!{ "unique": true }"#))]
+pub struct MemoIndex {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user: ObjectId,
+    pub memo: ObjectId,
+    pub title: String,
+    pub body: String,
+    pub terms: Vec<String>,
+    pub updated_at: Date,
+}
+
+impl MemoIndex {
+    pub fn new(user: ObjectId, memo: ObjectId, title: String, body: String) -> Self {
+        Self {
+            id: None,
+            user,
+            memo,
+            terms: index_terms(&title, &body),
+            title,
+            body,
+            updated_at: date::now(),
+        }
+    }
+
+    /// Creates or replaces the index entry of a memo.
+    pub async fn upsert(
+        user: ObjectId,
+        memo: ObjectId,
+        title: String,
+        body: String,
+    ) -> Result<(), Error> {
+        let entry = Self::new(user, memo, title, body);
+        let options = UpdateOptions::builder().upsert(true).build();
+        Self::update_one(
+            doc! { "memo": &memo },
+            doc! {
+                "$set": {
+                    "user": entry.user,
+                    "title": entry.title,
+                    "body": entry.body,
+                    "terms": entry.terms,
+                    "updated_at": entry.updated_at,
+                }
+            },
+            options,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn remove(memo: &ObjectId) -> Result<(), Error> {
+        Self::delete_one(doc! { "memo": memo }).await?;
+
+        Ok(())
+    }
+}
+
+/// A ranked search hit.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    #[serde(serialize_with = "serialize_object_id_as_hex_string")]
+    pub memo: ObjectId,
+    pub title: String,
+    pub score: f64,
+    pub snippet: Snippet,
+    #[serde(with = "bson_datetime_as_rfc3339_string")]
+    pub updated_at: Date,
+}
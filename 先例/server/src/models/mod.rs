@@ -1,4 +1,8 @@
 pub mod cat;
+pub mod folder;
+pub mod history;
+pub mod memo;
+pub mod memo_index;
 pub mod user;
 
 use crate::utils::models::ModelExt;
@@ -7,6 +11,10 @@ use crate::Error;
 pub async fn sync_indexes() -> Result<(), Error> {
     user::User::sync_indexes().await?;
     cat::Cat::sync_indexes().await?;
+    folder::Folder::sync_indexes().await?;
+    history::HistoryEntry::sync_indexes().await?;
+    memo::Memo::sync_indexes().await?;
+    memo_index::MemoIndex::sync_indexes().await?;
 
     Ok(())
 }
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use axum::{
+    extract::{Path, Query},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use bson::doc;
+use bson::oid::ObjectId;
+use serde::Deserialize;
+use tracing::debug;
+use validator::Validate;
+use wither::mongodb::options::FindOptions;
+
+use crate::errors::Error;
+use crate::models::folder::{Folder, FolderTree, MemoEntry, PublicFolder};
+use crate::models::memo::Memo;
+use crate::models::memo_index::MemoIndex;
+use crate::utils::custom_response::CustomResponseResult as Response;
+use crate::utils::custom_response::{CustomResponse, CustomResponseBuilder};
+use crate::utils::date;
+use crate::utils::models::ModelExt;
+use crate::utils::to_object_id::to_object_id;
+use crate::utils::token::TokenUser;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route("/folders", post(create_folder))
+        .route("/folders/tree", get(get_folder_tree))
+        .route("/folders/{id}", get(get_folder_by_id))
+        .route("/folders/{id}", delete(remove_folder_by_id))
+        .route("/folders/{id}", put(update_folder_by_id))
+}
+
+async fn create_folder(user: TokenUser, Json(payload): Json<SaveFolder>) -> Response<PublicFolder> {
+    let parent = find_parent(&user.id, payload.parent).await?;
+    let folder = Folder::new(user.id, parent.as_ref(), payload.name);
+    ensure_unique_name(&folder, None).await?;
+    let folder = Folder::create(folder).await?;
+    let res = PublicFolder::from(folder);
+
+    let res = CustomResponseBuilder::new()
+        .body(res)
+        .status_code(StatusCode::CREATED)
+        .build();
+
+    Ok(res)
+}
+
+/// The whole tree below `?root=<id>` (or the user's root) in one call:
+/// folders and memos, both sorted by name.
+async fn get_folder_tree(
+    user: TokenUser,
+    Query(params): Query<TreeParams>,
+) -> Result<Json<FolderTree>, Error> {
+    let root = match params.root {
+        Some(id) => Some(find_folder(&user.id, to_object_id(id)?).await?),
+        None => None,
+    };
+
+    let mut folder_query = doc! { "user": &user.id };
+    let mut memo_query = doc! { "user": &user.id, "deleted": false };
+    if let Some(root) = &root {
+        folder_query.insert("path", doc! { "$regex": root.descendants_pattern() });
+    }
+    let folders = Folder::find(folder_query, None).await?;
+    if let Some(root) = &root {
+        let ids = root
+            .id
+            .iter()
+            .chain(folders.iter().filter_map(|folder| folder.id.as_ref()))
+            .collect::<Vec<&ObjectId>>();
+        memo_query.insert("folder", doc! { "$in": ids });
+    }
+    let options = FindOptions::builder().sort(doc! { "title": 1_i32 }).build();
+    let memos = Memo::find(memo_query, options).await?;
+
+    let mut children: HashMap<Option<ObjectId>, Vec<Folder>> = HashMap::new();
+    for folder in folders {
+        children.entry(folder.parent).or_default().push(folder);
+    }
+    let mut entries: HashMap<Option<ObjectId>, Vec<MemoEntry>> = HashMap::new();
+    for memo in memos {
+        entries.entry(memo.folder).or_default().push(MemoEntry {
+            id: memo.id.unwrap().to_hex(),
+            title: memo.title,
+        });
+    }
+
+    let tree = match root {
+        Some(root) => build_tree(root.id, root.name, root.path, &mut children, &mut entries),
+        None => build_tree(
+            None,
+            String::new(),
+            "/".to_owned(),
+            &mut children,
+            &mut entries,
+        ),
+    };
+
+    debug!("Returning folder tree");
+    Ok(Json(tree))
+}
+
+fn build_tree(
+    id: Option<ObjectId>,
+    name: String,
+    path: String,
+    children: &mut HashMap<Option<ObjectId>, Vec<Folder>>,
+    entries: &mut HashMap<Option<ObjectId>, Vec<MemoEntry>>,
+) -> FolderTree {
+    let mut folders = children.remove(&id).unwrap_or_default();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    let folders = folders
+        .into_iter()
+        .map(|folder| build_tree(folder.id, folder.name, folder.path, children, entries))
+        .collect();
+
+    FolderTree {
+        id: id.map(|id| id.to_hex()),
+        name,
+        path,
+        folders,
+        memos: entries.remove(&id).unwrap_or_default(),
+    }
+}
+
+async fn get_folder_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+) -> Result<Json<PublicFolder>, Error> {
+    let folder = find_folder(&user.id, to_object_id(id)?).await?;
+
+    debug!("Returning folder");
+    Ok(Json(PublicFolder::from(folder)))
+}
+
+/// Deletes a folder. A folder with folders or memos inside is refused with
+/// 409 unless `?recursive=true`, which also removes everything below it.
+async fn remove_folder_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+    Query(params): Query<RemoveParams>,
+) -> Result<CustomResponse<()>, Error> {
+    let folder = find_folder(&user.id, to_object_id(id)?).await?;
+    let descendants = Folder::find(
+        doc! { "user": &user.id, "path": { "$regex": folder.descendants_pattern() } },
+        None,
+    )
+    .await?;
+    let folder_ids = folder
+        .id
+        .into_iter()
+        .chain(descendants.iter().filter_map(|folder| folder.id))
+        .collect::<Vec<ObjectId>>();
+    let memo_ids = Memo::find(
+        doc! { "user": &user.id, "deleted": false, "folder": { "$in": &folder_ids } },
+        None,
+    )
+    .await?
+    .into_iter()
+    .filter_map(|memo| memo.id)
+    .collect::<Vec<ObjectId>>();
+
+    if !params.recursive && (!descendants.is_empty() || !memo_ids.is_empty()) {
+        debug!("Folder is not empty, returning 409 status code");
+        return Err(Error::conflict());
+    }
+
+    if !memo_ids.is_empty() {
+        Memo::update_many(
+            doc! { "_id": { "$in": &memo_ids } },
+            doc! { "$set": { "deleted": true, "updated_at": date::now() } },
+            None,
+        )
+        .await?;
+        MemoIndex::delete_many(doc! { "memo": { "$in": &memo_ids } }).await?;
+    }
+    Folder::delete_many(doc! { "_id": { "$in": &folder_ids } }).await?;
+
+    let res = CustomResponseBuilder::new()
+        .status_code(StatusCode::NO_CONTENT)
+        .build();
+
+    Ok(res)
+}
+
+/// Renames and/or moves a folder, rewriting the cached path of every folder
+/// below it.
+async fn update_folder_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+    Json(payload): Json<SaveFolder>,
+) -> Result<Json<PublicFolder>, Error> {
+    let folder = find_folder(&user.id, to_object_id(id)?).await?;
+    let parent = find_parent(&user.id, payload.parent).await?;
+    if let Some(parent) = &parent {
+        if parent.id == folder.id || parent.path.starts_with(&format!("{}/", folder.path)) {
+            debug!("Folder can't be moved into itself, returning 400 status code");
+            return Err(Error::bad_request());
+        }
+    }
+
+    let moved = Folder::new(user.id, parent.as_ref(), payload.name);
+    moved.validate().map_err(|_error| Error::bad_request())?;
+    ensure_unique_name(&moved, folder.id).await?;
+
+    let descendants = Folder::find(
+        doc! { "user": &user.id, "path": { "$regex": folder.descendants_pattern() } },
+        None,
+    )
+    .await?;
+    let updated = Folder::find_one_and_update(
+        doc! { "_id": &folder.id, "user": &user.id },
+        doc! {
+            "$set": {
+                "parent": moved.parent,
+                "name": &moved.name,
+                "path": &moved.path,
+                "updated_at": date::now(),
+            }
+        },
+    )
+    .await?
+    .ok_or_else(Error::not_found)?;
+
+    for descendant in descendants {
+        let path = format!("{}{}", updated.path, &descendant.path[folder.path.len()..]);
+        Folder::update_one(
+            doc! { "_id": &descendant.id },
+            doc! { "$set": { "path": path } },
+            None,
+        )
+        .await?;
+    }
+
+    debug!("Returning folder");
+    Ok(Json(PublicFolder::from(updated)))
+}
+
+async fn find_folder(user: &ObjectId, id: ObjectId) -> Result<Folder, Error> {
+    match Folder::find_one(doc! { "_id": id, "user": user }, None).await? {
+        Some(folder) => Ok(folder),
+        None => {
+            debug!("Folder not found, returning 404 status code");
+            Err(Error::not_found())
+        }
+    }
+}
+
+/// `None` is the root.
+async fn find_parent(user: &ObjectId, parent: Option<String>) -> Result<Option<Folder>, Error> {
+    match parent {
+        Some(id) => Ok(Some(find_folder(user, to_object_id(id)?).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Checked before writing to answer with 409 instead of the duplicate key
+/// error of the unique index, which still guards against races.
+async fn ensure_unique_name(folder: &Folder, except: Option<ObjectId>) -> Result<(), Error> {
+    let mut query = doc! {
+        "user": &folder.user,
+        "parent": folder.parent,
+        "name": &folder.name,
+    };
+    if let Some(id) = except {
+        query.insert("_id", doc! { "$ne": id });
+    }
+
+    if Folder::exists(query).await? {
+        debug!("Folder name already exists, returning 409 status code");
+        return Err(Error::conflict());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct SaveFolder {
+    parent: Option<String>,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct TreeParams {
+    root: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoveParams {
+    #[serde(default)]
+    recursive: bool,
+}
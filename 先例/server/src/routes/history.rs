@@ -0,0 +1,85 @@
+use axum::http::StatusCode;
+use axum::{
+    routing::{delete, get, post},
+    Json, Router,
+};
+use bson::doc;
+use serde::Deserialize;
+use tracing::debug;
+use wither::mongodb::options::FindOptions;
+
+use crate::errors::Error;
+use crate::models::history::{HistoryEntry, PublicHistoryEntry};
+use crate::utils::custom_response::CustomResponseResult as Response;
+use crate::utils::custom_response::{CustomResponse, CustomResponseBuilder, ResponsePagination};
+use crate::utils::models::ModelExt;
+use crate::utils::pagination::Pagination;
+use crate::utils::token::TokenUser;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route("/history", post(create_history_entry))
+        .route("/history", get(query_history))
+        .route("/history", delete(clear_history))
+}
+
+async fn create_history_entry(
+    user: TokenUser,
+    Json(payload): Json<CreateHistoryEntry>,
+) -> Response<PublicHistoryEntry> {
+    let entry = HistoryEntry::new(user.id, payload.line);
+    let entry = HistoryEntry::create(entry).await?;
+    let res = PublicHistoryEntry::from(entry);
+
+    let res = CustomResponseBuilder::new()
+        .body(res)
+        .status_code(StatusCode::CREATED)
+        .build();
+
+    Ok(res)
+}
+
+/// Newest entries first, so the first page holds the most recent commands.
+async fn query_history(
+    user: TokenUser,
+    pagination: Pagination,
+) -> Response<Vec<PublicHistoryEntry>> {
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1_i32, "_id": -1_i32 })
+        .skip(pagination.offset)
+        .limit(pagination.limit as i64)
+        .build();
+
+    let (entries, count) = HistoryEntry::find_and_count(doc! { "user": &user.id }, options).await?;
+    let entries = entries
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<PublicHistoryEntry>>();
+
+    let res = CustomResponseBuilder::new()
+        .body(entries)
+        .pagination(ResponsePagination {
+            count,
+            offset: pagination.offset,
+            limit: pagination.limit,
+        })
+        .build();
+
+    debug!("Returning history");
+    Ok(res)
+}
+
+async fn clear_history(user: TokenUser) -> Result<CustomResponse<()>, Error> {
+    HistoryEntry::delete_many(doc! { "user": &user.id }).await?;
+
+    let res = CustomResponseBuilder::new()
+        .status_code(StatusCode::NO_CONTENT)
+        .build();
+
+    Ok(res)
+}
+
+#[derive(Deserialize)]
+struct CreateHistoryEntry {
+    line: String,
+}
@@ -0,0 +1,228 @@
+use axum::http::StatusCode;
+use axum::{
+    extract::{Path, Query},
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use bson::doc;
+use bson::oid::ObjectId;
+use serde::Deserialize;
+use tracing::debug;
+use validator::Validate;
+use wither::mongodb::options::FindOptions;
+
+use crate::errors::Error;
+use crate::models::folder::Folder;
+use crate::models::memo::{split_lines, Memo, PublicMemo};
+use crate::models::memo_index::MemoIndex;
+use crate::utils::custom_response::CustomResponseResult as Response;
+use crate::utils::custom_response::{CustomResponse, CustomResponseBuilder, ResponsePagination};
+use crate::utils::date;
+use crate::utils::models::ModelExt;
+use crate::utils::pagination::Pagination;
+use crate::utils::to_object_id::to_object_id;
+use crate::utils::token::TokenUser;
+
+pub fn create_route() -> Router {
+    Router::new()
+        .route("/memos", post(create_memo))
+        .route("/memos", get(query_memos))
+        .route("/memos/{id}", get(get_memo_by_id))
+        .route("/memos/{id}", delete(remove_memo_by_id))
+        .route("/memos/{id}", put(update_memo_by_id))
+}
+
+async fn create_memo(user: TokenUser, Json(payload): Json<CreateMemo>) -> Response<PublicMemo> {
+    let folder = find_folder_id(&user.id, payload.folder).await?;
+    let memo = Memo::new(user.id, folder, payload.title, payload.body);
+    let memo = Memo::create(memo).await?;
+    MemoIndex::upsert(
+        memo.user,
+        memo.id.unwrap(),
+        memo.title.clone(),
+        memo.body.clone(),
+    )
+    .await?;
+    let res = PublicMemo::from(memo);
+
+    let res = CustomResponseBuilder::new()
+        .body(res)
+        .status_code(StatusCode::CREATED)
+        .build();
+
+    Ok(res)
+}
+
+/// Most recently edited memos first. `?folder=<id>` lists a single folder.
+async fn query_memos(
+    user: TokenUser,
+    Query(filter): Query<MemoFilter>,
+    pagination: Pagination,
+) -> Response<Vec<PublicMemo>> {
+    let mut query = doc! { "user": &user.id, "deleted": false };
+    if let Some(folder) = filter.folder {
+        query.insert("folder", to_object_id(folder)?);
+    }
+
+    let options = FindOptions::builder()
+        .sort(doc! { "updated_at": -1_i32, "_id": -1_i32 })
+        .skip(pagination.offset)
+        .limit(pagination.limit as i64)
+        .build();
+
+    let (memos, count) = Memo::find_and_count(query, options).await?;
+    let memos = memos
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<PublicMemo>>();
+
+    let res = CustomResponseBuilder::new()
+        .body(memos)
+        .pagination(ResponsePagination {
+            count,
+            offset: pagination.offset,
+            limit: pagination.limit,
+        })
+        .build();
+
+    debug!("Returning memos");
+    Ok(res)
+}
+
+async fn get_memo_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+) -> Result<Json<PublicMemo>, Error> {
+    let memo_id = to_object_id(id)?;
+    let memo = Memo::find_one(
+        doc! { "_id": memo_id, "user": &user.id, "deleted": false },
+        None,
+    )
+    .await?
+    .map(PublicMemo::from);
+
+    let memo = match memo {
+        Some(memo) => memo,
+        None => {
+            debug!("Memo not found, returning 404 status code");
+            return Err(Error::not_found());
+        }
+    };
+
+    debug!("Returning memo");
+    Ok(Json(memo))
+}
+
+/// Soft delete: the memo is flagged and dropped from the search index.
+async fn remove_memo_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+) -> Result<CustomResponse<()>, Error> {
+    let memo_id = to_object_id(id)?;
+    let memo = Memo::find_one_and_update(
+        doc! { "_id": &memo_id, "user": &user.id, "deleted": false },
+        doc! { "$set": { "deleted": true, "updated_at": date::now() } },
+    )
+    .await?;
+
+    if memo.is_none() {
+        debug!("Memo not found, returning 404 status code");
+        return Err(Error::not_found());
+    }
+    MemoIndex::remove(&memo_id).await?;
+
+    let res = CustomResponseBuilder::new()
+        .status_code(StatusCode::NO_CONTENT)
+        .build();
+
+    Ok(res)
+}
+
+/// When `version` is given the update only applies to that version of the
+/// memo. A client editing a stale copy gets 409 and has to merge first.
+async fn update_memo_by_id(
+    user: TokenUser,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateMemo>,
+) -> Result<Json<PublicMemo>, Error> {
+    let memo_id = to_object_id(id)?;
+    payload.validate().map_err(|_error| Error::bad_request())?;
+    let folder = find_folder_id(&user.id, payload.folder).await?;
+
+    let query = doc! { "_id": &memo_id, "user": &user.id, "deleted": false };
+    let mut filter = query.clone();
+    if let Some(version) = payload.version {
+        filter.insert("version", version);
+    }
+    let memo = Memo::find_one_and_update(
+        filter,
+        doc! {
+            "$set": {
+                "folder": folder,
+                "title": payload.title.clone(),
+                "lines": split_lines(&payload.body),
+                "body": payload.body.clone(),
+                "updated_at": date::now(),
+            },
+            "$inc": { "version": 1_i64 },
+        },
+    )
+    .await?;
+
+    let memo = match memo {
+        Some(memo) => memo,
+        None if payload.version.is_some() && Memo::exists(query).await? => {
+            debug!("Memo was updated by another client, returning 409 status code");
+            return Err(Error::conflict());
+        }
+        None => {
+            debug!("Memo not found, returning 404 status code");
+            return Err(Error::not_found());
+        }
+    };
+    MemoIndex::upsert(memo.user, memo_id, memo.title.clone(), memo.body.clone()).await?;
+
+    debug!("Returning memo");
+    Ok(Json(PublicMemo::from(memo)))
+}
+
+/// A memo can only be put in one of the user's folders. `None` is the root.
+async fn find_folder_id(
+    user: &ObjectId,
+    folder: Option<String>,
+) -> Result<Option<ObjectId>, Error> {
+    let Some(folder) = folder else {
+        return Ok(None);
+    };
+    let folder = to_object_id(folder)?;
+    if !Folder::exists(doc! { "_id": folder, "user": user }).await? {
+        debug!("Folder not found, returning 404 status code");
+        return Err(Error::not_found());
+    }
+    Ok(Some(folder))
+}
+
+#[derive(Deserialize)]
+struct CreateMemo {
+    folder: Option<String>,
+    title: String,
+    #[serde(default)]
+    body: String,
+}
+
+#[derive(Deserialize, Validate)]
+struct UpdateMemo {
+    folder: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    title: String,
+    #[validate(length(max = 1_000_000))]
+    #[serde(default)]
+    body: String,
+    /// The version the client edited, see `update_memo_by_id`.
+    version: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct MemoFilter {
+    folder: Option<String>,
+}
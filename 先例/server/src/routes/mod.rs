@@ -1,3 +1,7 @@
 pub mod cat;
+pub mod folder;
+pub mod history;
+pub mod memo;
+pub mod search;
 pub mod status;
 pub mod user;
@@ -0,0 +1,90 @@
+use axum::{extract::Query, routing::get, Router};
+use bson::{doc, Document};
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::errors::Error;
+use crate::models::memo_index::{MemoIndex, SearchResult};
+use crate::utils::custom_response::CustomResponseResult as Response;
+use crate::utils::custom_response::{CustomResponseBuilder, ResponsePagination};
+use crate::utils::models::ModelExt;
+use crate::utils::pagination::Pagination;
+use crate::utils::search::{escape_regex, SearchQuery};
+use crate::utils::token::TokenUser;
+
+pub fn create_route() -> Router {
+    Router::new().route("/search", get(search_memos))
+}
+
+/// Candidates are narrowed down with the terms index, then ranked in memory so
+/// phrases and term frequencies can be taken into account.
+async fn search_memos(
+    user: TokenUser,
+    Query(params): Query<SearchParams>,
+    pagination: Pagination,
+) -> Response<Vec<SearchResult>> {
+    let query = SearchQuery::parse(&params.q);
+    if query.is_empty() {
+        debug!("Empty search query, returning 400 status code");
+        return Err(Error::bad_request());
+    }
+
+    let exact = query
+        .terms
+        .iter()
+        .filter(|term| !term.prefix)
+        .map(|term| term.text.as_str())
+        .collect::<Vec<&str>>();
+    let mut conditions: Vec<Document> = Vec::new();
+    if !exact.is_empty() {
+        conditions.push(doc! { "terms": { "$all": exact } });
+    }
+    for term in query.terms.iter().filter(|term| term.prefix) {
+        let pattern = format!("^{}", escape_regex(&term.text));
+        conditions.push(doc! { "terms": { "$regex": pattern } });
+    }
+
+    let candidates = MemoIndex::find(doc! { "user": &user.id, "$and": conditions }, None).await?;
+    let mut results = candidates
+        .into_iter()
+        .filter_map(|entry| {
+            let score = query.score(&entry.title, &entry.body)?;
+            Some(SearchResult {
+                memo: entry.memo,
+                snippet: query.snippet(&entry.body),
+                title: entry.title,
+                score,
+                updated_at: entry.updated_at,
+            })
+        })
+        .collect::<Vec<SearchResult>>();
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.updated_at.cmp(&a.updated_at))
+    });
+
+    let count = results.len() as u64;
+    let results = results
+        .into_iter()
+        .skip(pagination.offset as usize)
+        .take(pagination.limit as usize)
+        .collect::<Vec<SearchResult>>();
+
+    let res = CustomResponseBuilder::new()
+        .body(results)
+        .pagination(ResponsePagination {
+            count,
+            offset: pagination.offset,
+            limit: pagination.limit,
+        })
+        .build();
+
+    debug!("Returning search results");
+    Ok(res)
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+}
@@ -0,0 +1,267 @@
+use bson::doc;
+use reqwest;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::models::folder::Folder;
+use crate::models::folder::FolderTree;
+use crate::models::folder::PublicFolder;
+use crate::models::memo::Memo;
+use crate::tests::setup::use_app;
+use crate::tests::utils::create_user;
+use crate::tests::utils::create_user_token;
+use crate::utils::models::ModelExt;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Body {
+    parent: Option<String>,
+    name: String,
+}
+
+#[test]
+fn post_folder_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let notes = Folder::new(user.id.unwrap(), None, "notes".to_owned());
+        let notes = Folder::create(notes).await.unwrap();
+
+        let body = Body {
+            parent: Some(notes.id.unwrap().to_hex()),
+            name: "daily".to_owned(),
+        };
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post("http://localhost:8088/v1/folders")
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::CREATED;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicFolder>().await.unwrap();
+        assert_eq!(body.name, "daily");
+        assert_eq!(body.path, "/notes/daily");
+        assert_eq!(body.parent, Some(notes.id.unwrap().to_hex()));
+        assert_eq!(body.user, user.id.unwrap(), "Folder should belong to user");
+    });
+}
+
+#[test]
+fn post_folder_with_existing_name_route() {
+    let body = Body {
+        parent: None,
+        name: "notes".to_owned(),
+    };
+
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let notes = Folder::new(user.id.unwrap(), None, "notes".to_owned());
+        Folder::create(notes).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post("http://localhost:8088/v1/folders")
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::CONFLICT;
+        assert_eq!(actual, expected);
+    });
+}
+
+#[test]
+fn get_folder_tree_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+        let user_id = user.id.unwrap();
+
+        let notes = Folder::new(user_id, None, "notes".to_owned());
+        let notes = Folder::create(notes).await.unwrap();
+        let daily = Folder::new(user_id, Some(&notes), "daily".to_owned());
+        let daily = Folder::create(daily).await.unwrap();
+
+        let todo = Memo::new(user_id, None, "todo".to_owned(), "".to_owned());
+        Memo::create(todo).await.unwrap();
+        let monday = Memo::new(user_id, daily.id, "monday".to_owned(), "".to_owned());
+        Memo::create(monday).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/folders/tree")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let root = res.json::<FolderTree>().await.unwrap();
+        assert_eq!(root.path, "/");
+        assert_eq!(root.memos.len(), 1);
+        assert_eq!(root.memos.get(0).unwrap().title, "todo");
+
+        let notes = root.folders.get(0).unwrap();
+        assert_eq!(notes.name, "notes");
+        let daily = notes.folders.get(0).unwrap();
+        assert_eq!(daily.path, "/notes/daily");
+        assert_eq!(daily.memos.get(0).unwrap().title, "monday");
+
+        // Subtree:
+        let res = client
+            .get("http://localhost:8088/v1/folders/tree")
+            .query(&[("root", daily.id.clone().unwrap())])
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+        let subtree = res.json::<FolderTree>().await.unwrap();
+        assert_eq!(subtree.name, "daily");
+        assert_eq!(subtree.memos.len(), 1);
+        assert!(subtree.folders.is_empty());
+    });
+}
+
+#[test]
+fn update_folder_by_id_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+        let user_id = user.id.unwrap();
+
+        let notes = Folder::new(user_id, None, "notes".to_owned());
+        let notes = Folder::create(notes).await.unwrap();
+        let daily = Folder::new(user_id, Some(&notes), "daily".to_owned());
+        let daily = Folder::create(daily).await.unwrap();
+        let archive = Folder::new(user_id, None, "archive".to_owned());
+        let archive = Folder::create(archive).await.unwrap();
+
+        let body = Body {
+            parent: Some(archive.id.unwrap().to_hex()),
+            name: "old".to_owned(),
+        };
+
+        let client = reqwest::Client::new();
+        let res = client
+            .put(format!(
+                "http://localhost:8088/v1/folders/{}",
+                notes.id.unwrap()
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicFolder>().await.unwrap();
+        assert_eq!(body.path, "/archive/old");
+
+        // Descendants from the database
+        let daily = Folder::find_by_id(&daily.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(daily.path, "/archive/old/daily");
+
+        // Moving a folder into itself is refused
+        let body = Body {
+            parent: Some(daily.id.unwrap().to_hex()),
+            name: "old".to_owned(),
+        };
+        let res = client
+            .put(format!(
+                "http://localhost:8088/v1/folders/{}",
+                notes.id.unwrap()
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    });
+}
+
+#[test]
+fn remove_folder_by_id_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+        let user_id = user.id.unwrap();
+
+        let notes = Folder::new(user_id, None, "notes".to_owned());
+        let notes = Folder::create(notes).await.unwrap();
+        let daily = Folder::new(user_id, Some(&notes), "daily".to_owned());
+        let daily = Folder::create(daily).await.unwrap();
+        let monday = Memo::new(user_id, daily.id, "monday".to_owned(), "".to_owned());
+        let monday = Memo::create(monday).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let url = format!("http://localhost:8088/v1/folders/{}", notes.id.unwrap());
+
+        // Non-empty folders are refused:
+        let res = client
+            .delete(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        let res = client
+            .delete(&url)
+            .query(&[("recursive", "true")])
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::NO_CONTENT;
+        assert_eq!(actual, expected);
+
+        // Folders and memos from the database
+        let count = Folder::count(doc! { "user": user_id }).await.unwrap();
+        assert_eq!(count, 0, "Folders should be removed from the database");
+        let monday = Memo::find_by_id(&monday.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(monday.deleted, "Memos inside should be flagged as deleted");
+    });
+}
@@ -0,0 +1,120 @@
+use bson::doc;
+use reqwest;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::models::history::HistoryEntry;
+use crate::models::history::PublicHistoryEntry;
+use crate::tests::setup::use_app;
+use crate::tests::utils::create_user;
+use crate::tests::utils::create_user_token;
+use crate::utils::models::ModelExt;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[test]
+fn post_history_entry_route() {
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Body {
+        line: String,
+    }
+
+    let body = Body {
+        line: "vim memo1".to_owned(),
+    };
+
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post("http://localhost:8088/v1/history")
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::CREATED;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicHistoryEntry>().await.unwrap();
+        assert_eq!(body.line, "vim memo1");
+        assert_eq!(body.user, user.id.unwrap(), "Entry should belong to user");
+    });
+}
+
+#[test]
+fn get_history_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let ls = HistoryEntry::new(user.id.unwrap(), "ls".to_owned());
+        HistoryEntry::create(ls).await.unwrap();
+
+        let cd = HistoryEntry::new(user.id.unwrap(), "cd notes".to_owned());
+        HistoryEntry::create(cd).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/history")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Response pagination headers:
+        let headers = res.headers();
+        assert_eq!(headers.get("X-Pagination-Count").unwrap(), "2");
+
+        // Body (newest first):
+        let body = res.json::<Vec<PublicHistoryEntry>>().await.unwrap();
+        assert_eq!(body.len(), 2, "Should return two entries");
+        assert_eq!(body.get(0).unwrap().line, "cd notes");
+        assert_eq!(body.get(1).unwrap().line, "ls");
+    });
+}
+
+#[test]
+fn clear_history_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let ls = HistoryEntry::new(user.id.unwrap(), "ls".to_owned());
+        HistoryEntry::create(ls).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .delete("http://localhost:8088/v1/history")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::NO_CONTENT;
+        assert_eq!(actual, expected);
+
+        // History from the database
+        let count = HistoryEntry::count(doc! { "user": user.id.unwrap() })
+            .await
+            .unwrap();
+        assert_eq!(count, 0, "History should be removed from the database");
+    });
+}
@@ -0,0 +1,339 @@
+use bson::doc;
+use reqwest;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::models::memo::Memo;
+use crate::models::memo::PublicMemo;
+use crate::models::memo_index::MemoIndex;
+use crate::tests::setup::use_app;
+use crate::tests::utils::create_user;
+use crate::tests::utils::create_user_token;
+use crate::utils::models::ModelExt;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Body {
+    title: String,
+    body: String,
+}
+
+#[test]
+fn post_memo_route() {
+    let body = Body {
+        title: "Shopping".to_owned(),
+        body: "milk\neggs".to_owned(),
+    };
+
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post("http://localhost:8088/v1/memos")
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::CREATED;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicMemo>().await.unwrap();
+        assert_eq!(body.title, "Shopping");
+        assert_eq!(body.lines, vec!["milk", "eggs"]);
+        assert_eq!(body.folder, None);
+        assert_eq!(body.user, user.id.unwrap(), "Memo should belong to user");
+
+        // Search index
+        let indexed = MemoIndex::exists(doc! { "memo": body.id }).await.unwrap();
+        assert!(indexed, "Memo should be added to the search index");
+    });
+}
+
+#[test]
+fn post_memo_without_title_route() {
+    let body = Body {
+        title: "".to_owned(),
+        body: "milk".to_owned(),
+    };
+
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .post("http://localhost:8088/v1/memos")
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::BAD_REQUEST;
+        assert_eq!(actual, expected);
+    });
+}
+
+#[test]
+fn get_memos_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let shopping = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        );
+        Memo::create(shopping).await.unwrap();
+
+        let todo = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Todo".to_owned(),
+            "write tests".to_owned(),
+        );
+        Memo::create(todo).await.unwrap();
+
+        let mut removed = Memo::new(user.id.unwrap(), None, "Old".to_owned(), "".to_owned());
+        removed.deleted = true;
+        Memo::create(removed).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/memos")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Response headers:
+        let headers = res.headers();
+        assert_eq!(headers.get("Content-Type").unwrap(), "application/json");
+        // Response pagination headers:
+        assert_eq!(headers.get("X-Pagination-Count").unwrap(), "2");
+        assert_eq!(headers.get("X-Pagination-Offset").unwrap(), "0");
+        assert_eq!(headers.get("X-Pagination-Limit").unwrap(), "100");
+
+        // Body:
+        let body = res.json::<Vec<PublicMemo>>().await.unwrap();
+        assert_eq!(body.len(), 2, "Should return two memos");
+
+        // First memo (Todo):
+        let memo = body.get(0).unwrap();
+        assert_eq!(memo.title, "Todo");
+        assert_eq!(memo.user, user.id.unwrap());
+
+        // Second memo (Shopping):
+        let memo = body.get(1).unwrap();
+        assert_eq!(memo.title, "Shopping");
+        assert_eq!(memo.user, user.id.unwrap());
+    });
+}
+
+#[test]
+fn get_memo_by_id_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let shopping = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        );
+        let shopping = Memo::create(shopping).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get(format!(
+                "http://localhost:8088/v1/memos/{}",
+                shopping.id.unwrap()
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicMemo>().await.unwrap();
+        assert_eq!(body.title, "Shopping");
+        assert_eq!(body.body, "milk");
+        assert_eq!(body.user, user.id.unwrap());
+    });
+}
+
+#[test]
+fn update_memo_by_id_route() {
+    let body = Body {
+        title: "Groceries".to_owned(),
+        body: "milk\nbread".to_owned(),
+    };
+
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let shopping = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        );
+        let shopping = Memo::create(shopping).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .put(format!(
+                "http://localhost:8088/v1/memos/{}",
+                shopping.id.unwrap()
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<PublicMemo>().await.unwrap();
+        assert_eq!(body.title, "Groceries");
+        assert_eq!(body.lines, vec!["milk", "bread"]);
+        assert_eq!(body.version, 2, "Version should be incremented");
+
+        // Search index
+        let entry = MemoIndex::find_one(doc! { "memo": shopping.id.unwrap() }, None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.title, "Groceries");
+    });
+}
+
+#[test]
+fn update_memo_with_stale_version_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let shopping = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        );
+        let shopping = Memo::create(shopping).await.unwrap();
+        let url = format!("http://localhost:8088/v1/memos/{}", shopping.id.unwrap());
+
+        let client = reqwest::Client::new();
+        let update = |body: &'static str| {
+            client
+                .put(&url)
+                .header("Authorization", format!("Bearer {}", token))
+                .json(&serde_json::json!({ "title": "Shopping", "body": body, "version": 1 }))
+                .send()
+        };
+
+        // The first client edits version 1
+        let res = update("milk\nbread").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // The second client still has version 1
+        let res = update("milk\neggs").await.unwrap();
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+
+        // Memo from the database (the first update is kept)
+        let memo = Memo::find_by_id(&shopping.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(memo.body, "milk\nbread");
+        assert_eq!(memo.version, 2);
+    });
+}
+
+#[test]
+fn remove_memo_by_id_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let shopping = Memo::new(
+            user.id.unwrap(),
+            None,
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        );
+        let shopping = Memo::create(shopping).await.unwrap();
+        MemoIndex::upsert(
+            user.id.unwrap(),
+            shopping.id.unwrap(),
+            "Shopping".to_owned(),
+            "milk".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .delete(format!(
+                "http://localhost:8088/v1/memos/{}",
+                shopping.id.unwrap()
+            ))
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::NO_CONTENT;
+        assert_eq!(actual, expected);
+
+        // Memo from the database (kept, flagged as deleted)
+        let memo = Memo::find_by_id(&shopping.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(memo.deleted, "Memo should be flagged as deleted");
+
+        // Search index
+        let indexed = MemoIndex::exists(doc! { "memo": shopping.id.unwrap() })
+            .await
+            .unwrap();
+        assert!(!indexed, "Memo should be removed from the search index");
+    });
+}
@@ -1,3 +1,7 @@
 mod cat;
+mod folder;
+mod history;
+mod memo;
+mod search;
 mod status;
 mod user;
@@ -0,0 +1,147 @@
+use bson::oid::ObjectId;
+use reqwest;
+use reqwest::StatusCode;
+
+use crate::models::memo_index::{MemoIndex, SearchResult};
+use crate::tests::setup::use_app;
+use crate::tests::utils::create_user;
+use crate::tests::utils::create_user_token;
+
+#[cfg(test)]
+use pretty_assertions::assert_eq;
+
+#[test]
+fn search_memos_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+        let user_id = user.id.unwrap();
+
+        let rust = ObjectId::new();
+        MemoIndex::upsert(
+            user_id,
+            rust,
+            "Rust".to_owned(),
+            "Ownership and borrowing in rust".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let notes = ObjectId::new();
+        MemoIndex::upsert(
+            user_id,
+            notes,
+            "Notes".to_owned(),
+            "Learning rust today".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let other = create_user("other@test.com").await.unwrap();
+        MemoIndex::upsert(
+            other.id.unwrap(),
+            ObjectId::new(),
+            "Rust".to_owned(),
+            "Not mine".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/search?q=rust")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Response pagination headers:
+        let headers = res.headers();
+        assert_eq!(headers.get("X-Pagination-Count").unwrap(), "2");
+
+        // Body (title matches rank first):
+        let body = res.json::<Vec<SearchResult>>().await.unwrap();
+        assert_eq!(body.len(), 2, "Should only return the user's memos");
+        assert_eq!(body.get(0).unwrap().memo, rust);
+        assert_eq!(body.get(1).unwrap().memo, notes);
+        assert_eq!(body.get(1).unwrap().snippet.text, "Learning rust today");
+        assert_eq!(body.get(1).unwrap().snippet.highlights, vec![[9, 13]]);
+    });
+}
+
+#[test]
+fn search_memos_phrase_and_prefix_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+        let user_id = user.id.unwrap();
+
+        let tokyo = ObjectId::new();
+        MemoIndex::upsert(
+            user_id,
+            tokyo,
+            "旅行".to_owned(),
+            "東京都に行く memorandum".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let kyoto = ObjectId::new();
+        MemoIndex::upsert(
+            user_id,
+            kyoto,
+            "旅行".to_owned(),
+            "京都と東京に行く".to_owned(),
+        )
+        .await
+        .unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/search")
+            .query(&[("q", "\"東京都\" memo*")])
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::OK;
+        assert_eq!(actual, expected);
+
+        // Body:
+        let body = res.json::<Vec<SearchResult>>().await.unwrap();
+        assert_eq!(body.len(), 1, "Should only match the phrase");
+        assert_eq!(body.get(0).unwrap().memo, tokyo);
+    });
+}
+
+#[test]
+fn search_memos_empty_query_route() {
+    use_app(async move {
+        let user = create_user("nico@test.com").await.unwrap();
+        let token = create_user_token(user.clone()).await.unwrap();
+
+        let client = reqwest::Client::new();
+        let res = client
+            .get("http://localhost:8088/v1/search?q=%20")
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await
+            .unwrap();
+
+        // Status code:
+        let status_code = res.status();
+        let actual = status_code;
+        let expected = StatusCode::BAD_REQUEST;
+        assert_eq!(actual, expected);
+    });
+}
@@ -7,6 +7,10 @@ use tokio::sync::OnceCell;
 
 use crate::app::create_app;
 use crate::models::cat::Cat;
+use crate::models::folder::Folder;
+use crate::models::history::HistoryEntry;
+use crate::models::memo::Memo;
+use crate::models::memo_index::MemoIndex;
 use crate::models::user::User;
 use crate::settings::SETTINGS;
 use crate::utils::models::ModelExt;
@@ -41,6 +45,10 @@ where
         start_api_once().await;
 
         Cat::delete_many(doc! {}).await.unwrap();
+        Folder::delete_many(doc! {}).await.unwrap();
+        HistoryEntry::delete_many(doc! {}).await.unwrap();
+        Memo::delete_many(doc! {}).await.unwrap();
+        MemoIndex::delete_many(doc! {}).await.unwrap();
         User::delete_many(doc! {}).await.unwrap();
 
         test.await;
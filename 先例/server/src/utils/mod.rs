@@ -3,5 +3,6 @@ pub mod custom_response;
 pub mod date;
 pub mod models;
 pub mod pagination;
+pub mod search;
 pub mod to_object_id;
 pub mod token;
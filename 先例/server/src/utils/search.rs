@@ -0,0 +1,373 @@
+//! Tokenization, ranking and snippets for the memo search index.
+//!
+//! Latin words are indexed whole and lowercased. Runs of Japanese (or other
+//! CJK) characters have no spaces between words, so they are indexed as
+//! unigrams and bigrams instead; a query matches when every bigram of its
+//! run is present, which approximates a substring search.
+
+use serde::{Deserialize, Serialize};
+
+/// Characters of context kept around the first match in a snippet.
+const SNIPPET_CONTEXT: usize = 40;
+/// Maximum number of characters in a snippet, excluding the ellipses.
+const SNIPPET_LENGTH: usize = 160;
+/// Weight of a term found in the title compared to one in the body.
+const TITLE_WEIGHT: f64 = 3.0;
+/// Bonus for each quoted phrase the memo contains.
+const PHRASE_BONUS: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    /// Space separated words (latin letters, digits).
+    Word,
+    /// Scripts written without spaces (kana, kanji, hangul).
+    Ngram,
+}
+
+fn script(c: char) -> Option<Script> {
+    if is_cjk(c) {
+        Some(Script::Ngram)
+    } else if c.is_alphanumeric() || c == '_' {
+        Some(Script::Word)
+    } else {
+        None
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}' // Hiragana, Katakana
+        | '\u{3400}'..='\u{4DBF}' // CJK Extension A
+        | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+        | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+        | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+        | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+    )
+}
+
+/// Lowercases and turns every kind of whitespace (the client stores spaces as
+/// NBSP) into a plain space. Maps one char to one char, so char offsets in the
+/// normalized text are valid in the original.
+pub fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| {
+            if c.is_whitespace() {
+                ' '
+            } else {
+                c.to_lowercase().next().unwrap_or(c)
+            }
+        })
+        .collect()
+}
+
+/// Splits normalized text into runs of the same script.
+fn segments(text: &str) -> Vec<(Script, Vec<char>)> {
+    let mut segments: Vec<(Script, Vec<char>)> = Vec::new();
+    let mut current: Option<(Script, Vec<char>)> = None;
+    for c in normalize(text) {
+        match (script(c), current.as_mut()) {
+            (Some(kind), Some((current_kind, chars))) if kind == *current_kind => chars.push(c),
+            (kind, _) => {
+                segments.extend(current.take());
+                current = kind.map(|kind| (kind, vec![c]));
+            }
+        }
+    }
+    segments.extend(current);
+    segments
+}
+
+fn ngrams(chars: &[char], n: usize) -> impl Iterator<Item = String> + '_ {
+    chars.windows(n).map(|window| window.iter().collect())
+}
+
+/// Every term of a text, in order and with repetitions. Used both to build the
+/// index and to count term frequencies when ranking.
+pub fn terms(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (kind, chars) in segments(text) {
+        match kind {
+            Script::Word => terms.push(chars.into_iter().collect()),
+            Script::Ngram => {
+                terms.extend(ngrams(&chars, 1));
+                terms.extend(ngrams(&chars, 2));
+            }
+        }
+    }
+    terms
+}
+
+/// The distinct terms stored in the index for a memo.
+pub fn index_terms(title: &str, body: &str) -> Vec<String> {
+    let mut terms = terms(title);
+    terms.extend(self::terms(body));
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// A term a memo must contain to match a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    pub text: String,
+    /// Matches any indexed term starting with `text` (`memo*`).
+    pub prefix: bool,
+}
+
+/// A parsed search query.
+///
+/// Words are combined with AND, `"quoted text"` must appear as is and a
+/// trailing `*` matches words by prefix.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub terms: Vec<QueryTerm>,
+    /// Normalized phrases, with runs of whitespace collapsed to one space.
+    pub phrases: Vec<String>,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> SearchQuery {
+        let mut parsed = SearchQuery::default();
+        // Odd parts are inside quotes. An unterminated quote runs to the end.
+        for (idx, part) in query.split('"').enumerate() {
+            if idx % 2 == 1 {
+                let phrase = collapse_whitespace(&normalize(part));
+                if !phrase.is_empty() {
+                    parsed.add_terms(&phrase, false);
+                    parsed.phrases.push(phrase);
+                }
+                continue;
+            }
+            for word in part.split_whitespace() {
+                match word.strip_suffix('*') {
+                    Some(word) => parsed.add_terms(word, true),
+                    None => parsed.add_terms(word, false),
+                }
+            }
+        }
+        parsed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Only the last word of `text` can be a prefix; n-gram runs already
+    /// match inside words.
+    fn add_terms(&mut self, text: &str, prefix: bool) {
+        let segments = segments(text);
+        let last = segments.len().saturating_sub(1);
+        for (idx, (kind, chars)) in segments.into_iter().enumerate() {
+            let terms: Vec<QueryTerm> = match kind {
+                Script::Word => vec![QueryTerm {
+                    text: chars.into_iter().collect(),
+                    prefix: prefix && idx == last,
+                }],
+                Script::Ngram => {
+                    let n = if chars.len() == 1 { 1 } else { 2 };
+                    ngrams(&chars, n)
+                        .map(|text| QueryTerm {
+                            text,
+                            prefix: false,
+                        })
+                        .collect()
+                }
+            };
+            for term in terms {
+                if !self.terms.contains(&term) {
+                    self.terms.push(term);
+                }
+            }
+        }
+    }
+
+    /// Ranks a memo against the query. `None` when it doesn't match.
+    pub fn score(&self, title: &str, body: &str) -> Option<f64> {
+        let title_terms = terms(title);
+        let body_terms = terms(body);
+        let frequency = |terms: &[String], query: &QueryTerm| {
+            terms
+                .iter()
+                .filter(|term| {
+                    if query.prefix {
+                        term.starts_with(&query.text)
+                    } else {
+                        **term == query.text
+                    }
+                })
+                .count()
+        };
+
+        let mut score = 0.0;
+        for term in &self.terms {
+            let weighted = TITLE_WEIGHT * frequency(&title_terms, term) as f64
+                + frequency(&body_terms, term) as f64;
+            if weighted == 0.0 {
+                return None;
+            }
+            // Dampen repetitions so a single term can't dominate the ranking.
+            score += 1.0 + weighted.ln();
+        }
+
+        let title = collapse_whitespace(&normalize(title));
+        let body = collapse_whitespace(&normalize(body));
+        for phrase in &self.phrases {
+            if !title.contains(phrase.as_str()) && !body.contains(phrase.as_str()) {
+                return None;
+            }
+            score += PHRASE_BONUS;
+        }
+        Some(score)
+    }
+
+    /// Excerpt of `text` around the first match with every match highlighted.
+    pub fn snippet(&self, text: &str) -> Snippet {
+        let chars: Vec<char> = text.chars().collect();
+        let normalized = normalize(text);
+        let needles: Vec<Vec<char>> = self
+            .phrases
+            .iter()
+            .map(|phrase| phrase.chars().collect())
+            .chain(self.terms.iter().map(|term| term.text.chars().collect()))
+            .collect();
+
+        let mut matches: Vec<[usize; 2]> = Vec::new();
+        for needle in needles.iter().filter(|needle| !needle.is_empty()) {
+            let found = normalized
+                .windows(needle.len())
+                .enumerate()
+                .filter(|(_, window)| window == needle)
+                .map(|(start, _)| [start, start + needle.len()]);
+            matches.extend(found);
+        }
+        matches.sort();
+
+        let first = matches.first().map_or(0, |range| range[0]);
+        let start = first.saturating_sub(SNIPPET_CONTEXT);
+        let end = (start + SNIPPET_LENGTH).min(chars.len());
+        let leading = if start > 0 { "…" } else { "" };
+        let trailing = if end < chars.len() { "…" } else { "" };
+        let offset = leading.chars().count();
+
+        let mut highlights: Vec<[usize; 2]> = Vec::new();
+        for [from, to] in matches {
+            if from < start || from >= end {
+                continue;
+            }
+            let range = [from - start + offset, to.min(end) - start + offset];
+            match highlights.last_mut() {
+                Some(last) if range[0] <= last[1] => last[1] = last[1].max(range[1]),
+                _ => highlights.push(range),
+            }
+        }
+
+        let excerpt: String = chars[start..end]
+            .iter()
+            .map(|c| if c.is_whitespace() { ' ' } else { *c })
+            .collect();
+        Snippet {
+            text: format!("{leading}{excerpt}{trailing}"),
+            highlights,
+        }
+    }
+}
+
+/// A short excerpt of a memo for search results.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    pub text: String,
+    /// Highlighted `[start, end)` ranges, in chars of `text`.
+    pub highlights: Vec<[usize; 2]>,
+}
+
+fn collapse_whitespace(chars: &[char]) -> String {
+    let text: String = chars.iter().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Escapes `text` so it can be used literally inside a MongoDB `$regex`.
+pub fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn term(text: &str, prefix: bool) -> QueryTerm {
+        QueryTerm {
+            text: text.to_owned(),
+            prefix,
+        }
+    }
+
+    #[test]
+    fn indexes_words_and_ngrams() {
+        assert_eq!(
+            index_terms("Rust\u{00A0}memo", "東京都"),
+            vec!["memo", "rust", "京", "京都", "東", "東京", "都"]
+        );
+    }
+
+    #[test]
+    fn parses_phrases_and_prefixes() {
+        let query = SearchQuery::parse(r#"mem* "Hello  World" 東京都"#);
+        assert_eq!(
+            query.terms,
+            vec![
+                term("mem", true),
+                term("hello", false),
+                term("world", false),
+                term("東京", false),
+                term("京都", false),
+            ]
+        );
+        assert_eq!(query.phrases, vec!["hello world"]);
+        assert!(SearchQuery::parse(" \"\" ").is_empty());
+    }
+
+    #[test]
+    fn ranks_title_matches_higher() {
+        let query = SearchQuery::parse("rust");
+        let title = query.score("Rust notes", "nothing").unwrap();
+        let body = query.score("notes", "learning rust").unwrap();
+        assert!(title > body);
+        assert_eq!(query.score("notes", "nothing"), None);
+
+        let phrase = SearchQuery::parse(r#""hello world""#);
+        assert!(phrase.score("", "Hello\nworld").is_some());
+        assert_eq!(phrase.score("", "world hello"), None);
+        assert!(SearchQuery::parse("東京")
+            .score("", "東京都に行く")
+            .is_some());
+        assert!(SearchQuery::parse("memo*")
+            .score("", "memorandum")
+            .is_some());
+    }
+
+    #[test]
+    fn highlights_matches_in_snippet() {
+        let query = SearchQuery::parse("rust");
+        let snippet = query.snippet("I like Rust.\nrust is fast");
+        assert_eq!(snippet.text, "I like Rust. rust is fast");
+        assert_eq!(snippet.highlights, vec![[7, 11], [13, 17]]);
+
+        let long = format!("{}needle{}", "a ".repeat(50), " b".repeat(100));
+        let snippet = SearchQuery::parse("needle").snippet(&long);
+        assert!(snippet.text.starts_with('…') && snippet.text.ends_with('…'));
+        assert_eq!(snippet.highlights, vec![[41, 47]]);
+    }
+
+    #[test]
+    fn escapes_regex() {
+        assert_eq!(escape_regex("a.b*"), r"a\.b\*");
+    }
+}